@@ -367,7 +367,7 @@ impl GuestsValidator {
                         // Check if the response indicates success and includes a MAC
                         if let Some(returned_mac) = auth["mac"].as_str() {
                             // Check if the returned MAC is normalized to a particular format
-                            if returned_mac == &standard_mac {
+                            if *returned_mac == standard_mac {
                                 println!(
                                     "✅ Format accepted: {} - Controller returned standard format",
                                     description
@@ -503,7 +503,7 @@ impl GuestsValidator {
                         if auth["qos_usage_quota"].is_number() {
                             // Ensure QoS overwrite is enabled
                             if let Some(qos_overwritten) = auth["qos_overwrite"].as_bool() {
-                                if qos_overwritten == false {
+                                if !qos_overwritten {
                                     println!(
                                         "⚠️ Bytes = {}: {} accepted but QoS overwrite was disabled: {}",
                                         bytes, description, qos_overwritten
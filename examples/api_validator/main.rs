@@ -20,6 +20,11 @@ struct Cli {
     #[arg(short = 'p', long)]
     password: String,
 
+    /// Record all controller traffic to a JSON cassette
+    #[cfg(feature = "cassettes")]
+    #[arg(long)]
+    record: Option<std::path::PathBuf>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -35,16 +40,20 @@ enum Commands {
 async fn main() -> UniFiResult<()> {
     let cli = Cli::parse();
 
-    let unifi_client = UniFiClient::builder()
+    #[allow(unused_mut)]
+    let mut builder = UniFiClient::builder()
         .controller_url(&cli.controller_url)
         .username(&cli.username)
         .password(&cli.password)
         .site("default")
         // Dangerous, only use for lab environments or testing
-        .accept_invalid_certs(true)
-        .build()
-        .await
-        .expect("Failed to build UniFiClient");
+        .accept_invalid_certs(true);
+    #[cfg(feature = "cassettes")]
+    if let Some(cassette) = &cli.record {
+        builder = builder.record_cassette(cassette);
+    }
+
+    let unifi_client = builder.build().await.expect("Failed to build UniFiClient");
 
     match cli.command.unwrap_or(Commands::All) {
         Commands::Sites => {
@@ -34,7 +34,7 @@ impl SitesValidator {
 
     async fn validate_list_sites(&self) -> UniFiResult<()> {
         let client = self.client.clone();
-        let endpoint = format!("/api/stat/sites");
+        let endpoint = "/api/stat/sites".to_string();
 
         let sites: Value = client
             .request_json(Method::GET, &endpoint, None::<()>)
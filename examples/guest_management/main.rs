@@ -3,7 +3,6 @@ use std::error::Error;
 use std::io::{self, Write};
 
 use chrono::{TimeZone, Utc};
-use env_logger;
 use unifi_client::UniFiClient;
 
 #[tokio::main]
@@ -16,7 +15,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .unwrap_or_else(|_| "https://unifi.example.com:8443".to_string());
     let username = env::var("UNIFI_USERNAME").unwrap_or_else(|_| "admin".to_string());
     let site = env::var("UNIFI_SITE").unwrap_or_else(|_| "default".to_string());
-    let accept_invalid_certs = env::var("UNIFI_ACCEPT_INVALID_CERTS")
+    let accept_invalid_certs = env::var("UNIFI_TLS_ACCEPT_INVALID_CERTS")
         .map(|v| v.to_lowercase() == "true")
         .unwrap_or(false);
 
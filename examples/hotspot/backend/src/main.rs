@@ -15,7 +15,7 @@ use tower_http::cors::{Any, CorsLayer};
 use tower_http::services::ServeDir;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
-use unifi_client::{models, UniFiClient};
+use unifi_client::{models, UniFiClient, UniFiClientBuilder};
 use validator::{Validate, ValidateArgs, ValidationError};
 
 // Context struct for validation limits
@@ -121,10 +121,6 @@ struct GuestAuthResponse {
 struct AppConfig {
     frontend_dir: String,
     port: u16,
-    unifi_controller_url: String,
-    unifi_username: String,
-    unifi_site: String,
-    unifi_accept_invalid_certs: bool,
     max_duration_minutes: u32,
     max_data_quota_megabytes: u64,
 }
@@ -251,13 +247,9 @@ async fn main() {
         config
     );
 
-    // Create and initialize the UniFi client.
-    let unifi_client = UniFiClient::builder()
-        .controller_url(&config.unifi_controller_url)
-        .username(&config.unifi_username)
-        .password_from_env("UNIFI_PASSWORD")
-        .site(&config.unifi_site)
-        .accept_invalid_certs(config.unifi_accept_invalid_certs)
+    // Create and initialize the UniFi client from the `UNIFI_*` environment variables.
+    let unifi_client = UniFiClientBuilder::from_env("UNIFI")
+        .expect("Invalid UniFi client configuration")
         .build()
         .await
         .expect("Failed to build UniFiClient");
@@ -0,0 +1,434 @@
+use std::marker::PhantomData;
+
+use http::Method;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use url::Url;
+
+use crate::error::ControllerError;
+use crate::models::integration::{
+    ApplicationInfo, CreateVouchersRequest, CreateVouchersResponse, DeleteVouchersResponse, Device,
+    IntegrationErrorResponse, IntegrationSite, NetworkClient, Page, Voucher,
+};
+use crate::{UniFiClient, UniFiError, UniFiResult};
+
+/// Path prefix of the Integration API, relative to the client's API base URL.
+const INTEGRATION_PREFIX: &str = "/integration/v1";
+
+/// Provides typed access to the official UniFi Network Integration API (v1).
+///
+/// The Integration API is a documented, versioned API served by UniFi OS
+/// consoles under `/proxy/network/integration/v1`. It uses its own response
+/// envelope ([`Page`]) with `offset`/`limit` paging and identifies sites by
+/// UUID rather than by short name. It is typically used together with
+/// [`UniFiClientBuilder::api_key`](crate::UniFiClientBuilder::api_key).
+#[derive(Debug)]
+pub struct IntegrationHandler {
+    client: UniFiClient,
+}
+
+impl IntegrationHandler {
+    /// Creates a new Integration API instance.
+    ///
+    /// This method is intended for internal use by the UniFi client.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Reference to the UniFi client that will be used for API requests
+    pub(crate) fn new(client: UniFiClient) -> Self {
+        Self { client }
+    }
+
+    /// Retrieves information about the UniFi Network application.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use unifi_client::UniFiClient;
+    /// #
+    /// # async fn example(client: &UniFiClient) -> Result<(), unifi_client::UniFiError> {
+    /// let info = client.integration().info().await?;
+    /// println!("Network application {}", info.application_version);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn info(&self) -> UniFiResult<ApplicationInfo> {
+        let url = integration_url(&self.client, &["info"], &[])?;
+        send_integration_request(&self.client, Method::GET, url, None::<()>).await
+    }
+
+    /// Lists the sites visible to the authenticated user.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `ListBuilder` instance, which allows for setting paging and
+    /// filtering options before sending the request.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use unifi_client::UniFiClient;
+    /// #
+    /// # async fn example(client: &UniFiClient) -> Result<(), unifi_client::UniFiError> {
+    /// for site in client.integration().sites().send_all().await? {
+    ///     println!("{} ({})", site.name, site.id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn sites(&self) -> ListBuilder<IntegrationSite> {
+        ListBuilder::new(self.client.clone(), vec!["sites".into()])
+    }
+
+    /// Lists the devices adopted at a site.
+    ///
+    /// # Arguments
+    ///
+    /// * `site_id` - The site UUID (see [`IntegrationSite::id`])
+    pub fn devices(&self, site_id: impl Into<String>) -> ListBuilder<Device> {
+        ListBuilder::new(
+            self.client.clone(),
+            vec!["sites".into(), site_id.into(), "devices".into()],
+        )
+    }
+
+    /// Retrieves the details of a single device.
+    ///
+    /// # Arguments
+    ///
+    /// * `site_id` - The site UUID
+    /// * `device_id` - The device identifier
+    pub async fn device(&self, site_id: &str, device_id: &str) -> UniFiResult<Device> {
+        let url = integration_url(&self.client, &["sites", site_id, "devices", device_id], &[])?;
+        send_integration_request(&self.client, Method::GET, url, None::<()>).await
+    }
+
+    /// Lists the clients connected at a site.
+    ///
+    /// # Arguments
+    ///
+    /// * `site_id` - The site UUID
+    pub fn clients(&self, site_id: impl Into<String>) -> ListBuilder<NetworkClient> {
+        ListBuilder::new(
+            self.client.clone(),
+            vec!["sites".into(), site_id.into(), "clients".into()],
+        )
+    }
+
+    /// Lists the hotspot vouchers of a site.
+    ///
+    /// # Arguments
+    ///
+    /// * `site_id` - The site UUID
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use unifi_client::UniFiClient;
+    /// #
+    /// # async fn example(client: &UniFiClient, site_id: &str) -> Result<(), unifi_client::UniFiError> {
+    /// let page = client
+    ///     .integration()
+    ///     .vouchers(site_id)
+    ///     .limit(50)
+    ///     .filter("expired.eq(false)")
+    ///     .send()
+    ///     .await?;
+    /// println!("{} of {} vouchers", page.count, page.total_count);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn vouchers(&self, site_id: impl Into<String>) -> ListBuilder<Voucher> {
+        ListBuilder::new(
+            self.client.clone(),
+            vec![
+                "sites".into(),
+                site_id.into(),
+                "hotspot".into(),
+                "vouchers".into(),
+            ],
+        )
+    }
+
+    /// Retrieves a single hotspot voucher.
+    ///
+    /// # Arguments
+    ///
+    /// * `site_id` - The site UUID
+    /// * `voucher_id` - The voucher identifier
+    pub async fn voucher(&self, site_id: &str, voucher_id: &str) -> UniFiResult<Voucher> {
+        let url = integration_url(
+            &self.client,
+            &["sites", site_id, "hotspot", "vouchers", voucher_id],
+            &[],
+        )?;
+        send_integration_request(&self.client, Method::GET, url, None::<()>).await
+    }
+
+    /// Generates hotspot vouchers.
+    ///
+    /// # Arguments
+    ///
+    /// * `site_id` - The site UUID
+    /// * `time_limit_minutes` - How long guest access lasts once a voucher is used
+    ///
+    /// # Returns
+    ///
+    /// Returns a `CreateVouchersBuilder` instance, which allows for setting
+    /// optional parameters before sending the request.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use unifi_client::UniFiClient;
+    /// #
+    /// # async fn example(client: &UniFiClient, site_id: &str) -> Result<(), unifi_client::UniFiError> {
+    /// let vouchers = client
+    ///     .integration()
+    ///     .create_vouchers(site_id, 60)
+    ///     .count(10)
+    ///     .name("Conference")
+    ///     .data_quota_megabytes(500)
+    ///     .send()
+    ///     .await?;
+    /// for voucher in vouchers {
+    ///     println!("{}", voucher.code);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create_vouchers(
+        &self,
+        site_id: impl Into<String>,
+        time_limit_minutes: u32,
+    ) -> CreateVouchersBuilder {
+        CreateVouchersBuilder::new(self.client.clone(), site_id.into(), time_limit_minutes)
+    }
+
+    /// Deletes a single hotspot voucher.
+    ///
+    /// # Arguments
+    ///
+    /// * `site_id` - The site UUID
+    /// * `voucher_id` - The voucher identifier
+    pub async fn delete_voucher(&self, site_id: &str, voucher_id: &str) -> UniFiResult<()> {
+        let url = integration_url(
+            &self.client,
+            &["sites", site_id, "hotspot", "vouchers", voucher_id],
+            &[],
+        )?;
+        send_integration_request(&self.client, Method::DELETE, url, None::<()>)
+            .await
+            .map(|_: DeleteVouchersResponse| ())
+    }
+}
+
+/// Builder for paged Integration API list requests.
+#[derive(Debug, Clone)]
+pub struct ListBuilder<T> {
+    client: UniFiClient,
+    segments: Vec<String>,
+    offset: Option<u32>,
+    limit: Option<u32>,
+    filter: Option<String>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> ListBuilder<T>
+where
+    T: DeserializeOwned,
+{
+    pub(crate) fn new(client: UniFiClient, segments: Vec<String>) -> Self {
+        Self {
+            client,
+            segments,
+            offset: None,
+            limit: None,
+            filter: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Skip the first `offset` items. Defaults to `0`.
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Return at most `limit` items per page. The controller applies its own
+    /// default (25) and maximum (200) when unset or out of range.
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Filter results with an Integration API filter expression
+    /// (e.g., `name.like('AP*')`).
+    pub fn filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    /// Fetches a single page.
+    pub async fn send(self) -> UniFiResult<Page<T>> {
+        self.fetch_page(self.offset.unwrap_or(0)).await
+    }
+
+    /// Fetches every page starting at the configured offset and returns all
+    /// items.
+    pub async fn send_all(self) -> UniFiResult<Vec<T>> {
+        let mut offset = self.offset.unwrap_or(0);
+        let mut items = Vec::new();
+        loop {
+            let page = self.fetch_page(offset).await?;
+            let has_more = page.has_more();
+            offset = page.offset.saturating_add(page.count);
+            items.extend(page.data);
+            if !has_more {
+                return Ok(items);
+            }
+        }
+    }
+
+    async fn fetch_page(&self, offset: u32) -> UniFiResult<Page<T>> {
+        let mut query = vec![("offset", offset.to_string())];
+        if let Some(limit) = self.limit {
+            query.push(("limit", limit.to_string()));
+        }
+        if let Some(ref filter) = self.filter {
+            query.push(("filter", filter.clone()));
+        }
+
+        let segments: Vec<&str> = self.segments.iter().map(String::as_str).collect();
+        let url = integration_url(&self.client, &segments, &query)?;
+        send_integration_request(&self.client, Method::GET, url, None::<()>).await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateVouchersBuilder {
+    client: UniFiClient,
+    site_id: String,
+    count: u32,
+    name: String,
+    time_limit_minutes: u32,
+    guest_limit: Option<u32>,
+    data_quota_megabytes: Option<u64>,
+    download_speed_limit_kbps: Option<u32>,
+    upload_speed_limit_kbps: Option<u32>,
+}
+
+impl CreateVouchersBuilder {
+    pub(crate) fn new(client: UniFiClient, site_id: String, time_limit_minutes: u32) -> Self {
+        Self {
+            client,
+            site_id,
+            count: 1,
+            name: "unifi-client".to_string(),
+            time_limit_minutes,
+            guest_limit: None,
+            data_quota_megabytes: None,
+            download_speed_limit_kbps: None,
+            upload_speed_limit_kbps: None,
+        }
+    }
+
+    /// Number of vouchers to generate. Defaults to `1`.
+    pub fn count(mut self, count: u32) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Voucher name (note) shown in the UniFi UI.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// How many guests may use each voucher. Unlimited if not set.
+    pub fn guest_limit(mut self, guest_limit: u32) -> Self {
+        self.guest_limit = Some(guest_limit);
+        self
+    }
+
+    pub fn data_quota_megabytes(mut self, data_quota_megabytes: u64) -> Self {
+        self.data_quota_megabytes = Some(data_quota_megabytes);
+        self
+    }
+
+    pub fn download_speed_limit_kbps(mut self, download_speed_limit_kbps: u32) -> Self {
+        self.download_speed_limit_kbps = Some(download_speed_limit_kbps);
+        self
+    }
+
+    pub fn upload_speed_limit_kbps(mut self, upload_speed_limit_kbps: u32) -> Self {
+        self.upload_speed_limit_kbps = Some(upload_speed_limit_kbps);
+        self
+    }
+
+    pub async fn send(self) -> UniFiResult<Vec<Voucher>> {
+        let url = integration_url(
+            &self.client,
+            &["sites", &self.site_id, "hotspot", "vouchers"],
+            &[],
+        )?;
+
+        let request = CreateVouchersRequest {
+            count: self.count,
+            name: self.name,
+            authorized_guest_limit: self.guest_limit,
+            time_limit_minutes: self.time_limit_minutes,
+            data_usage_limit_megabytes: self.data_quota_megabytes,
+            rx_rate_limit_kbps: self.download_speed_limit_kbps,
+            tx_rate_limit_kbps: self.upload_speed_limit_kbps,
+        };
+
+        send_integration_request(&self.client, Method::POST, url, Some(request))
+            .await
+            .map(|response: CreateVouchersResponse| response.vouchers)
+    }
+}
+
+// Build an Integration API URL. Path segments are percent-encoded individually so identifiers can
+// never alter the path structure.
+fn integration_url(
+    client: &UniFiClient,
+    segments: &[&str],
+    query: &[(&str, String)],
+) -> UniFiResult<Url> {
+    let mut url = client.api_url(INTEGRATION_PREFIX)?;
+    url.path_segments_mut()
+        .map_err(|_| UniFiError::ConfigurationError("Base URL cannot be a base".into()))?
+        .extend(segments);
+    if !query.is_empty() {
+        url.query_pairs_mut().extend_pairs(query);
+    }
+    Ok(url)
+}
+
+// Send an Integration API request and decode the bare JSON response body.
+async fn send_integration_request<B, R>(
+    client: &UniFiClient,
+    method: Method,
+    url: Url,
+    body: Option<B>,
+) -> UniFiResult<R>
+where
+    B: Serialize,
+    R: DeserializeOwned,
+{
+    let endpoint = url.path().to_string();
+    let response = client.execute(method, url, body).await?;
+    let status = response.status();
+
+    if !status.is_success() {
+        let text = response.text();
+        let mut error = ControllerError::new(status, endpoint, &text);
+        if let Ok(err) = serde_json::from_str::<IntegrationErrorResponse>(&text) {
+            error = error.with_message(format!("{}: {}", err.status_name, err.message));
+        }
+        return Err(UniFiError::ControllerError(Box::new(error)));
+    }
+
+    response.json()
+}
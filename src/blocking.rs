@@ -0,0 +1,621 @@
+//! A blocking UniFi client for synchronous code.
+//!
+//! The types in this module mirror the async API method for method, but
+//! every call that talks to the controller blocks until it completes. Each
+//! client owns a small single-threaded Tokio runtime that drives the
+//! requests, so callers need no runtime of their own. Clones, site scopes and
+//! guest handlers share that runtime.
+//!
+//! # Panics
+//!
+//! Like `reqwest::blocking`, these types must not be used from within an
+//! async runtime: blocking calls (and dropping the last clone of a client)
+//! panic inside a Tokio runtime. Use the async [`crate::UniFiClient`] there.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use unifi_client::{blocking::UniFiClient, UniFiError};
+//! # fn main() -> Result<(), UniFiError> {
+//! let client = UniFiClient::builder()
+//!     .controller_url("https://controller.example:8443")
+//!     .username("admin")
+//!     .password("secret")
+//!     .build()?;
+//!
+//! let guest = client
+//!     .guests()
+//!     .authorize("00:11:22:33:44:55")
+//!     .duration_minutes(60)
+//!     .send()?;
+//! println!("Guest authorized until {}", guest.expires_at());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::io::Write;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use http::Method;
+use secrecy::SecretString;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::io::AsyncWrite;
+use tokio::runtime::{Builder as RuntimeBuilder, Runtime};
+
+use crate::{
+    ControllerKind, CredentialProvider, MfaCodeProvider, Middleware, RateLimit, RequestBody,
+    Response, RetryPolicy, Transport, UniFiError, UniFiResult,
+};
+
+pub mod guests;
+pub mod integration;
+
+/// Generates setters that forward to the same-named setter of the wrapped
+/// async builder.
+macro_rules! forward_setters {
+    ($async_ty:literal; $($(#[$attr:meta])* fn $name:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            #[doc = concat!("See [`", $async_ty, "::", stringify!($name), "`].")]
+            $(#[$attr])*
+            pub fn $name(mut self, $($arg: $ty),*) -> Self {
+                self.inner = self.inner.$name($($arg),*);
+                self
+            }
+        )*
+    };
+}
+pub(crate) use forward_setters;
+
+/// The runtime shared by a blocking client and everything created from it.
+#[derive(Debug, Clone)]
+pub(crate) struct BlockingRuntime(Arc<Runtime>);
+
+impl BlockingRuntime {
+    fn new() -> UniFiResult<Self> {
+        RuntimeBuilder::new_current_thread()
+            .enable_all()
+            .build()
+            .map(|runtime| Self(Arc::new(runtime)))
+            .map_err(|e| UniFiError::ConfigurationError(format!("Failed to create runtime: {e}")))
+    }
+
+    /// Runs `future` to completion on the client's runtime.
+    pub(crate) fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.0.block_on(future)
+    }
+}
+
+/// Builder for a blocking [`UniFiClient`].
+///
+/// Accepts the same settings as [`crate::UniFiClientBuilder`].
+#[derive(Default)]
+pub struct UniFiClientBuilder {
+    inner: crate::UniFiClientBuilder,
+}
+
+impl UniFiClientBuilder {
+    /// Creates a builder from prefixed environment variables.
+    ///
+    /// See [`crate::UniFiClientBuilder::from_env`].
+    #[cfg(feature = "config")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "config")))]
+    pub fn from_env(prefix: &str) -> UniFiResult<Self> {
+        crate::UniFiClientBuilder::from_env(prefix).map(Self::from)
+    }
+
+    forward_setters! {
+        "crate::UniFiClientBuilder";
+        fn controller_url(url: impl Into<String>);
+        fn username(username: impl Into<String>);
+        fn password(password: impl Into<String>);
+        fn password_from_env(var_name: &str);
+        fn password_provider(provider: impl CredentialProvider + 'static);
+        fn api_key(api_key: impl Into<String>);
+        fn mfa_code_provider(provider: impl MfaCodeProvider + 'static);
+        #[cfg(feature = "totp")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "totp")))]
+        fn totp_secret(secret: impl Into<String>);
+        fn site(site: impl Into<String>);
+        fn controller_kind(kind: ControllerKind);
+        fn accept_invalid_certs(accept: bool);
+        fn add_root_certificate_pem(pem: impl Into<Vec<u8>>);
+        fn add_root_certificate_der(der: impl Into<Vec<u8>>);
+        #[cfg(feature = "tls-pinning")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "tls-pinning")))]
+        fn pin_certificate_sha256(fingerprint: impl Into<String>);
+        #[cfg(feature = "tls-pinning")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "tls-pinning")))]
+        fn trust_on_first_use(enabled: bool);
+        fn timeout(timeout: Duration);
+        fn proxy(url: impl Into<String>);
+        fn proxy_auth(username: impl Into<String>, password: impl Into<String>);
+        fn resolve(host: impl Into<String>, addr: IpAddr);
+        fn retry_policy(retry_policy: RetryPolicy);
+        fn rate_limit(rate_limit: RateLimit);
+        fn max_concurrent_requests(max: usize);
+        fn middleware(middleware: impl Middleware + 'static);
+        fn http_client(http_client: reqwest::Client);
+        fn transport(transport: impl Transport + 'static);
+        fn restore_session(session: impl Into<SecretString>);
+        fn session_key(key: impl Into<SecretString>);
+        #[cfg(feature = "cassettes")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "cassettes")))]
+        fn record_cassette(path: impl Into<std::path::PathBuf>);
+    }
+
+    /// Builds and authenticates a blocking `UniFiClient`.
+    ///
+    /// See [`crate::UniFiClientBuilder::build`].
+    pub fn build(self) -> UniFiResult<UniFiClient> {
+        let runtime = BlockingRuntime::new()?;
+        let inner = runtime.block_on(self.inner.build())?;
+        Ok(UniFiClient { inner, runtime })
+    }
+}
+
+/// Wraps an async builder, e.g. one created by
+/// [`UniFiClientConfig::into_builder`](crate::UniFiClientConfig::into_builder).
+impl From<crate::UniFiClientBuilder> for UniFiClientBuilder {
+    fn from(inner: crate::UniFiClientBuilder) -> Self {
+        Self { inner }
+    }
+}
+
+/// A blocking client for the UniFi Controller API.
+///
+/// Wraps [`crate::UniFiClient`]; see there for the behavior of each method.
+#[derive(Debug, Clone)]
+pub struct UniFiClient {
+    inner: crate::UniFiClient,
+    runtime: BlockingRuntime,
+}
+
+impl UniFiClient {
+    /// Creates a new blocking `UniFiClientBuilder`.
+    pub fn builder() -> UniFiClientBuilder {
+        UniFiClientBuilder::default()
+    }
+
+    /// Gets the current site identifier.
+    pub fn site(&self) -> &str {
+        self.inner.site()
+    }
+
+    /// Returns a handle for another site on the same controller, sharing this
+    /// client's session and runtime.
+    pub fn site_scope(&self, site: impl Into<String>) -> UniFiClient {
+        UniFiClient {
+            inner: self.inner.site_scope(site),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Returns the endpoint of a v2 API resource of the current site.
+    ///
+    /// See [`crate::UniFiClient::v2_endpoint`].
+    pub fn v2_endpoint(&self, path: &str) -> String {
+        self.inner.v2_endpoint(path)
+    }
+
+    /// Gets the kind of controller.
+    pub fn controller_kind(&self) -> ControllerKind {
+        self.inner.controller_kind()
+    }
+
+    /// Gets the UniFi Network application version reported by the controller.
+    pub fn controller_version(&self) -> Option<&str> {
+        self.inner.controller_version()
+    }
+
+    /// Returns the SHA-256 fingerprint of the pinned controller certificate.
+    #[cfg(feature = "tls-pinning")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tls-pinning")))]
+    pub fn pinned_certificate_sha256(&self) -> Option<String> {
+        self.inner.pinned_certificate_sha256()
+    }
+
+    /// Creates a new blocking `guests::GuestHandler` for the Guests API.
+    pub fn guests(&self) -> guests::GuestHandler {
+        guests::GuestHandler::new(self.inner.guests(), self.runtime.clone())
+    }
+
+    /// Creates a new blocking `integration::IntegrationHandler` for the
+    /// official UniFi Network Integration API (v1).
+    pub fn integration(&self) -> integration::IntegrationHandler {
+        integration::IntegrationHandler::new(self.inner.integration(), self.runtime.clone())
+    }
+
+    /// Logs in with the configured username and password.
+    pub fn login(&self) -> UniFiResult<()> {
+        self.runtime.block_on(self.inner.login())
+    }
+
+    /// Ends the current session on the controller.
+    pub fn logout(&self) -> UniFiResult<()> {
+        self.runtime.block_on(self.inner.logout())
+    }
+
+    /// Exports the current authenticated session.
+    pub fn export_session(&self) -> UniFiResult<SecretString> {
+        self.runtime.block_on(self.inner.export_session())
+    }
+
+    /// Sends a GET request and parses the standard UniFi API response.
+    pub fn get<T, R>(&self, endpoint: &str, params: Option<T>) -> UniFiResult<R>
+    where
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        self.runtime.block_on(self.inner.get(endpoint, params))
+    }
+
+    /// Sends a POST request and parses the standard UniFi API response.
+    pub fn post<T, R>(&self, endpoint: &str, body: Option<T>) -> UniFiResult<R>
+    where
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        self.runtime.block_on(self.inner.post(endpoint, body))
+    }
+
+    /// Makes a request and returns the `data` field of the parsed JSON body.
+    pub fn request_json<T>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Option<T>,
+    ) -> UniFiResult<serde_json::Value>
+    where
+        T: Serialize,
+    {
+        self.runtime
+            .block_on(self.inner.request_json(method, endpoint, body))
+    }
+
+    /// Like [`request_json`](Self::request_json), with `query` encoded as the
+    /// URL query string.
+    ///
+    /// See [`crate::UniFiClient::request_with_query`].
+    pub fn request_json_with_query<Q, T>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        query: &Q,
+        body: Option<T>,
+    ) -> UniFiResult<serde_json::Value>
+    where
+        Q: Serialize + ?Sized,
+        T: Serialize,
+    {
+        self.runtime.block_on(
+            self.inner
+                .request_json_with_query(method, endpoint, query, body),
+        )
+    }
+
+    /// Makes a request and deserializes the whole response body as `R`,
+    /// without expecting the `{ meta, data }` envelope.
+    ///
+    /// See [`crate::UniFiClient::request_typed`].
+    pub fn request_typed<T, R>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Option<T>,
+    ) -> UniFiResult<R>
+    where
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        self.runtime
+            .block_on(self.inner.request_typed(method, endpoint, body))
+    }
+
+    /// Like [`request_typed`](Self::request_typed), with `query` encoded as
+    /// the URL query string.
+    pub fn request_typed_with_query<Q, T, R>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        query: &Q,
+        body: Option<T>,
+    ) -> UniFiResult<R>
+    where
+        Q: Serialize + ?Sized,
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        self.runtime.block_on(
+            self.inner
+                .request_typed_with_query(method, endpoint, query, body),
+        )
+    }
+
+    /// Makes a raw request to the UniFi API.
+    ///
+    /// See [`crate::UniFiClient::request`].
+    pub fn request<T>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Option<T>,
+    ) -> UniFiResult<Response>
+    where
+        T: Serialize,
+    {
+        self.runtime
+            .block_on(self.inner.request(method, endpoint, body))
+    }
+
+    /// Like [`request`](Self::request), with `query` encoded as the URL query
+    /// string.
+    ///
+    /// See [`crate::UniFiClient::request_with_query`].
+    pub fn request_with_query<Q, T>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        query: &Q,
+        body: Option<T>,
+    ) -> UniFiResult<Response>
+    where
+        Q: Serialize + ?Sized,
+        T: Serialize,
+    {
+        self.runtime
+            .block_on(self.inner.request_with_query(method, endpoint, query, body))
+    }
+
+    /// Like [`request`](Self::request), with a form, multipart or raw body
+    /// instead of JSON.
+    ///
+    /// See [`crate::UniFiClient::request_with_body`].
+    pub fn request_with_body(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: impl Into<RequestBody>,
+    ) -> UniFiResult<Response> {
+        self.runtime
+            .block_on(self.inner.request_with_body(method, endpoint, body))
+    }
+
+    /// Downloads `endpoint` and streams the body to `writer`, e.g. a
+    /// `std::fs::File`.
+    ///
+    /// See [`crate::UniFiClient::download`].
+    pub fn download<W>(&self, endpoint: &str, writer: &mut W) -> UniFiResult<Response>
+    where
+        W: Write + Send,
+    {
+        let mut writer = SyncWriter(writer);
+        self.runtime
+            .block_on(self.inner.download(endpoint, &mut writer))
+    }
+}
+
+/// Adapts a `std::io::Write` to `AsyncWrite` for downloads.
+///
+/// Writes complete immediately and block the client's runtime, which only
+/// drives the calling thread's request.
+struct SyncWriter<'a, W>(&'a mut W);
+
+impl<W: Write> AsyncWrite for SyncWriter<'_, W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Poll::Ready(self.get_mut().0.write(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(self.get_mut().0.flush())
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+    use std::time::Duration;
+
+    use http::Method;
+    use secrecy::SecretString;
+    use serde_json::Value;
+
+    use crate::models::integration::Device;
+    use crate::{
+        ControllerKind, LoggingMiddleware, RateLimit, RequestBody, ReqwestTransport, RetryPolicy,
+        UniFiResult,
+    };
+
+    // Stands in for an argument or receiver of the given type; never called.
+    fn any<T>() -> T {
+        unreachable!()
+    }
+
+    // Requires both results to have the same type.
+    fn same<T>(_: T, _: T) {}
+
+    // Calls each method on the async and the blocking type with arguments of the same types.
+    // `new` calls an associated function. `wraps` methods return the respective wrapper types
+    // (`wraps_awaited` after awaiting the async one), `returns` methods the same type, and
+    // `awaits` methods return a future on the async side and its output on the blocking side.
+    macro_rules! mirror {
+        ($async_ty:ty => $blocking_ty:ty {
+            $($(#[$attr:meta])* $kind:ident $method:ident $(::<$($generic:ty),+>)? ($($arg:ty),*);)*
+        }) => {
+            $(
+                $(#[$attr])*
+                mirror!(@$kind $async_ty, $blocking_ty, $method $(::<$($generic),+>)? ($($arg),*));
+            )*
+        };
+        (@new $a:ty, $b:ty, $method:ident ($($arg:ty),*)) => {
+            let _ = <$a>::$method($(any::<$arg>()),*);
+            let _ = <$b>::$method($(any::<$arg>()),*);
+        };
+        (@wraps $a:ty, $b:ty, $method:ident ($($arg:ty),*)) => {
+            let _ = any::<$a>().$method($(any::<$arg>()),*);
+            let _ = any::<$b>().$method($(any::<$arg>()),*);
+        };
+        (@wraps_awaited $a:ty, $b:ty, $method:ident ($($arg:ty),*)) => {
+            let _ = any::<$a>().$method($(any::<$arg>()),*).await;
+            let _ = any::<$b>().$method($(any::<$arg>()),*);
+        };
+        (@returns $a:ty, $b:ty, $method:ident ($($arg:ty),*)) => {
+            same(
+                any::<$a>().$method($(any::<$arg>()),*),
+                any::<$b>().$method($(any::<$arg>()),*),
+            );
+        };
+        (@awaits $a:ty, $b:ty, $method:ident $(::<$($generic:ty),+>)? ($($arg:ty),*)) => {
+            same(
+                any::<$a>().$method $(::<$($generic),+>)? ($(any::<$arg>()),*).await,
+                any::<$b>().$method $(::<$($generic),+>)? ($(any::<$arg>()),*),
+            );
+        };
+    }
+
+    // Fails to compile when a blocking method is missing or its signature drifts from the
+    // async one.
+    #[allow(dead_code)]
+    async fn blocking_api_mirrors_async_api() {
+        mirror!(crate::UniFiClientBuilder => super::UniFiClientBuilder {
+            #[cfg(feature = "config")]
+            new from_env(&str);
+            wraps controller_url(String);
+            wraps username(String);
+            wraps password(String);
+            wraps password_from_env(&str);
+            wraps password_provider(SecretString);
+            wraps api_key(String);
+            wraps mfa_code_provider(fn() -> UniFiResult<String>);
+            #[cfg(feature = "totp")]
+            wraps totp_secret(String);
+            wraps site(String);
+            wraps controller_kind(ControllerKind);
+            wraps accept_invalid_certs(bool);
+            wraps add_root_certificate_pem(Vec<u8>);
+            wraps add_root_certificate_der(Vec<u8>);
+            #[cfg(feature = "tls-pinning")]
+            wraps pin_certificate_sha256(String);
+            #[cfg(feature = "tls-pinning")]
+            wraps trust_on_first_use(bool);
+            wraps timeout(Duration);
+            wraps proxy(String);
+            wraps proxy_auth(String, String);
+            wraps resolve(String, IpAddr);
+            wraps retry_policy(RetryPolicy);
+            wraps rate_limit(RateLimit);
+            wraps max_concurrent_requests(usize);
+            wraps middleware(LoggingMiddleware);
+            wraps http_client(reqwest::Client);
+            wraps transport(ReqwestTransport);
+            wraps restore_session(String);
+            wraps session_key(String);
+            #[cfg(feature = "cassettes")]
+            wraps record_cassette(std::path::PathBuf);
+            wraps_awaited build();
+        });
+
+        mirror!(crate::UniFiClient => super::UniFiClient {
+            new builder();
+            returns site();
+            wraps site_scope(String);
+            returns v2_endpoint(&str);
+            returns controller_kind();
+            returns controller_version();
+            #[cfg(feature = "tls-pinning")]
+            returns pinned_certificate_sha256();
+            wraps guests();
+            wraps integration();
+            awaits login();
+            awaits logout();
+            awaits export_session();
+            awaits get::<(), Value>(&str, Option<()>);
+            awaits post::<(), Value>(&str, Option<()>);
+            awaits request_json(Method, &str, Option<()>);
+            awaits request_json_with_query(Method, &str, &[(&str, &str)], Option<()>);
+            awaits request_typed::<(), Value>(Method, &str, Option<()>);
+            awaits request_typed_with_query::<[(&str, &str)], (), Value>(
+                Method, &str, &[(&str, &str)], Option<()>
+            );
+            awaits request(Method, &str, Option<()>);
+            awaits request_with_query(Method, &str, &[(&str, &str)], Option<()>);
+            awaits request_with_body(Method, &str, RequestBody);
+            awaits download(&str, &mut Vec<u8>);
+        });
+
+        mirror!(crate::guests::GuestHandler => super::guests::GuestHandler {
+            wraps authorize(String);
+            wraps list();
+            wraps unauthorize(String);
+            wraps unauthorize_all();
+        });
+        mirror!(crate::guests::AuthorizeGuestBuilder => super::guests::AuthorizeGuestBuilder {
+            wraps duration_minutes(u32);
+            wraps upload_speed_limit_kbps(u32);
+            wraps download_speed_limit_kbps(u32);
+            wraps data_quota_megabytes(u64);
+            wraps access_point_mac_address(String);
+            wraps captive_portal_timestamp(i64);
+            wraps requested_url(String);
+            wraps wifi_network(String);
+            awaits send();
+        });
+        mirror!(crate::guests::ListGuestsBuilder => super::guests::ListGuestsBuilder {
+            wraps within_hours(u32);
+            awaits send();
+        });
+        mirror!(crate::guests::UnauthorizeGuestBuilder => super::guests::UnauthorizeGuestBuilder {
+            awaits send();
+        });
+        mirror!(
+            crate::guests::UnauthorizeAllGuestsBuilder => super::guests::UnauthorizeAllGuestsBuilder {
+                awaits send();
+            }
+        );
+
+        mirror!(crate::integration::IntegrationHandler => super::integration::IntegrationHandler {
+            awaits info();
+            wraps sites();
+            wraps devices(String);
+            awaits device(&str, &str);
+            wraps clients(String);
+            wraps vouchers(String);
+            awaits voucher(&str, &str);
+            wraps create_vouchers(String, u32);
+            awaits delete_voucher(&str, &str);
+        });
+        mirror!(
+            crate::integration::ListBuilder<Device> => super::integration::ListBuilder<Device> {
+                wraps offset(u32);
+                wraps limit(u32);
+                wraps filter(String);
+                awaits send();
+                awaits send_all();
+            }
+        );
+        mirror!(
+            crate::integration::CreateVouchersBuilder => super::integration::CreateVouchersBuilder {
+                wraps count(u32);
+                wraps name(String);
+                wraps guest_limit(u32);
+                wraps data_quota_megabytes(u64);
+                wraps download_speed_limit_kbps(u32);
+                wraps upload_speed_limit_kbps(u32);
+                awaits send();
+            }
+        );
+    }
+}
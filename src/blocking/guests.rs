@@ -0,0 +1,117 @@
+//! Blocking counterparts of the [`crate::guests`] handler and builders.
+
+use super::{forward_setters, BlockingRuntime};
+use crate::{guests, models, UniFiResult};
+
+/// Provides blocking methods for managing UniFi wireless guest
+/// authorizations.
+///
+/// See [`crate::guests::GuestHandler`].
+#[derive(Debug)]
+pub struct GuestHandler {
+    inner: guests::GuestHandler,
+    runtime: BlockingRuntime,
+}
+
+impl GuestHandler {
+    pub(crate) fn new(inner: guests::GuestHandler, runtime: BlockingRuntime) -> Self {
+        Self { inner, runtime }
+    }
+
+    /// Authorizes a guest device for network access.
+    pub fn authorize(&self, mac: impl Into<String>) -> AuthorizeGuestBuilder {
+        AuthorizeGuestBuilder {
+            inner: self.inner.authorize(mac),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Lists all guest authorizations within a specified time window.
+    pub fn list(&self) -> ListGuestsBuilder {
+        ListGuestsBuilder {
+            inner: self.inner.list(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Revokes network access for a specific guest device.
+    pub fn unauthorize(&self, mac: impl Into<String>) -> UnauthorizeGuestBuilder {
+        UnauthorizeGuestBuilder {
+            inner: self.inner.unauthorize(mac),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Revokes network access for all authorized guest devices.
+    pub fn unauthorize_all(&self) -> UnauthorizeAllGuestsBuilder {
+        UnauthorizeAllGuestsBuilder {
+            inner: self.inner.unauthorize_all(),
+            runtime: self.runtime.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthorizeGuestBuilder {
+    inner: guests::AuthorizeGuestBuilder,
+    runtime: BlockingRuntime,
+}
+
+impl AuthorizeGuestBuilder {
+    forward_setters! {
+        "crate::guests::AuthorizeGuestBuilder";
+        fn duration_minutes(duration_minutes: u32);
+        fn upload_speed_limit_kbps(upload_speed_limit_kbps: u32);
+        fn download_speed_limit_kbps(download_speed_limit_kbps: u32);
+        fn data_quota_megabytes(data_quota_megabytes: u64);
+        fn access_point_mac_address(access_point_mac_address: impl Into<String>);
+        fn captive_portal_timestamp(captive_portal_timestamp: i64);
+        fn requested_url(requested_url: impl Into<String>);
+        fn wifi_network(wifi_network: impl Into<String>);
+    }
+
+    pub fn send(self) -> UniFiResult<models::guests::GuestEntry> {
+        self.runtime.block_on(self.inner.send())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ListGuestsBuilder {
+    inner: guests::ListGuestsBuilder,
+    runtime: BlockingRuntime,
+}
+
+impl ListGuestsBuilder {
+    forward_setters! {
+        "crate::guests::ListGuestsBuilder";
+        fn within_hours(hours: u32);
+    }
+
+    pub fn send(self) -> UniFiResult<Vec<models::guests::GuestEntry>> {
+        self.runtime.block_on(self.inner.send())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UnauthorizeGuestBuilder {
+    inner: guests::UnauthorizeGuestBuilder,
+    runtime: BlockingRuntime,
+}
+
+impl UnauthorizeGuestBuilder {
+    pub fn send(self) -> UniFiResult<()> {
+        self.runtime.block_on(self.inner.send())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UnauthorizeAllGuestsBuilder {
+    inner: guests::UnauthorizeAllGuestsBuilder,
+    runtime: BlockingRuntime,
+}
+
+impl UnauthorizeAllGuestsBuilder {
+    pub fn send(self) -> UniFiResult<()> {
+        self.runtime.block_on(self.inner.send())
+    }
+}
@@ -0,0 +1,136 @@
+//! Blocking counterparts of the [`crate::integration`] handler and builders.
+
+use serde::de::DeserializeOwned;
+
+use super::{forward_setters, BlockingRuntime};
+use crate::integration;
+use crate::models::integration::{
+    ApplicationInfo, Device, IntegrationSite, NetworkClient, Page, Voucher,
+};
+use crate::UniFiResult;
+
+/// Provides blocking typed access to the official UniFi Network Integration
+/// API (v1).
+///
+/// See [`crate::integration::IntegrationHandler`].
+#[derive(Debug)]
+pub struct IntegrationHandler {
+    inner: integration::IntegrationHandler,
+    runtime: BlockingRuntime,
+}
+
+impl IntegrationHandler {
+    pub(crate) fn new(inner: integration::IntegrationHandler, runtime: BlockingRuntime) -> Self {
+        Self { inner, runtime }
+    }
+
+    /// Retrieves information about the UniFi Network application.
+    pub fn info(&self) -> UniFiResult<ApplicationInfo> {
+        self.runtime.block_on(self.inner.info())
+    }
+
+    /// Lists the sites visible to the authenticated user.
+    pub fn sites(&self) -> ListBuilder<IntegrationSite> {
+        ListBuilder::new(self.inner.sites(), self.runtime.clone())
+    }
+
+    /// Lists the devices adopted at a site.
+    pub fn devices(&self, site_id: impl Into<String>) -> ListBuilder<Device> {
+        ListBuilder::new(self.inner.devices(site_id), self.runtime.clone())
+    }
+
+    /// Retrieves the details of a single device.
+    pub fn device(&self, site_id: &str, device_id: &str) -> UniFiResult<Device> {
+        self.runtime.block_on(self.inner.device(site_id, device_id))
+    }
+
+    /// Lists the clients connected at a site.
+    pub fn clients(&self, site_id: impl Into<String>) -> ListBuilder<NetworkClient> {
+        ListBuilder::new(self.inner.clients(site_id), self.runtime.clone())
+    }
+
+    /// Lists the hotspot vouchers of a site.
+    pub fn vouchers(&self, site_id: impl Into<String>) -> ListBuilder<Voucher> {
+        ListBuilder::new(self.inner.vouchers(site_id), self.runtime.clone())
+    }
+
+    /// Retrieves a single hotspot voucher.
+    pub fn voucher(&self, site_id: &str, voucher_id: &str) -> UniFiResult<Voucher> {
+        self.runtime
+            .block_on(self.inner.voucher(site_id, voucher_id))
+    }
+
+    /// Generates hotspot vouchers.
+    pub fn create_vouchers(
+        &self,
+        site_id: impl Into<String>,
+        time_limit_minutes: u32,
+    ) -> CreateVouchersBuilder {
+        CreateVouchersBuilder {
+            inner: self.inner.create_vouchers(site_id, time_limit_minutes),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Deletes a single hotspot voucher.
+    pub fn delete_voucher(&self, site_id: &str, voucher_id: &str) -> UniFiResult<()> {
+        self.runtime
+            .block_on(self.inner.delete_voucher(site_id, voucher_id))
+    }
+}
+
+/// Builder for paged Integration API list requests.
+#[derive(Debug, Clone)]
+pub struct ListBuilder<T> {
+    inner: integration::ListBuilder<T>,
+    runtime: BlockingRuntime,
+}
+
+impl<T> ListBuilder<T>
+where
+    T: DeserializeOwned,
+{
+    fn new(inner: integration::ListBuilder<T>, runtime: BlockingRuntime) -> Self {
+        Self { inner, runtime }
+    }
+
+    forward_setters! {
+        "crate::integration::ListBuilder";
+        fn offset(offset: u32);
+        fn limit(limit: u32);
+        fn filter(filter: impl Into<String>);
+    }
+
+    /// Fetches a single page.
+    pub fn send(self) -> UniFiResult<Page<T>> {
+        self.runtime.block_on(self.inner.send())
+    }
+
+    /// Fetches every page starting at the configured offset and returns all
+    /// items.
+    pub fn send_all(self) -> UniFiResult<Vec<T>> {
+        self.runtime.block_on(self.inner.send_all())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateVouchersBuilder {
+    inner: integration::CreateVouchersBuilder,
+    runtime: BlockingRuntime,
+}
+
+impl CreateVouchersBuilder {
+    forward_setters! {
+        "crate::integration::CreateVouchersBuilder";
+        fn count(count: u32);
+        fn name(name: impl Into<String>);
+        fn guest_limit(guest_limit: u32);
+        fn data_quota_megabytes(data_quota_megabytes: u64);
+        fn download_speed_limit_kbps(download_speed_limit_kbps: u32);
+        fn upload_speed_limit_kbps(upload_speed_limit_kbps: u32);
+    }
+
+    pub fn send(self) -> UniFiResult<Vec<Voucher>> {
+        self.runtime.block_on(self.inner.send())
+    }
+}
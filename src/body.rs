@@ -0,0 +1,311 @@
+//! Request bodies other than JSON.
+//!
+//! Most controller endpoints take JSON, which the typed APIs and
+//! [`UniFiClient::request`](crate::UniFiClient::request) send for any
+//! `Serialize` value. Uploads such as portal customization bundles, backup
+//! restores and firmware images need a form, multipart or raw body instead;
+//! send those with
+//! [`UniFiClient::request_with_body`](crate::UniFiClient::request_with_body).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bytes::Bytes;
+use http::HeaderValue;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{UniFiError, UniFiResult};
+
+/// The body of a request sent with
+/// [`UniFiClient::request_with_body`](crate::UniFiClient::request_with_body).
+///
+/// # Examples
+///
+/// ```no_run
+/// # use reqwest::Method;
+/// # use unifi_client::{Multipart, RequestBody, UniFiClient, UniFiError};
+/// # async fn example(client: &UniFiClient, backup: Vec<u8>) -> Result<(), UniFiError> {
+/// let form = Multipart::new().file("file", "restore.unf", "application/octet-stream", backup);
+/// client
+///     .request_with_body(Method::POST, "/upload/backup", RequestBody::Multipart(form))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum RequestBody {
+    /// A JSON document, sent as `application/json`.
+    Json(Value),
+    /// Key/value pairs, sent as `application/x-www-form-urlencoded`.
+    Form(Vec<(String, String)>),
+    /// A `multipart/form-data` body, e.g. for file uploads.
+    Multipart(Multipart),
+    /// Raw bytes with the given `Content-Type`.
+    Bytes {
+        /// The `Content-Type` header value.
+        content_type: String,
+        /// The body.
+        data: Vec<u8>,
+    },
+}
+
+impl RequestBody {
+    /// Serializes `value` into a [`RequestBody::Json`] body.
+    pub fn json(value: &impl Serialize) -> UniFiResult<Self> {
+        Ok(Self::Json(serde_json::to_value(value)?))
+    }
+
+    /// Creates a [`RequestBody::Bytes`] body.
+    pub fn bytes(content_type: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        Self::Bytes {
+            content_type: content_type.into(),
+            data: data.into(),
+        }
+    }
+
+    /// Encodes the body into its `Content-Type` and bytes.
+    pub(crate) fn encode(self) -> UniFiResult<EncodedBody> {
+        match self {
+            Self::Json(value) => EncodedBody::json(&value),
+            Self::Form(pairs) => {
+                let encoded = serde_urlencoded::to_string(pairs).map_err(|e| {
+                    UniFiError::ConfigurationError(format!("Failed to encode form: {e}"))
+                })?;
+                Ok(EncodedBody {
+                    content_type: HeaderValue::from_static("application/x-www-form-urlencoded"),
+                    data: encoded.into(),
+                })
+            }
+            Self::Multipart(multipart) => Ok(EncodedBody {
+                content_type: multipart.content_type()?,
+                data: multipart.to_bytes()?.into(),
+            }),
+            Self::Bytes { content_type, data } => {
+                let content_type = HeaderValue::from_str(&content_type).map_err(|_| {
+                    UniFiError::ConfigurationError(format!(
+                        "Invalid content type: {content_type:?}"
+                    ))
+                })?;
+                Ok(EncodedBody {
+                    content_type,
+                    data: data.into(),
+                })
+            }
+        }
+    }
+}
+
+/// A request body encoded for sending.
+///
+/// Clones share the bytes, so retries and re-authentication resend one
+/// buffer instead of copying it per attempt.
+#[derive(Debug, Clone)]
+pub(crate) struct EncodedBody {
+    pub(crate) content_type: HeaderValue,
+    pub(crate) data: Bytes,
+}
+
+impl EncodedBody {
+    /// Encodes a JSON document.
+    pub(crate) fn json(value: &Value) -> UniFiResult<Self> {
+        Ok(Self {
+            content_type: HeaderValue::from_static("application/json"),
+            data: serde_json::to_vec(value)?.into(),
+        })
+    }
+}
+
+impl From<Value> for RequestBody {
+    fn from(value: Value) -> Self {
+        Self::Json(value)
+    }
+}
+
+impl From<Multipart> for RequestBody {
+    fn from(multipart: Multipart) -> Self {
+        Self::Multipart(multipart)
+    }
+}
+
+/// A `multipart/form-data` body made of text fields and files.
+///
+/// The boundary is chosen when the body is created, so retries and
+/// re-authentication resend identical bytes.
+#[derive(Debug, Clone)]
+pub struct Multipart {
+    boundary: String,
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone)]
+struct Part {
+    name: String,
+    file_name: Option<String>,
+    content_type: Option<String>,
+    data: Vec<u8>,
+}
+
+impl Multipart {
+    /// Creates an empty body.
+    pub fn new() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        Self {
+            boundary: format!(
+                "unifi-client-{nanos:016x}{:08x}{count:08x}",
+                std::process::id()
+            ),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Appends a text field.
+    pub fn text(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            file_name: None,
+            content_type: None,
+            data: value.into().into_bytes(),
+        });
+        self
+    }
+
+    /// Appends a file field.
+    pub fn file(
+        mut self,
+        name: impl Into<String>,
+        file_name: impl Into<String>,
+        content_type: impl Into<String>,
+        data: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            file_name: Some(file_name.into()),
+            content_type: Some(content_type.into()),
+            data: data.into(),
+        });
+        self
+    }
+
+    /// Returns the boundary separating the parts.
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    fn content_type(&self) -> UniFiResult<HeaderValue> {
+        HeaderValue::from_str(&format!("multipart/form-data; boundary={}", self.boundary))
+            .map_err(|e| UniFiError::ConfigurationError(e.to_string()))
+    }
+
+    fn to_bytes(&self) -> UniFiResult<Vec<u8>> {
+        let mut out = Vec::new();
+        for part in &self.parts {
+            out.extend_from_slice(format!("--{}\r\n", self.boundary).as_bytes());
+            out.extend_from_slice(
+                format!(
+                    "Content-Disposition: form-data; name=\"{}\"",
+                    escape_quoted(&part.name)
+                )
+                .as_bytes(),
+            );
+            if let Some(file_name) = &part.file_name {
+                out.extend_from_slice(
+                    format!("; filename=\"{}\"", escape_quoted(file_name)).as_bytes(),
+                );
+            }
+            out.extend_from_slice(b"\r\n");
+            if let Some(content_type) = &part.content_type {
+                // Unlike names, a content type is not quoted and cannot be escaped.
+                if content_type.contains(['\r', '\n']) {
+                    return Err(UniFiError::ConfigurationError(format!(
+                        "Invalid content type for part {:?}: {content_type:?}",
+                        part.name
+                    )));
+                }
+                out.extend_from_slice(format!("Content-Type: {content_type}\r\n").as_bytes());
+            }
+            out.extend_from_slice(b"\r\n");
+            out.extend_from_slice(&part.data);
+            out.extend_from_slice(b"\r\n");
+        }
+        out.extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        Ok(out)
+    }
+}
+
+impl Default for Multipart {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Percent-encode the characters that would end a quoted header parameter.
+fn escape_quoted(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multipart_encodes_fields_and_files() {
+        let multipart = Multipart::new().text("site", "default").file(
+            "file",
+            "a\"b.zip",
+            "application/zip",
+            b"PK".to_vec(),
+        );
+        let boundary = multipart.boundary().to_string();
+        let body = RequestBody::Multipart(multipart).encode().unwrap();
+
+        assert_eq!(
+            body.content_type,
+            format!("multipart/form-data; boundary={boundary}").as_str()
+        );
+        assert_eq!(
+            String::from_utf8(body.data.to_vec()).unwrap(),
+            format!(
+                "--{boundary}\r\n\
+                 Content-Disposition: form-data; name=\"site\"\r\n\r\n\
+                 default\r\n\
+                 --{boundary}\r\n\
+                 Content-Disposition: form-data; name=\"file\"; filename=\"a%22b.zip\"\r\n\
+                 Content-Type: application/zip\r\n\r\n\
+                 PK\r\n\
+                 --{boundary}--\r\n"
+            )
+        );
+    }
+
+    #[test]
+    fn multipart_rejects_line_breaks_in_content_type() {
+        let multipart = Multipart::new().file(
+            "file",
+            "a.zip",
+            "application/zip\r\nX-Injected: 1",
+            b"PK".to_vec(),
+        );
+        assert!(matches!(
+            RequestBody::Multipart(multipart).encode(),
+            Err(UniFiError::ConfigurationError(_))
+        ));
+    }
+
+    #[test]
+    fn form_is_url_encoded() {
+        let body = RequestBody::Form(vec![("cmd".into(), "a b&c".into())]);
+        let body = body.encode().unwrap();
+        assert_eq!(body.content_type, "application/x-www-form-urlencoded");
+        assert_eq!(body.data, &b"cmd=a+b%26c"[..]);
+    }
+}
@@ -0,0 +1,795 @@
+//! Record controller traffic to cassette files and replay it later.
+//!
+//! [`RecordingTransport`] wraps another [`Transport`] and collects every
+//! request/response pair in memory, writing the [`Cassette`] file when it is
+//! flushed or dropped. Passwords, one-time codes, API keys, cookies and CSRF
+//! tokens are redacted before anything is stored, in JSON and form-encoded
+//! bodies alike; other request bodies are stored as a SHA-256 digest unless
+//! [`RecordingTransport::record_raw_bodies`] is enabled. [`ReplayTransport`]
+//! answers requests from a cassette without touching the network, so
+//! interactions captured once against a real controller can run in CI.
+//!
+//! Cassettes are pretty-printed JSON. They store paths rather than full URLs,
+//! so a replaying client may use any controller URL.
+//!
+//! # Examples
+//!
+//! Record against a real controller:
+//!
+//! ```no_run
+//! # use unifi_client::{UniFiClient, UniFiError};
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), UniFiError> {
+//! let client = UniFiClient::builder()
+//!     .controller_url("https://controller.example:8443")
+//!     .username("admin")
+//!     .password("secret")
+//!     .record_cassette("tests/cassettes/guests.json")
+//!     .build()
+//!     .await?;
+//! client.guests().list().send().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Replay in a test:
+//!
+//! ```no_run
+//! # use unifi_client::cassette::{MatchOn, ReplayTransport};
+//! # use unifi_client::{UniFiClient, UniFiError};
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), UniFiError> {
+//! let replay = ReplayTransport::load("tests/cassettes/guests.json")?
+//!     .match_on([MatchOn::Method, MatchOn::Path, MatchOn::Body]);
+//! let client = UniFiClient::builder()
+//!     .controller_url("https://controller.example:8443")
+//!     .username("admin")
+//!     .password("anything")
+//!     .transport(replay)
+//!     .build()
+//!     .await?;
+//! let guests = client.guests().list().send().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use data_encoding::{BASE64, HEXLOWER};
+use http::header::CONTENT_TYPE;
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::transport::{Response, Transport, TransportError, TransportErrorKind, TransportRequest};
+use crate::{UniFiError, UniFiResult};
+
+/// Replaces redacted values in cassettes.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Headers whose values are always redacted.
+const SECRET_HEADERS: [&str; 5] = [
+    "authorization",
+    "cookie",
+    "x-api-key",
+    "x-csrf-token",
+    "x-updated-csrf-token",
+];
+
+/// JSON fields redacted by default, at any depth.
+const SECRET_FIELDS: [&str; 6] = [
+    "password",
+    "token",
+    "ubic_2fa_token",
+    "x_password",
+    "x_passphrase",
+    "x_api_key",
+];
+
+/// A recorded sequence of request/response pairs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Cassette {
+    /// The interactions in the order they happened.
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    /// Reads a cassette file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UniFiError::ConfigurationError` if the file cannot be read or
+    /// parsed.
+    pub fn load(path: impl AsRef<Path>) -> UniFiResult<Self> {
+        let path = path.as_ref();
+        let invalid = |e: &dyn std::fmt::Display| {
+            UniFiError::ConfigurationError(format!("Invalid cassette {}: {e}", path.display()))
+        };
+        let contents = std::fs::read_to_string(path).map_err(|e| invalid(&e))?;
+        serde_json::from_str(&contents).map_err(|e| invalid(&e))
+    }
+
+    /// Writes the cassette as pretty-printed JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> UniFiResult<()> {
+        let path = path.as_ref();
+        let contents = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, contents).map_err(|e| {
+            UniFiError::ConfigurationError(format!(
+                "Failed to write cassette {}: {e}",
+                path.display()
+            ))
+        })
+    }
+}
+
+/// One request and the response it received.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    /// The request as sent, with secrets redacted.
+    pub request: RecordedRequest,
+    /// The response the controller returned, with secrets redacted.
+    pub response: RecordedResponse,
+}
+
+/// A recorded request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    /// The HTTP method, e.g. `GET`.
+    pub method: String,
+    /// The URL path, including any `/proxy/network` prefix.
+    pub path: String,
+    /// The raw query string, without the leading `?`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Header values by lowercase header name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, Vec<String>>,
+    /// The request body.
+    #[serde(default, skip_serializing_if = "RecordedBody::is_empty")]
+    pub body: RecordedBody,
+}
+
+/// A recorded response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    /// The HTTP status code.
+    pub status: u16,
+    /// Header values by lowercase header name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, Vec<String>>,
+    /// The response body.
+    #[serde(default, skip_serializing_if = "RecordedBody::is_empty")]
+    pub body: RecordedBody,
+}
+
+/// A recorded body, stored as JSON when it parses as JSON, as text when it is
+/// form-encoded or UTF-8, and as base64 otherwise.
+///
+/// Request bodies that are neither JSON nor form-encoded (e.g. multipart
+/// uploads) are stored only as their SHA-256 digest, unless
+/// [`RecordingTransport::record_raw_bodies`] is enabled.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordedBody {
+    /// A body that parsed as JSON.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json: Option<Value>,
+    /// A form-encoded or other UTF-8 body.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Any other body, base64-encoded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base64: Option<String>,
+    /// Hex-encoded SHA-256 digest of a body that was not stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+impl RecordedBody {
+    fn request(bytes: &[u8], headers: &HeaderMap, redaction: &Redaction) -> Self {
+        if bytes.is_empty() {
+            return Self::default();
+        }
+        if let Some(body) = Self::redacted(bytes, headers, redaction) {
+            return body;
+        }
+        if redaction.raw_bodies {
+            return Self::verbatim(bytes);
+        }
+        Self {
+            sha256: Some(sha256_hex(bytes)),
+            ..Self::default()
+        }
+    }
+
+    fn response(bytes: &[u8], headers: &HeaderMap, redaction: &Redaction) -> Self {
+        if bytes.is_empty() {
+            return Self::default();
+        }
+        Self::redacted(bytes, headers, redaction).unwrap_or_else(|| Self::verbatim(bytes))
+    }
+
+    /// Parses JSON and form-encoded bodies and redacts their secret fields.
+    fn redacted(bytes: &[u8], headers: &HeaderMap, redaction: &Redaction) -> Option<Self> {
+        if let Ok(mut json) = serde_json::from_slice::<Value>(bytes) {
+            redaction.redact_json(&mut json);
+            return Some(Self {
+                json: Some(json),
+                ..Self::default()
+            });
+        }
+        let is_form = headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("application/x-www-form-urlencoded"));
+        if !is_form {
+            return None;
+        }
+        let mut pairs = serde_urlencoded::from_bytes::<Vec<(String, String)>>(bytes).ok()?;
+        for (key, value) in pairs.iter_mut() {
+            if redaction.fields.contains(key) {
+                *value = REDACTED.into();
+            }
+        }
+        Some(Self {
+            text: Some(serde_urlencoded::to_string(pairs).ok()?),
+            ..Self::default()
+        })
+    }
+
+    fn verbatim(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Self {
+                text: Some(text.to_owned()),
+                ..Self::default()
+            },
+            Err(_) => Self {
+                base64: Some(BASE64.encode(bytes)),
+                ..Self::default()
+            },
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Compares bodies, treating a digest as equal to the body it was taken of.
+    fn matches(&self, other: &Self) -> bool {
+        if self == other {
+            return true;
+        }
+        match (self.digest(), other.digest()) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    fn digest(&self) -> Option<String> {
+        match self.sha256 {
+            Some(ref digest) => Some(digest.clone()),
+            None => self.to_bytes().ok().map(|bytes| sha256_hex(&bytes)),
+        }
+    }
+
+    fn to_bytes(&self) -> UniFiResult<Vec<u8>> {
+        if let Some(ref json) = self.json {
+            return Ok(serde_json::to_vec(json)?);
+        }
+        if let Some(ref text) = self.text {
+            return Ok(text.clone().into_bytes());
+        }
+        match self.base64 {
+            Some(ref encoded) => BASE64.decode(encoded.as_bytes()).map_err(|e| {
+                UniFiError::ConfigurationError(format!("Invalid base64 body in cassette: {e}"))
+            }),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    HEXLOWER.encode(&Sha256::digest(bytes))
+}
+
+/// What gets redacted before an interaction is recorded or matched.
+#[derive(Debug, Clone)]
+struct Redaction {
+    headers: HashSet<String>,
+    fields: HashSet<String>,
+    /// Store opaque request bodies instead of their digest.
+    raw_bodies: bool,
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self {
+            headers: SECRET_HEADERS.iter().map(|h| h.to_string()).collect(),
+            fields: SECRET_FIELDS.iter().map(|f| f.to_string()).collect(),
+            raw_bodies: false,
+        }
+    }
+}
+
+impl Redaction {
+    fn redact_json(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if self.fields.contains(key) {
+                        *value = Value::String(REDACTED.into());
+                    } else {
+                        self.redact_json(value);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|v| self.redact_json(v)),
+            _ => {}
+        }
+    }
+
+    fn headers(&self, headers: &HeaderMap) -> BTreeMap<String, Vec<String>> {
+        let mut recorded: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (name, value) in headers {
+            let value = if name == "set-cookie" {
+                redact_set_cookie(value)
+            } else if self.headers.contains(name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            recorded
+                .entry(name.as_str().to_owned())
+                .or_default()
+                .push(value);
+        }
+        recorded
+    }
+
+    fn request(&self, request: &TransportRequest) -> RecordedRequest {
+        RecordedRequest {
+            method: request.method().to_string(),
+            path: request.url().path().to_owned(),
+            query: request.url().query().map(str::to_owned),
+            headers: self.headers(request.headers()),
+            body: RecordedBody::request(
+                request.body().unwrap_or_default(),
+                request.headers(),
+                self,
+            ),
+        }
+    }
+}
+
+/// Keeps the cookie name and attributes, but not the value.
+fn redact_set_cookie(value: &HeaderValue) -> String {
+    let value = String::from_utf8_lossy(value.as_bytes());
+    let (pair, attributes) = value.split_once(';').unwrap_or((&value, ""));
+    let name = pair.split('=').next().unwrap_or_default().trim();
+    if attributes.is_empty() {
+        format!("{name}={REDACTED}")
+    } else {
+        format!("{name}={REDACTED};{attributes}")
+    }
+}
+
+fn transport_error(message: String) -> UniFiError {
+    TransportError::new(TransportErrorKind::Other, message).into()
+}
+
+/// A [`Transport`] that records all traffic through an inner transport to a
+/// cassette file.
+///
+/// Interactions are kept in memory and written by [`flush`](Self::flush) or
+/// when the transport is dropped, i.e. when the last clone of the client
+/// using it goes away. Recording never fails a request; a cassette that
+/// cannot be written on drop is reported through the `log` crate. Failed
+/// sends (connection errors, timeouts) are not recorded. Cookie access is
+/// passed through to the inner transport.
+#[derive(Debug)]
+pub struct RecordingTransport<T> {
+    inner: T,
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+    /// Set when interactions were recorded since the last write.
+    dirty: AtomicBool,
+    redaction: Redaction,
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    /// Records traffic sent through `inner` to the cassette at `path`,
+    /// replacing any existing file.
+    pub fn new(inner: T, path: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            path: path.into(),
+            cassette: Mutex::new(Cassette::default()),
+            dirty: AtomicBool::new(false),
+            redaction: Redaction::default(),
+        }
+    }
+
+    /// Also redacts the values of the header `name`.
+    pub fn redact_header(mut self, name: impl AsRef<str>) -> Self {
+        self.redaction
+            .headers
+            .insert(name.as_ref().to_ascii_lowercase());
+        self
+    }
+
+    /// Also redacts JSON and form fields called `name` in request and
+    /// response bodies.
+    pub fn redact_field(mut self, name: impl Into<String>) -> Self {
+        self.redaction.fields.insert(name.into());
+        self
+    }
+
+    /// Stores request bodies that are neither JSON nor form-encoded, such as
+    /// multipart uploads, verbatim instead of as a SHA-256 digest.
+    ///
+    /// Their contents are not redacted, so only enable this when they carry
+    /// no secrets.
+    pub fn record_raw_bodies(mut self, enabled: bool) -> Self {
+        self.redaction.raw_bodies = enabled;
+        self
+    }
+}
+
+impl<T> RecordingTransport<T> {
+    /// Writes the interactions recorded so far to the cassette file.
+    ///
+    /// This is blocking file I/O; call it from a blocking context or at the
+    /// end of a test. Dropping the transport flushes as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UniFiError::ConfigurationError` if the file cannot be written.
+    pub fn flush(&self) -> UniFiResult<()> {
+        self.dirty.store(false, Ordering::Release);
+        let cassette = self
+            .cassette
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let result = cassette.save(&self.path);
+        if result.is_err() {
+            self.dirty.store(true, Ordering::Release);
+        }
+        result
+    }
+}
+
+impl<T> Drop for RecordingTransport<T> {
+    fn drop(&mut self) {
+        if self.dirty.load(Ordering::Acquire) {
+            if let Err(e) = self.flush() {
+                log::error!("Failed to save cassette: {e}");
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<T: Transport> Transport for RecordingTransport<T> {
+    async fn send(&self, request: TransportRequest) -> UniFiResult<Response> {
+        let recorded = self.redaction.request(&request);
+        let response = self.inner.send(request).await?;
+
+        let interaction = Interaction {
+            request: recorded,
+            response: RecordedResponse {
+                status: response.status().as_u16(),
+                headers: self.redaction.headers(response.headers()),
+                body: RecordedBody::response(response.body(), response.headers(), &self.redaction),
+            },
+        };
+        self.cassette
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .interactions
+            .push(interaction);
+        self.dirty.store(true, Ordering::Release);
+        Ok(response)
+    }
+
+    fn cookies(&self, url: &Url) -> UniFiResult<Option<HeaderValue>> {
+        self.inner.cookies(url)
+    }
+
+    fn add_cookie(&self, cookie: &str, url: &Url) -> UniFiResult<()> {
+        self.inner.add_cookie(cookie, url)
+    }
+}
+
+/// What a request must have in common with a recorded one to be answered by
+/// it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MatchOn {
+    /// The HTTP method.
+    Method,
+    /// The URL path.
+    Path,
+    /// The query string.
+    Query,
+    /// The body, after redaction. Bodies recorded as a digest match the
+    /// bodies they were taken of.
+    Body,
+    /// The value of a header, after redaction.
+    Header(String),
+}
+
+/// A [`Transport`] that answers requests from a [`Cassette`].
+///
+/// Each request is answered by the first unused interaction that matches it
+/// according to the [`MatchOn`] rules (method, path and query by default),
+/// so repeated requests replay their recorded responses in order. Requests
+/// without a match fail with a [`TransportError`].
+#[derive(Debug)]
+pub struct ReplayTransport {
+    interactions: Vec<Interaction>,
+    used: Mutex<Vec<bool>>,
+    match_on: Vec<MatchOn>,
+    allow_reuse: bool,
+    redaction: Redaction,
+}
+
+impl ReplayTransport {
+    /// Replays the given cassette.
+    pub fn new(cassette: Cassette) -> Self {
+        let used = vec![false; cassette.interactions.len()];
+        Self {
+            interactions: cassette.interactions,
+            used: Mutex::new(used),
+            match_on: vec![MatchOn::Method, MatchOn::Path, MatchOn::Query],
+            allow_reuse: false,
+            redaction: Redaction::default(),
+        }
+    }
+
+    /// Replays the cassette at `path`; see [`Cassette::load`].
+    pub fn load(path: impl AsRef<Path>) -> UniFiResult<Self> {
+        Cassette::load(path).map(Self::new)
+    }
+
+    /// Sets the rules a request must satisfy to match a recorded one.
+    pub fn match_on(mut self, rules: impl IntoIterator<Item = MatchOn>) -> Self {
+        self.match_on = rules.into_iter().collect();
+        self
+    }
+
+    /// When enabled, a request whose matching interactions have all been used
+    /// replays the last of them again. Disabled by default.
+    pub fn allow_reuse(mut self, allow: bool) -> Self {
+        self.allow_reuse = allow;
+        self
+    }
+
+    /// Returns true once every recorded interaction has been replayed.
+    pub fn is_exhausted(&self) -> bool {
+        self.used
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .all(|used| *used)
+    }
+
+    fn matches(&self, recorded: &RecordedRequest, actual: &RecordedRequest) -> bool {
+        self.match_on.iter().all(|rule| match rule {
+            MatchOn::Method => recorded.method.eq_ignore_ascii_case(&actual.method),
+            MatchOn::Path => recorded.path == actual.path,
+            MatchOn::Query => recorded.query == actual.query,
+            MatchOn::Body => recorded.body.matches(&actual.body),
+            MatchOn::Header(name) => {
+                let name = name.to_ascii_lowercase();
+                recorded.headers.get(&name) == actual.headers.get(&name)
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    async fn send(&self, request: TransportRequest) -> UniFiResult<Response> {
+        let actual = self.redaction.request(&request);
+        let index = {
+            let mut used = self.used.lock().unwrap_or_else(|e| e.into_inner());
+            let candidates: Vec<usize> = (0..self.interactions.len())
+                .filter(|&i| self.matches(&self.interactions[i].request, &actual))
+                .collect();
+            let index = candidates
+                .iter()
+                .copied()
+                .find(|&i| !used[i])
+                .or_else(|| candidates.last().copied().filter(|_| self.allow_reuse))
+                .ok_or_else(|| {
+                    transport_error(format!(
+                        "No recorded interaction for {} {}",
+                        actual.method,
+                        request.url()
+                    ))
+                })?;
+            used[index] = true;
+            index
+        };
+
+        let recorded = &self.interactions[index].response;
+        let status = StatusCode::from_u16(recorded.status).map_err(|e| {
+            UniFiError::ConfigurationError(format!("Invalid status in cassette: {e}"))
+        })?;
+        let mut headers = HeaderMap::new();
+        for (name, values) in &recorded.headers {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+                UniFiError::ConfigurationError(format!("Invalid header in cassette: {e}"))
+            })?;
+            for value in values {
+                let value = HeaderValue::from_str(value).map_err(|e| {
+                    UniFiError::ConfigurationError(format!("Invalid header in cassette: {e}"))
+                })?;
+                headers.append(name.clone(), value);
+            }
+        }
+        Ok(Response::new(status, headers, recorded.body.to_bytes()?))
+    }
+}
+
+impl From<Cassette> for ReplayTransport {
+    fn from(cassette: Cassette) -> Self {
+        Self::new(cassette)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_secrets_in_bodies_and_headers() {
+        let redaction = Redaction::default();
+        let body = RecordedBody::request(
+            br#"{"username":"admin","password":"secret","nested":[{"x_passphrase":"wifi"}]}"#,
+            &HeaderMap::new(),
+            &redaction,
+        );
+        assert_eq!(
+            body.json,
+            Some(serde_json::json!({
+                "username": "admin",
+                "password": REDACTED,
+                "nested": [{ "x_passphrase": REDACTED }]
+            }))
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-csrf-token", HeaderValue::from_static("abc"));
+        headers.insert(
+            "set-cookie",
+            HeaderValue::from_static("TOKEN=abc; Path=/; HttpOnly"),
+        );
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+        let recorded = redaction.headers(&headers);
+        assert_eq!(recorded["x-csrf-token"], vec![REDACTED]);
+        assert_eq!(
+            recorded["set-cookie"],
+            vec![format!("TOKEN={REDACTED}; Path=/; HttpOnly")]
+        );
+        assert_eq!(recorded["content-type"], vec!["application/json"]);
+    }
+
+    #[test]
+    fn bodies_round_trip_through_every_encoding() {
+        let redaction = Redaction::default();
+        for bytes in [&b"plain text"[..], &[0xff, 0x00, 0xfe][..], &b""[..]] {
+            let body = RecordedBody::response(bytes, &HeaderMap::new(), &redaction);
+            assert_eq!(body.to_bytes().unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn redacts_form_bodies_and_hashes_opaque_request_bodies() {
+        let redaction = Redaction::default();
+        let mut form = HeaderMap::new();
+        form.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        );
+        let body = RecordedBody::request(b"username=admin&password=s3cret", &form, &redaction);
+        assert_eq!(
+            body.text.as_deref(),
+            Some("username=admin&password=%5BREDACTED%5D")
+        );
+
+        let multipart =
+            b"--b\r\nContent-Disposition: form-data; name=\"password\"\r\n\r\ns3cret\r\n--b--\r\n";
+        let body = RecordedBody::request(multipart, &HeaderMap::new(), &redaction);
+        assert_eq!(body.text, None);
+        assert_eq!(body.sha256, Some(sha256_hex(multipart)));
+
+        // Bodies kept verbatim on request still match their digest.
+        let raw = RecordedBody::request(
+            multipart,
+            &HeaderMap::new(),
+            &Redaction {
+                raw_bodies: true,
+                ..Redaction::default()
+            },
+        );
+        assert!(raw.text.is_some());
+        assert!(raw.matches(&body));
+        assert!(!body.matches(&RecordedBody::request(
+            b"other",
+            &HeaderMap::new(),
+            &redaction
+        )));
+    }
+
+    #[derive(Debug)]
+    struct Ok200;
+
+    #[async_trait]
+    impl Transport for Ok200 {
+        async fn send(&self, _request: TransportRequest) -> UniFiResult<Response> {
+            Ok(Response::new(StatusCode::OK, HeaderMap::new(), "ok"))
+        }
+    }
+
+    fn get() -> TransportRequest {
+        TransportRequest::new(
+            http::Method::GET,
+            Url::parse("https://unifi.example/api/self").unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn recording_writes_on_flush_and_drop_only() {
+        let path =
+            std::env::temp_dir().join(format!("unifi-client-{}-flush.json", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let recorder = RecordingTransport::new(Ok200, &path);
+        recorder.send(get()).await.unwrap();
+        assert!(!path.exists());
+        recorder.flush().unwrap();
+        assert_eq!(Cassette::load(&path).unwrap().interactions.len(), 1);
+
+        recorder.send(get()).await.unwrap();
+        drop(recorder);
+        assert_eq!(Cassette::load(&path).unwrap().interactions.len(), 2);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn unwritable_cassette_does_not_fail_requests() {
+        let path = std::env::temp_dir()
+            .join("unifi-client-missing-dir")
+            .join("cassette.json");
+        let recorder = RecordingTransport::new(Ok200, &path);
+        assert!(recorder.send(get()).await.is_ok());
+        assert!(matches!(
+            recorder.flush(),
+            Err(UniFiError::ConfigurationError(_))
+        ));
+    }
+
+    #[test]
+    fn matching_follows_the_configured_rules() {
+        let request = |method: &str| RecordedRequest {
+            method: method.into(),
+            path: "/api/self".into(),
+            query: None,
+            headers: BTreeMap::new(),
+            body: RecordedBody::default(),
+        };
+        let replay = ReplayTransport::new(Cassette::default());
+        assert!(replay.matches(&request("GET"), &request("get")));
+        assert!(!replay.matches(&request("GET"), &request("POST")));
+        assert!(ReplayTransport::new(Cassette::default())
+            .match_on([MatchOn::Path])
+            .matches(&request("GET"), &request("POST")));
+    }
+}
@@ -1,136 +1,176 @@
 use std::fmt;
 use std::future::Future;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
-
-#[cfg(feature = "default-client")]
-use arc_swap::ArcSwap;
-use http::Method;
-#[cfg(feature = "default-client")]
-use once_cell::sync::Lazy;
-use reqwest::header::HeaderValue;
+use std::time::{Duration, Instant};
+
+use http::{HeaderMap, HeaderValue, Method, StatusCode};
+use reqwest::cookie::Jar;
 use reqwest::redirect::Policy;
-use reqwest::{Client as ReqwestClient, StatusCode};
+use reqwest::Client as ReqwestClient;
 use secrecy::{ExposeSecret, SecretString};
 use serde::de::DeserializeOwned;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use tokio::io::AsyncWrite;
 use tokio::sync::{Mutex, RwLock};
 use url::Url;
 
-use crate::api::guests;
-use crate::models::ApiResponse;
-use crate::{models, UniFiError, UniFiResult};
-
-// Global default instance
-// Initializes to an inert default client; applications should call `initialize()`
-// early to replace it with a configured client.
-#[cfg(feature = "default-client")]
-static UNIFI_CLIENT: Lazy<ArcSwap<UniFiClient>> =
-    Lazy::new(|| ArcSwap::from_pointee(UniFiClient::default()));
-
+use crate::api::{guests, integration};
+use crate::body::{EncodedBody, RequestBody};
+use crate::credentials::CredentialProvider;
+use crate::error::ControllerError;
+use crate::mfa::MfaCodeProvider;
+use crate::middleware::{Middleware, MiddlewareRequest};
+use crate::models::{ApiResponse, V2ErrorResponse};
+use crate::retry::RetryPolicy;
+use crate::session::{SessionCookie, SessionState};
+use crate::throttle::{RateLimit, Throttle, ThrottleDelay};
+#[cfg(feature = "tls-pinning")]
+use crate::tls::CertificatePin;
+use crate::tls::{self, RootCertificate};
+use crate::transport::{ReqwestTransport, Response, Transport, TransportRequest};
+use crate::{models, telemetry, UniFiError, UniFiResult};
+
+const HEADER_API_KEY: &str = "x-api-key";
 const HEADER_CSRF_TOKEN: &str = "x-csrf-token";
 const HEADER_UPDATED_CSRF_TOKEN: &str = "x-updated-csrf-token";
 
 /// Helper to create a reqwest client builder to ensure consistent configuration.
-fn reqwest_builder(timeout: Duration, accept_invalid_certs: bool) -> reqwest::ClientBuilder {
+fn reqwest_builder(
+    timeout: Duration,
+    accept_invalid_certs: bool,
+    cookie_jar: Arc<Jar>,
+) -> reqwest::ClientBuilder {
     ReqwestClient::builder()
         .timeout(timeout)
         .danger_accept_invalid_certs(accept_invalid_certs)
         .redirect(Policy::none())
-        .cookie_store(true)
+        .cookie_provider(cookie_jar)
         .user_agent(concat!("unifi-client/", env!("CARGO_PKG_VERSION")))
 }
 
-/// Initializes the global UniFi client instance.
-///
-/// # Warning
-///
-/// Call this exactly once at application startup. Calling it again will
-/// replace the existing instance used by `instance()`.
-///
-/// # Arguments
-///
-/// - `client` - A fully constructed `UniFiClient`.
-///
-/// # Returns
-///
-/// - `Arc<UniFiClient>`: The previously configured global client instance. On the first call this
-///   will be the inert default instance.
-///
-/// # Examples
-///
-/// Basic initialization:
-/// ```no_run
-/// # use unifi_client::UniFiClient;
-/// # #[tokio::main]
-/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-/// let client = UniFiClient::builder()
-///     .controller_url("https://controller.example:8443")
-///     .username("admin")
-///     .password("secret")
-///     // .accept_invalid_certs(true) // only for lab/test
-///     .build()
-///     .await?;
-/// let _prev = unifi_client::initialize(client);
-/// # Ok(())
-/// # }
-/// ```
+/// Builds the proxy configured with [`UniFiClientBuilder::proxy`].
+fn reqwest_proxy(url: &str, auth: Option<&(String, SecretString)>) -> UniFiResult<reqwest::Proxy> {
+    let invalid = |reason: &dyn fmt::Display| {
+        UniFiError::ConfigurationError(format!("Invalid proxy URL: {reason}"))
+    };
+    let url = Url::parse(url).map_err(|e| invalid(&e))?;
+    if !matches!(url.scheme(), "http" | "https" | "socks5" | "socks5h") {
+        return Err(invalid(&format!("unsupported scheme `{}`", url.scheme())));
+    }
+    let proxy = reqwest::Proxy::all(url).map_err(|e| invalid(&e))?;
+    Ok(match auth {
+        Some((username, password)) => proxy.basic_auth(username, password.expose_secret()),
+        None => proxy,
+    })
+}
+
+/// Detects the controller kind and, where reported, the Network application version.
 ///
-/// Swapping instances (e.g., tests or hot-reload scenarios):
-/// ```no_run
-/// # use unifi_client::UniFiClient;
-/// # #[tokio::main]
-/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-/// let client_a = UniFiClient::builder()
-///     .controller_url("https://a.example:8443")
-///     .username("user_a")
-///     .password("pass_a")
-///     .build()
-///     .await?;
-/// let _ = unifi_client::initialize(client_a);
+/// Probes, in order:
+/// 1. `GET /api/system`: UniFi OS consoles and UniFi OS Server answer with a JSON object describing
+///    the console.
+/// 2. `GET /status`: classic Network controllers answer with `{ "meta": { "server_version": .. }
+///    }`.
+/// 3. `POST /api/auth/login` and `POST /api/login` with empty credentials: UniFi OS rejects the
+///    former with a bare JSON error, classic controllers reject the latter with a `meta` envelope.
 ///
-/// let client_b = UniFiClient::builder()
-///     .controller_url("https://b.example:8443")
-///     .username("user_b")
-///     .password("pass_b")
-///     .build()
-///     .await?;
-/// let previous = unifi_client::initialize(client_b);
-/// // `previous` is the prior global client (client_a).
-/// # Ok(())
-/// # }
-/// ```
-#[cfg(feature = "default-client")]
-#[cfg_attr(docsrs, doc(cfg(feature = "default-client")))]
-pub fn initialize(client: UniFiClient) -> Arc<UniFiClient> {
-    // Swap in the provided client and return the previous instance.
-    UNIFI_CLIENT.swap(Arc::new(client))
+/// Fails with [`UniFiError::ConfigurationError`] when none of the probes matches; set the kind
+/// with [`UniFiClientBuilder::controller_kind`] in that case. Connection failures abort detection
+/// instead of being mistaken for either kind.
+async fn detect_controller(
+    transport: &dyn Transport,
+    controller_url: &Url,
+) -> UniFiResult<(ControllerKind, Option<String>)> {
+    let status_version = |status: Option<Value>| {
+        status
+            .as_ref()
+            .and_then(|v| v.pointer("/meta/server_version"))
+            .and_then(Value::as_str)
+            .map(str::to_owned)
+    };
+
+    if let Some(Value::Object(_)) =
+        probe_json(transport, controller_url.join("/api/system")?).await?
+    {
+        let status = probe_json(transport, controller_url.join("/proxy/network/status")?).await?;
+        return Ok((ControllerKind::Os, status_version(status)));
+    }
+
+    let status = probe_json(transport, controller_url.join("/status")?).await?;
+    if let Some(version) = status_version(status) {
+        return Ok((ControllerKind::Network, Some(version)));
+    }
+
+    // Classic controllers wrap every `/api` error, including unknown paths, in `meta`.
+    if let Some(Value::Object(body)) =
+        probe_login(transport, controller_url.join("/api/auth/login")?).await?
+    {
+        if !body.contains_key("meta") {
+            let status =
+                probe_json(transport, controller_url.join("/proxy/network/status")?).await?;
+            return Ok((ControllerKind::Os, status_version(status)));
+        }
+    }
+    if let Some(Value::Object(body)) =
+        probe_login(transport, controller_url.join("/api/login")?).await?
+    {
+        if body.contains_key("meta") {
+            return Ok((ControllerKind::Network, None));
+        }
+    }
+
+    Err(UniFiError::ConfigurationError(format!(
+        "Could not detect the controller kind at {controller_url}; set it with controller_kind()"
+    )))
 }
 
-/// Returns a reference to the global UniFi client instance.
-///
-/// # Returns
+/// Sends an unauthenticated `GET` and returns the JSON body of a successful response.
 ///
-/// - `Arc<UniFiClient>`: A thread-safe handle to the current client. If `initialize()` hasn't been
-///   called, a default (unauthenticated) client is returned.
+/// Returns `None` for error statuses and non-JSON bodies; only transport errors are propagated.
+async fn probe_json(transport: &dyn Transport, url: Url) -> UniFiResult<Option<Value>> {
+    let response = transport
+        .send(TransportRequest::new(Method::GET, url))
+        .await?;
+    if !response.status().is_success() {
+        return Ok(None);
+    }
+    Ok(response.json().ok())
+}
+
+/// Sends a login with empty credentials and returns the JSON body of the rejection.
 ///
-/// # Examples
+/// Returns `None` for `404 Not Found`, successful statuses and non-JSON bodies; only transport
+/// errors are propagated.
+async fn probe_login(transport: &dyn Transport, url: Url) -> UniFiResult<Option<Value>> {
+    let request = TransportRequest::new(Method::POST, url).json(&serde_json::json!({}))?;
+    let response = transport.send(request).await?;
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND || status.is_success() {
+        return Ok(None);
+    }
+    Ok(response.json().ok())
+}
+
+/// Returns true if a failed login response is a two-factor authentication challenge.
 ///
-/// ```no_run
-/// # use unifi_client::UniFiError;
-/// # #[tokio::main]
-/// # async fn main() -> Result<(), UniFiError> {
-/// let client = unifi_client::instance();
-/// // Use `client` to perform requests...
-/// # Ok(())
-/// # }
-/// ```
-#[cfg(feature = "default-client")]
-#[cfg_attr(docsrs, doc(cfg(feature = "default-client")))]
-pub fn instance() -> Arc<UniFiClient> {
-    UNIFI_CLIENT.load_full()
+/// - UniFi OS answers with status 499 and `{ "code": "MFA_AUTH_REQUIRED", ... }`
+/// - Classic Network controllers answer with `meta.msg` set to `api.err.Ubic2faTokenRequired`
+fn is_mfa_challenge(kind: ControllerKind, status: StatusCode, body: &[u8]) -> bool {
+    match kind {
+        ControllerKind::Os => {
+            status.as_u16() == 499
+                || serde_json::from_slice::<Value>(body)
+                    .ok()
+                    .and_then(|v| v.get("code")?.as_str().map(|c| c == "MFA_AUTH_REQUIRED"))
+                    .unwrap_or(false)
+        }
+        ControllerKind::Network => serde_json::from_slice::<ApiResponse<Value>>(body)
+            .map(|r| r.meta.msg.as_deref() == Some("api.err.Ubic2faTokenRequired"))
+            .unwrap_or(false),
+    }
 }
 
 /// Builder for UniFi client.
@@ -142,12 +182,35 @@ pub struct UniFiClientBuilder {
     controller_url: Option<String>,
     username: Option<String>,
     password: Option<SecretString>,
+    password_provider: Option<Arc<dyn CredentialProvider>>,
+    api_key: Option<SecretString>,
+    mfa: Option<Arc<dyn MfaCodeProvider>>,
+    #[cfg(feature = "totp")]
+    totp_secret: Option<SecretString>,
     site: Option<String>,
+    controller_kind: Option<ControllerKind>,
     /// When `true`, TLS certificates are **not** verified (dangerous).
     /// Defaults to `false` (secure-by-default).
     accept_invalid_certs: bool,
+    root_certificates: Vec<RootCertificate>,
+    #[cfg(feature = "tls-pinning")]
+    pinned_certificate: Option<String>,
+    #[cfg(feature = "tls-pinning")]
+    trust_on_first_use: bool,
     timeout: Option<Duration>,
+    proxy: Option<String>,
+    proxy_auth: Option<(String, SecretString)>,
+    resolve: Vec<(String, IpAddr)>,
     http_client: Option<ReqwestClient>,
+    transport: Option<Arc<dyn Transport>>,
+    #[cfg(feature = "cassettes")]
+    cassette: Option<std::path::PathBuf>,
+    session: Option<SecretString>,
+    session_key: Option<SecretString>,
+    retry_policy: Option<RetryPolicy>,
+    rate_limit: Option<RateLimit>,
+    max_concurrent_requests: Option<usize>,
+    middlewares: Vec<Arc<dyn Middleware>>,
 }
 
 impl UniFiClientBuilder {
@@ -171,6 +234,11 @@ impl UniFiClientBuilder {
 
     /// Sets the password from an environment variable.
     ///
+    /// The variable is read once. Use
+    /// [`EnvCredentialProvider`](crate::EnvCredentialProvider) with
+    /// [`password_provider`](Self::password_provider) to read it on every
+    /// login instead.
+    ///
     /// # Panics
     ///
     /// Panics if the environment variable cannot be read.
@@ -182,37 +250,277 @@ impl UniFiClientBuilder {
         self
     }
 
+    /// Sets where the password comes from, instead of a fixed
+    /// [`password`](Self::password).
+    ///
+    /// The provider is consulted on every login, including re-logins after
+    /// the session expires, so rotated passwords are picked up without
+    /// rebuilding the client.
+    pub fn password_provider(mut self, provider: impl CredentialProvider + 'static) -> Self {
+        self.password_provider = Some(Arc::new(provider));
+        self
+    }
+
+    /// Sets a UniFi OS API key for authentication.
+    ///
+    /// API keys are sent as an `X-API-KEY` header on every request. No login
+    /// is performed and no session or CSRF token is used, so this cannot be
+    /// combined with `username`/`password`.
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(SecretString::from(api_key.into()));
+        self
+    }
+
+    /// Sets the provider of one-time codes for accounts with two-factor
+    /// authentication enforced.
+    ///
+    /// The provider is consulted whenever the controller answers a login with
+    /// an MFA challenge, including re-logins after the session expires.
+    pub fn mfa_code_provider(mut self, provider: impl MfaCodeProvider + 'static) -> Self {
+        self.mfa = Some(Arc::new(provider));
+        self
+    }
+
+    /// Sets the base32 TOTP secret used to generate two-factor codes.
+    ///
+    /// This is the secret shown when enrolling an authenticator app. It takes
+    /// precedence over [`mfa_code_provider`](Self::mfa_code_provider).
+    #[cfg(feature = "totp")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "totp")))]
+    pub fn totp_secret(mut self, secret: impl Into<String>) -> Self {
+        self.totp_secret = Some(SecretString::from(secret.into()));
+        self
+    }
+
     /// Sets the site name (e.g., `default`, `qc4lt5rs`) to use.
     pub fn site(mut self, site: impl Into<String>) -> Self {
         self.site = Some(site.into());
         self
     }
 
+    /// Sets the controller kind instead of detecting it.
+    ///
+    /// Use this for controllers behind reverse proxies or on custom ports
+    /// where detection is unreliable. When set, no probe requests are sent and
+    /// [`UniFiClient::controller_version`] is `None`.
+    pub fn controller_kind(mut self, kind: ControllerKind) -> Self {
+        self.controller_kind = Some(kind);
+        self
+    }
+
     /// Accept invalid/self-signed TLS certificates (dangerous).
     ///
-    /// Default is `false` (certificates are verified).
+    /// Default is `false` (certificates are verified). For self-signed
+    /// controllers, prefer [`add_root_certificate_pem`](Self::add_root_certificate_pem) or,
+    /// with the `tls-pinning` feature, `pin_certificate_sha256`.
     pub fn accept_invalid_certs(mut self, accept: bool) -> Self {
         self.accept_invalid_certs = accept;
         self
     }
 
+    /// Trusts an additional root certificate in PEM format, e.g. the
+    /// controller's self-signed certificate or a private CA.
+    ///
+    /// Cannot be combined with certificate pinning; [`build`](Self::build)
+    /// fails if both are configured.
+    pub fn add_root_certificate_pem(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates
+            .push(RootCertificate::Pem(pem.into()));
+        self
+    }
+
+    /// Trusts an additional root certificate in DER format.
+    ///
+    /// Cannot be combined with certificate pinning; [`build`](Self::build)
+    /// fails if both are configured.
+    pub fn add_root_certificate_der(mut self, der: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates
+            .push(RootCertificate::Der(der.into()));
+        self
+    }
+
+    /// Pins the controller's leaf certificate by its SHA-256 fingerprint.
+    ///
+    /// The fingerprint is 64 hex digits, optionally colon-separated as printed
+    /// by `openssl x509 -noout -fingerprint -sha256`. Connections are only
+    /// accepted if the controller presents exactly this certificate, checked
+    /// during the TLS handshake before anything is sent. The pin replaces CA
+    /// and hostname validation, so self-signed certificates work without
+    /// disabling verification.
+    ///
+    /// Pinned connections use rustls instead of the default TLS backend and
+    /// ignore root certificates, so [`build`](Self::build) fails if root
+    /// certificates are added as well.
+    #[cfg(feature = "tls-pinning")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tls-pinning")))]
+    pub fn pin_certificate_sha256(mut self, fingerprint: impl Into<String>) -> Self {
+        self.pinned_certificate = Some(fingerprint.into());
+        self
+    }
+
+    /// Pins whatever certificate the controller presents first
+    /// (trust-on-first-use) when no fingerprint is configured.
+    ///
+    /// The learned fingerprint is available from
+    /// [`UniFiClient::pinned_certificate_sha256`]; persist it and pass it to
+    /// [`pin_certificate_sha256`](Self::pin_certificate_sha256) on the next
+    /// run so a changed certificate is detected.
+    ///
+    /// Like [`pin_certificate_sha256`](Self::pin_certificate_sha256), cannot
+    /// be combined with root certificates.
+    #[cfg(feature = "tls-pinning")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tls-pinning")))]
+    pub fn trust_on_first_use(mut self, enabled: bool) -> Self {
+        self.trust_on_first_use = enabled;
+        self
+    }
+
     /// Sets the HTTP request timeout.
     pub fn timeout(mut self, timeout: Duration) -> Self {
         self.timeout = Some(timeout);
         self
     }
 
+    /// Routes all traffic through a proxy.
+    ///
+    /// Supports `http://` and `https://` proxies, which tunnel TLS with
+    /// `CONNECT`, and `socks5://` and `socks5h://` proxies (the latter resolve
+    /// the controller hostname on the proxy). Credentials can be embedded in
+    /// the URL or set with [`proxy_auth`](Self::proxy_auth). The proxy is
+    /// added to the client's own HTTP settings, so cookies, redirect handling
+    /// and certificate checks are unchanged; `HTTP_PROXY` and related
+    /// environment variables are ignored once a proxy is set.
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// Sets the username and password for the [`proxy`](Self::proxy), sent as
+    /// `Proxy-Authorization: Basic` to HTTP proxies and as SOCKS5
+    /// username/password authentication.
+    pub fn proxy_auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.proxy_auth = Some((username.into(), SecretString::from(password.into())));
+        self
+    }
+
+    /// Resolves `host` to `addr` instead of using DNS.
+    ///
+    /// The port from the controller URL is still used. TLS certificates are
+    /// verified against `host`, not the address. Can be called repeatedly to
+    /// override several hosts.
+    pub fn resolve(mut self, host: impl Into<String>, addr: IpAddr) -> Self {
+        self.resolve.push((host.into(), addr));
+        self
+    }
+
+    /// Sets the policy for retrying transient failures such as connection
+    /// errors, timeouts, and `429`/`502`/`503`/`504` responses.
+    ///
+    /// Defaults to [`RetryPolicy::none`], i.e. transient failures are returned
+    /// to the caller immediately.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Limits the rate of requests sent to the controller.
+    ///
+    /// The limit is shared by all clones of the client. Requests over the limit
+    /// wait instead of failing; see [`ThrottleDelay`] for how long a request
+    /// waited. Retries count against the limit, logins do not.
+    ///
+    /// Defaults to no limit.
+    pub fn rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Limits how many requests may be in flight at once.
+    ///
+    /// The limit is shared by all clones of the client. A request holds its
+    /// slot until the response has been received.
+    ///
+    /// Defaults to no limit.
+    pub fn max_concurrent_requests(mut self, max: usize) -> Self {
+        self.max_concurrent_requests = Some(max);
+        self
+    }
+
+    /// Registers a middleware that runs around every request.
+    ///
+    /// Middlewares run in registration order before the request is sent and in
+    /// reverse order afterwards. Logins are not passed through middlewares.
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+
     /// Sets a custom reqwest client (e.g., for testing or custom middleware).
     pub fn http_client(mut self, http_client: ReqwestClient) -> Self {
         self.http_client = Some(http_client);
         self
     }
 
+    /// Sends all traffic through a custom [`Transport`] instead of reqwest.
+    ///
+    /// Authentication, CSRF handling, retries, throttling and middlewares run
+    /// on top of the transport as usual. TLS settings and the timeout are the
+    /// transport's business, so they cannot be combined with it, and neither
+    /// can [`http_client`](Self::http_client).
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Records every request and response to a cassette file at `path`.
+    ///
+    /// Wraps the transport in a
+    /// [`RecordingTransport`](crate::cassette::RecordingTransport), including
+    /// detection probes and logins, with credentials, cookies and CSRF tokens
+    /// redacted. The file is written when the last clone of the client is
+    /// dropped. Replay it later with
+    /// [`ReplayTransport`](crate::cassette::ReplayTransport).
+    #[cfg(feature = "cassettes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cassettes")))]
+    pub fn record_cassette(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.cassette = Some(path.into());
+        self
+    }
+
+    /// Restores a session previously exported with
+    /// [`UniFiClient::export_session`].
+    ///
+    /// The restored cookies and CSRF token are used instead of performing an
+    /// initial login. Credentials are still required: if the controller
+    /// rejects the restored session (401), the client falls back to a regular
+    /// login. Restoring requires the built-in cookie store, so it cannot be
+    /// combined with [`http_client`](Self::http_client), and the
+    /// [`session_key`](Self::session_key) the session was exported with.
+    pub fn restore_session(mut self, session: impl Into<SecretString>) -> Self {
+        self.session = Some(session.into());
+        self
+    }
+
+    /// Sets the key that encrypts exported sessions and decrypts restored
+    /// ones. Required by [`UniFiClient::export_session`] and
+    /// [`restore_session`](Self::restore_session).
+    ///
+    /// Sessions are encrypted and authenticated with ChaCha20-Poly1305 under
+    /// a key derived from `key`, so their cookies and CSRF token cannot be
+    /// read without it, and blobs that were modified or encrypted with
+    /// another key are rejected. Use a long random value, stored apart from
+    /// the sessions themselves.
+    pub fn session_key(mut self, key: impl Into<SecretString>) -> Self {
+        self.session_key = Some(key.into());
+        self
+    }
+
     /// Builds and authenticates a `UniFiClient`.
     ///
     /// This constructs the HTTP client, detects the controller kind
     /// (UniFi OS vs. Network), configures `api_base_url`, and performs an
-    /// initial login.
+    /// initial login. When an API key is configured or a session is restored,
+    /// the login is skipped.
     ///
     /// # Example
     ///
@@ -234,16 +542,54 @@ impl UniFiClientBuilder {
         let site = self.site.unwrap_or_else(|| "default".to_string());
 
         let timeout = self.timeout.unwrap_or(Duration::from_secs(30));
-
-        let username = self
-            .username
-            .filter(|u| !u.trim().is_empty())
-            .ok_or_else(|| UniFiError::ConfigurationError("Username is required".into()))?;
-
-        let password = self
-            .password
-            .filter(|p| !p.expose_secret().trim().is_empty())
-            .ok_or_else(|| UniFiError::ConfigurationError("Password is required".into()))?;
+        let throttle = Arc::new(Throttle::new(
+            self.rate_limit,
+            self.max_concurrent_requests,
+        )?);
+
+        let (username, password, api_key) = match self.api_key {
+            Some(api_key) => {
+                if api_key.expose_secret().trim().is_empty() {
+                    return Err(UniFiError::ConfigurationError(
+                        "API key must not be empty".into(),
+                    ));
+                }
+                if self.username.is_some()
+                    || self.password.is_some()
+                    || self.password_provider.is_some()
+                {
+                    return Err(UniFiError::ConfigurationError(
+                        "API key cannot be combined with username/password".into(),
+                    ));
+                }
+                (String::new(), None, Some(api_key))
+            }
+            None => {
+                let username = self
+                    .username
+                    .filter(|u| !u.trim().is_empty())
+                    .ok_or_else(|| UniFiError::ConfigurationError("Username is required".into()))?;
+
+                let password: Arc<dyn CredentialProvider> =
+                    match (self.password, self.password_provider) {
+                        (Some(_), Some(_)) => {
+                            return Err(UniFiError::ConfigurationError(
+                                "A password cannot be combined with a password provider".into(),
+                            ))
+                        }
+                        (None, Some(provider)) => provider,
+                        (password, None) => Arc::new(
+                            password
+                                .filter(|p| !p.expose_secret().trim().is_empty())
+                                .ok_or_else(|| {
+                                    UniFiError::ConfigurationError("Password is required".into())
+                                })?,
+                        ),
+                    };
+
+                (username, Some(password), None)
+            }
+        };
 
         let controller_url = self
             .controller_url
@@ -254,54 +600,184 @@ impl UniFiClientBuilder {
                 })
             })?;
 
-        let http_client = if let Some(custom_client) = self.http_client {
-            custom_client
+        #[cfg(feature = "totp")]
+        let mfa = match self.totp_secret {
+            Some(secret) => Some(Arc::new(crate::mfa::TotpCodeProvider::from_base32(
+                secret.expose_secret(),
+            )?) as Arc<dyn MfaCodeProvider>),
+            None => self.mfa,
+        };
+        #[cfg(not(feature = "totp"))]
+        let mfa = self.mfa;
+
+        let restored = self
+            .session
+            .as_ref()
+            .map(|session| {
+                let key = self.session_key.as_ref().ok_or_else(|| {
+                    UniFiError::ConfigurationError(
+                        "Restoring a session requires a session key".into(),
+                    )
+                })?;
+                SessionState::decode(session, key)
+            })
+            .transpose()?;
+        if let Some(ref state) = restored {
+            if api_key.is_some() {
+                return Err(UniFiError::ConfigurationError(
+                    "API key cannot be combined with a restored session".into(),
+                ));
+            }
+            if state.controller_url != controller_url.as_str() {
+                return Err(UniFiError::ConfigurationError(
+                    "Restored session belongs to a different controller URL".into(),
+                ));
+            }
+        }
+
+        #[cfg(feature = "tls-pinning")]
+        let certificate_pin = if self.pinned_certificate.is_some() || self.trust_on_first_use {
+            if self.accept_invalid_certs {
+                return Err(UniFiError::ConfigurationError(
+                    "Certificate pinning cannot be combined with accept_invalid_certs".into(),
+                ));
+            }
+            if !self.root_certificates.is_empty() {
+                return Err(UniFiError::ConfigurationError(
+                    "Certificate pinning cannot be combined with root certificates".into(),
+                ));
+            }
+            Some(Arc::new(CertificatePin::new(
+                self.pinned_certificate.as_deref(),
+                self.trust_on_first_use,
+            )?))
         } else {
-            reqwest_builder(timeout, self.accept_invalid_certs)
-                .build()
-                .map_err(|e| {
-                    UniFiError::ConfigurationError(format!("Failed to create HTTP client: {e}"))
-                })?
+            None
         };
 
-        // Detect controller kind with a lightweight HEAD request to '/'
-        let probe_url = controller_url
-            .join("/")
-            .map_err(|e| UniFiError::UrlParseError(e))?;
-        let probe_status = http_client
-            .head(probe_url)
-            .send()
-            .await
-            .map(|r| r.status())
-            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        if self.transport.is_some() || self.http_client.is_some() {
+            if self.transport.is_some() && self.http_client.is_some() {
+                return Err(UniFiError::ConfigurationError(
+                    "A custom transport cannot be combined with a custom HTTP client".into(),
+                ));
+            }
+            #[cfg(feature = "tls-pinning")]
+            let custom_tls = certificate_pin.is_some() || !self.root_certificates.is_empty();
+            #[cfg(not(feature = "tls-pinning"))]
+            let custom_tls = !self.root_certificates.is_empty();
+            if custom_tls {
+                return Err(UniFiError::ConfigurationError(
+                    "Root certificates and pinning cannot be applied to a custom HTTP client"
+                        .into(),
+                ));
+            }
+            if self.proxy.is_some() || !self.resolve.is_empty() {
+                return Err(UniFiError::ConfigurationError(
+                    "Proxy and resolve overrides cannot be applied to a custom HTTP client".into(),
+                ));
+            }
+        }
 
-        // UniFi OS servers return 200, UniFi Network controllers return 304 redirects.
-        let controller_kind = if probe_status == StatusCode::OK {
-            ControllerKind::Os
+        let transport: Arc<dyn Transport> = if let Some(transport) = self.transport {
+            transport
+        } else if let Some(custom_client) = self.http_client {
+            if restored.is_some() {
+                return Err(UniFiError::ConfigurationError(
+                    "Restoring a session requires the built-in cookie store".into(),
+                ));
+            }
+            Arc::new(ReqwestTransport::new(custom_client))
         } else {
-            ControllerKind::Network
+            let cookie_jar = Arc::new(Jar::default());
+            let mut builder =
+                reqwest_builder(timeout, self.accept_invalid_certs, Arc::clone(&cookie_jar));
+            if let Some(ref url) = self.proxy {
+                builder = builder.proxy(reqwest_proxy(url, self.proxy_auth.as_ref())?);
+            } else if self.proxy_auth.is_some() {
+                return Err(UniFiError::ConfigurationError(
+                    "Proxy credentials require a proxy".into(),
+                ));
+            }
+            for (host, addr) in &self.resolve {
+                // reqwest ignores the port here and uses the one from the URL.
+                builder = builder.resolve(host, SocketAddr::new(*addr, 0));
+            }
+            builder = tls::configure(builder, &self.root_certificates)?;
+            #[cfg(feature = "tls-pinning")]
+            if let Some(pin) = &certificate_pin {
+                builder = tls::configure_pin(builder, pin)?;
+            }
+            let http_client = builder.build().map_err(|e| {
+                UniFiError::ConfigurationError(format!("Failed to create HTTP client: {e}"))
+            })?;
+            Arc::new(ReqwestTransport::with_cookie_jar(http_client, cookie_jar))
+        };
+        #[cfg(feature = "cassettes")]
+        let transport: Arc<dyn Transport> = match self.cassette {
+            Some(path) => Arc::new(crate::cassette::RecordingTransport::new(transport, path)),
+            None => transport,
         };
 
-        let api_base_url = match controller_kind {
-            ControllerKind::Os => controller_url
-                .join("/proxy/network")
-                .map_err(UniFiError::UrlParseError)?,
-            ControllerKind::Network => controller_url.clone(),
+        let (controller_kind, controller_version, api_base_url) = match restored {
+            // A restored session already knows the controller layout; skip the probe.
+            Some(ref state) => (
+                state.controller_kind,
+                None,
+                Url::parse(&state.api_base_url)?,
+            ),
+            None => {
+                let (controller_kind, controller_version) = match self.controller_kind {
+                    Some(kind) => (kind, None),
+                    None => detect_controller(transport.as_ref(), &controller_url).await?,
+                };
+
+                let api_base_url = match controller_kind {
+                    ControllerKind::Os => controller_url
+                        .join("/proxy/network")
+                        .map_err(UniFiError::UrlParseError)?,
+                    ControllerKind::Network => controller_url.clone(),
+                };
+
+                (controller_kind, controller_version, api_base_url)
+            }
         };
 
         let client = UniFiClient {
             controller_kind,
+            controller_version,
             controller_url,
             api_base_url,
             username,
-            password: Some(password),
+            password,
+            api_key,
+            mfa,
             site,
-            http_client,
+            transport,
+            retry_policy: self.retry_policy.unwrap_or_else(RetryPolicy::none),
+            throttle,
+            middlewares: self.middlewares,
+            #[cfg(feature = "tls-pinning")]
+            certificate_pin,
+            session_key: self.session_key,
             auth: Arc::new(AuthState::new()),
         };
 
-        // Perform initial login to fail fast if authentication fails.
-        client.login().await?;
+        if let Some(state) = restored {
+            // Reuse the exported session; a 401 on first use falls back to `login()`.
+            for cookie in &state.cookies {
+                client
+                    .transport
+                    .add_cookie(&cookie.pair, &Url::parse(&cookie.url)?)?;
+            }
+            client.auth.establish_session(state.csrf_token).await;
+        } else if client.api_key.is_some() {
+            // API keys are stateless: there is no session to establish, so just mark the client
+            // as authenticated (and without a CSRF token).
+            client.auth.establish_session(None::<SecretString>).await;
+        } else {
+            // Perform initial login to fail fast if authentication fails.
+            client.login().await?;
+        }
         Ok(client)
     }
 }
@@ -318,8 +794,26 @@ struct AuthState {
     reauth_lock: Mutex<()>,
 
     /// The epoch/generation counter to prevent the "thundering herd".
-    /// Incremented on successful login to signal a new session.
+    /// Incremented on every successful login and logout to signal a session
+    /// change; never decreases. `0` means no login has happened yet.
     epoch: AtomicUsize,
+
+    /// Set by `logout()` and cleared by the next login, to tell a logged-out
+    /// client apart from one that never logged in.
+    logged_out: AtomicBool,
+
+    /// Logout to send when the last client clone drops the shared state.
+    /// Registered by [`LogoutGuard`](crate::LogoutGuard).
+    logout_on_drop: std::sync::Mutex<Option<LogoutOnDrop>>,
+}
+
+/// What the drop of [`AuthState`] needs to end the session on the controller.
+#[derive(Debug)]
+struct LogoutOnDrop {
+    runtime: tokio::runtime::Handle,
+    transport: Arc<dyn Transport>,
+    url: Url,
+    controller_kind: ControllerKind,
 }
 
 impl AuthState {
@@ -328,6 +822,8 @@ impl AuthState {
             csrf_token: RwLock::new(None),
             reauth_lock: Mutex::new(()),
             epoch: AtomicUsize::new(0),
+            logged_out: AtomicBool::new(false),
+            logout_on_drop: std::sync::Mutex::new(None),
         }
     }
 
@@ -337,20 +833,31 @@ impl AuthState {
     }
 
     fn is_authenticated(&self) -> bool {
-        self.epoch() != 0
+        !self.logged_out.load(Ordering::Acquire) && self.epoch() != 0
     }
 
     /// Rotate CSRF token mid-session when server provides a new value (only present for UniFi OS).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "unifi.csrf_rotate",
+            level = "debug",
+            skip_all,
+            fields(auth_epoch = self.epoch())
+        )
+    )]
     async fn rotate_csrf(&self, token: impl Into<SecretString>) {
         // Convert to owned secret **before** any await to avoid borrowing across await.
         let token: SecretString = token.into();
         let mut w = self.csrf_token.write().await;
         *w = Some(token);
+        telemetry::record_csrf_rotation();
     }
 
     /// Apply the results of a successful authentication:
     /// - set/clear the CSRF token (OS: Some, Network: None)
     /// - advance the auth epoch
+    ///
     /// Returns the new epoch.
     async fn establish_session<T>(&self, csrf_token: Option<T>) -> usize
     where
@@ -361,12 +868,24 @@ impl AuthState {
             *w = csrf_token.map(Into::into);
         }
 
+        self.logged_out.store(false, Ordering::Release);
+
         // Advance the auth generation counter and return the new value.
         // Uses `AcqRel` so writes that happen before bumping (e.g., CSRF update)
         // are visible to tasks that observe the new epoch with `Acquire` loads.
         self.epoch.fetch_add(1, Ordering::AcqRel) + 1
     }
 
+    /// Forget the current session: clear the CSRF token, mark the client as
+    /// logged out and advance the epoch, so re-authentications that started
+    /// before the logout see a session change instead of a reused epoch.
+    async fn clear_session(&self) {
+        let mut w = self.csrf_token.write().await;
+        *w = None;
+        self.logged_out.store(true, Ordering::Release);
+        self.epoch.fetch_add(1, Ordering::AcqRel);
+    }
+
     /// Deduplicate concurrent re-auth attempts.
     ///
     /// Semantics:
@@ -377,6 +896,14 @@ impl AuthState {
     /// Returns:
     /// - `Ok(true)`  if this call performed the login (was the leader).
     /// - `Ok(false)` if another task already completed re-authentication while we waited.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "unifi.reauth",
+            skip_all,
+            fields(auth_epoch = tracing::field::Empty, role = tracing::field::Empty)
+        )
+    )]
     async fn dedupe_reauthentication<F, Fut>(&self, login_fn: F) -> UniFiResult<bool>
     where
         F: FnOnce() -> Fut,
@@ -384,6 +911,8 @@ impl AuthState {
     {
         // Get the current epoch (or 0 if not authenticated).
         let epoch_before = self.epoch();
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("auth_epoch", epoch_before);
 
         // Acquire the lock to serialize authentication attempts.
         let _guard = self.reauth_lock.lock().await;
@@ -391,18 +920,66 @@ impl AuthState {
         // Check if another thread already re-authenticated while we waited.
         if self.epoch() == epoch_before {
             // Still stale: we are the leader; perform re-authentication.
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("role", "leader");
             login_fn().await?;
             return Ok(true);
         }
 
         // Another thread already re-authenticated while we waited.
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("role", "follower");
         Ok(false)
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum ControllerKind {
+impl Drop for AuthState {
+    fn drop(&mut self) {
+        let hook = match self.logout_on_drop.get_mut() {
+            Ok(hook) => hook.take(),
+            Err(poisoned) => poisoned.into_inner().take(),
+        };
+        let Some(hook) = hook else {
+            return;
+        };
+        if !self.is_authenticated() {
+            return;
+        }
+
+        let mut request = TransportRequest::new(Method::POST, hook.url);
+        if hook.controller_kind == ControllerKind::Os {
+            if let Some(token) = self.csrf_token.get_mut().as_ref() {
+                if let Ok(mut hv) = HeaderValue::from_str(token.expose_secret()) {
+                    hv.set_sensitive(true);
+                    request = request.header(HEADER_CSRF_TOKEN, hv);
+                }
+            }
+        }
+        let transport = hook.transport;
+        // Dropping cannot wait; the request is lost if the runtime shuts down
+        // first, in which case the session expires on the controller.
+        hook.runtime.spawn(async move {
+            if let Err(e) = transport.send(request).await {
+                log::warn!("Logout on drop failed: {e}");
+            }
+        });
+    }
+}
+
+/// The kind of UniFi controller the client talks to.
+///
+/// Detected automatically by [`UniFiClientBuilder::build`] unless set with
+/// [`UniFiClientBuilder::controller_kind`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ControllerKind {
+    /// A classic UniFi Network controller (self-hosted software, Cloud Key
+    /// gen1). The Network API is served at the root and logins go to
+    /// `/api/login`.
     Network,
+    /// A UniFi OS console or UniFi OS Server (UDM, UCG, Cloud Key gen2+). The
+    /// Network API is served under `/proxy/network` and logins go to
+    /// `/api/auth/login`.
     Os,
 }
 
@@ -413,12 +990,22 @@ enum ControllerKind {
 #[derive(Clone)]
 pub struct UniFiClient {
     controller_kind: ControllerKind,
+    controller_version: Option<String>,
     controller_url: Url,
     api_base_url: Url,
     username: String,
-    password: Option<SecretString>,
+    password: Option<Arc<dyn CredentialProvider>>,
+    api_key: Option<SecretString>,
+    mfa: Option<Arc<dyn MfaCodeProvider>>,
     site: String,
-    http_client: ReqwestClient,
+    transport: Arc<dyn Transport>,
+    retry_policy: RetryPolicy,
+    /// Rate limiter and in-flight cap shared by all clones.
+    throttle: Arc<Throttle>,
+    middlewares: Vec<Arc<dyn Middleware>>,
+    #[cfg(feature = "tls-pinning")]
+    certificate_pin: Option<Arc<CertificatePin>>,
+    session_key: Option<SecretString>,
     auth: Arc<AuthState>,
 }
 
@@ -432,13 +1019,24 @@ impl fmt::Debug for UniFiClient {
             .map(|g| g.is_some())
             .unwrap_or(false);
 
-        f.debug_struct("UniFiClient")
+        let mut debug = f.debug_struct("UniFiClient");
+        debug
             .field("controller_kind", &format!("{:?}", self.controller_kind))
+            .field("controller_version", &self.controller_version)
             .field("controller_url", &self.controller_url.as_str())
             .field("api_base_url", &self.api_base_url.as_str())
             .field("username", &self.username)
-            .field("password", &self.password)
+            .field("password_configured", &self.password.is_some())
+            .field("api_key", &self.api_key)
+            .field("mfa_configured", &self.mfa.is_some())
             .field("site", &self.site)
+            .field("transport", &self.transport)
+            .field("retry_policy", &self.retry_policy)
+            .field("throttle", &self.throttle)
+            .field("middlewares", &self.middlewares.len());
+        #[cfg(feature = "tls-pinning")]
+        debug.field("certificate_pin", &self.certificate_pin);
+        debug
             .field("auth_epoch", &epoch)
             .field("csrf_present", &csrf_present)
             .finish()
@@ -451,8 +1049,11 @@ impl fmt::Debug for UniFiClient {
 /// - `api_base_url`: `https://example.invalid:8443`
 /// - `username`: empty
 /// - `password`: `None`
+/// - `api_key`: `None`
 /// - `site`: `default`
-/// - `http_client`: reqwest client with `cookie_store(true)` and no redirects
+/// - `transport`: [`ReqwestTransport`] with a cookie store and no redirects
+/// - `retry_policy`: [`RetryPolicy::none`]
+/// - no rate limit or concurrency cap
 ///
 /// Note: This Default is inert and intended to be replaced via
 /// `UniFiClient::builder().build().await` and `initialize()`. Using the default
@@ -462,19 +1063,29 @@ impl Default for UniFiClient {
     fn default() -> Self {
         let timeout = Duration::from_secs(30);
         // Secure by default: do NOT accept invalid certs.
-        let http_client = reqwest_builder(timeout, false)
+        let cookie_jar = Arc::new(Jar::default());
+        let http_client = reqwest_builder(timeout, false, Arc::clone(&cookie_jar))
             .build()
             .expect("Failed to create default HTTP client");
 
         UniFiClient {
             controller_kind: ControllerKind::Network,
+            controller_version: None,
             controller_url: Url::parse("https://example.invalid:8443")
                 .expect("Invalid default URL"),
             api_base_url: Url::parse("https://example.invalid:8443").expect("Invalid default URL"),
             username: String::new(),
             password: None,
+            api_key: None,
+            mfa: None,
             site: "default".to_string(),
-            http_client,
+            transport: Arc::new(ReqwestTransport::with_cookie_jar(http_client, cookie_jar)),
+            retry_policy: RetryPolicy::none(),
+            throttle: Arc::new(Throttle::unlimited()),
+            middlewares: Vec::new(),
+            #[cfg(feature = "tls-pinning")]
+            certificate_pin: None,
+            session_key: None,
             auth: Arc::new(AuthState::new()),
         }
     }
@@ -505,6 +1116,71 @@ impl UniFiClient {
         &self.site
     }
 
+    /// Returns a handle for another site on the same controller.
+    ///
+    /// The handle is a clone of this client that shares its session, rate
+    /// limit and connection pool; only [`site`](Self::site) differs, and with
+    /// it the site used by [`guests`](Self::guests). No additional login takes
+    /// place, so one client can serve any number of sites.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use unifi_client::{UniFiClient, UniFiError};
+    /// # async fn example(client: &UniFiClient) -> Result<(), UniFiError> {
+    /// let branch = client.site_scope("branch-7");
+    /// let guests = branch.guests().list().send().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn site_scope(&self, site: impl Into<String>) -> UniFiClient {
+        let mut scoped = self.clone();
+        scoped.site = site.into();
+        scoped
+    }
+
+    /// Returns the endpoint of a v2 API resource of the current site.
+    ///
+    /// Newer Network application features (traffic rules, static DNS,
+    /// firewall policies, AP groups, ...) live under
+    /// `/v2/api/site/{site}/...`. Like every endpoint passed to the request
+    /// methods, the result is relative to the Network API, so UniFi OS
+    /// requests get the `/proxy/network` prefix. v2 endpoints return bare
+    /// JSON; read them with [`request_typed`](Self::request_typed).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use unifi_client::{UniFiClient, UniFiError};
+    /// # async fn example(client: &UniFiClient) -> Result<(), UniFiError> {
+    /// let endpoint = client.site_scope("lab").v2_endpoint("trafficrules");
+    /// assert_eq!(endpoint, "/v2/api/site/lab/trafficrules");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn v2_endpoint(&self, path: &str) -> String {
+        format!(
+            "/v2/api/site/{}/{}",
+            self.site,
+            path.trim_start_matches('/')
+        )
+    }
+
+    /// Gets the kind of controller, as detected at build time or configured
+    /// with [`UniFiClientBuilder::controller_kind`].
+    pub fn controller_kind(&self) -> ControllerKind {
+        self.controller_kind
+    }
+
+    /// Gets the UniFi Network application version reported by the controller
+    /// during detection (e.g., `8.6.9`).
+    ///
+    /// Returns `None` if the controller did not report it, the kind was set
+    /// explicitly, or the client was built from a restored session.
+    pub fn controller_version(&self) -> Option<&str> {
+        self.controller_version.as_deref()
+    }
+
     /// Creates a new `guests::GuestHandler` for the Guests API.
     ///
     /// # Returns
@@ -513,56 +1189,114 @@ impl UniFiClient {
     pub fn guests(&self) -> guests::GuestHandler {
         guests::GuestHandler::new(self.clone())
     }
+
+    /// Creates a new `integration::IntegrationHandler` for the official UniFi
+    /// Network Integration API (v1).
+    ///
+    /// # Returns
+    ///
+    /// - `integration::IntegrationHandler`: A typed handler scoped to this client.
+    pub fn integration(&self) -> integration::IntegrationHandler {
+        integration::IntegrationHandler::new(self.clone())
+    }
 }
 
 /// # UniFi Authentication Methods
 impl UniFiClient {
-    async fn login(&self) -> UniFiResult<()> {
+    /// Logs in with the configured username and password.
+    ///
+    /// [`build`](UniFiClientBuilder::build) already logs in, and expired
+    /// sessions are refreshed automatically, so this is only needed to start a
+    /// new session after [`logout`](Self::logout).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "unifi.login",
+            skip_all,
+            err,
+            fields(
+                controller_kind = ?self.controller_kind,
+                username = %self.username,
+                mfa = false,
+                status = tracing::field::Empty,
+            )
+        )
+    )]
+    pub async fn login(&self) -> UniFiResult<()> {
+        let relogin = self.auth.is_authenticated();
+        let result = self.authenticate().await;
+        telemetry::record_login(relogin, &result);
+        result
+    }
+
+    // Log in with username and password, answering an MFA challenge if needed.
+    async fn authenticate(&self) -> UniFiResult<()> {
         // Validate username and password fields once per session initiation.
         if self.username.trim().is_empty() {
             return Err(UniFiError::ConfigurationError(
                 "Username is required".into(),
             ));
         }
-        let password = self
+        let provider = self
             .password
             .as_ref()
-            .and_then(|p| {
-                let s = p.expose_secret();
-                (!s.trim().is_empty()).then(|| s.to_owned())
-            })
             .ok_or_else(|| UniFiError::ConfigurationError("Password is required".into()))?;
+        // Fetched per login so rotated passwords take effect without a rebuild.
+        let password = provider.password().await?;
+        if password.expose_secret().trim().is_empty() {
+            return Err(UniFiError::ConfigurationError(
+                "Password is required".into(),
+            ));
+        }
 
-        // Choose login path based on pre-detected controller kind
-        let login_path = match self.controller_kind {
-            ControllerKind::Os => "/api/auth/login",
-            ControllerKind::Network => "/api/login",
-        };
-
-        let login_url = self
-            .controller_url
-            .join(login_path)
-            .map_err(|e| UniFiError::UrlParseError(e))?;
+        let login_url = self.login_url()?;
 
-        let login_data = models::auth::LoginRequest {
+        let mut login_data = models::auth::LoginRequest {
             username: self.username.clone(),
             password,
+            token: None,
+            ubic_2fa_token: None,
         };
 
-        let response = self
-            .http_client
-            .post(login_url)
-            .json(&login_data)
-            .send()
+        let mut response = self
+            .transport
+            .send(TransportRequest::new(Method::POST, login_url.clone()).json(&login_data)?)
             .await?;
 
         if !response.status().is_success() {
-            return Err(UniFiError::AuthenticationError(format!(
-                "Authentication failed with status code: {}",
-                response.status()
-            )));
+            let status = response.status();
+            if !is_mfa_challenge(self.controller_kind, status, response.body()) {
+                return Err(UniFiError::AuthenticationError(format!(
+                    "Authentication failed with status code: {status}"
+                )));
+            }
+
+            // Answer the two-factor challenge with a fresh one-time code.
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("mfa", true);
+            let provider = self.mfa.as_ref().ok_or(UniFiError::MfaRequired)?;
+            let code = provider.code().await?;
+            match self.controller_kind {
+                ControllerKind::Os => login_data.token = Some(code),
+                ControllerKind::Network => login_data.ubic_2fa_token = Some(code),
+            }
+
+            response = self
+                .transport
+                .send(TransportRequest::new(Method::POST, login_url).json(&login_data)?)
+                .await?;
+
+            if !response.status().is_success() {
+                return Err(UniFiError::AuthenticationError(format!(
+                    "Two-factor authentication failed with status code: {}",
+                    response.status()
+                )));
+            }
         }
 
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("status", response.status().as_u16());
+
         // Ensure a cookie was set (required for both Network and UniFi OS)
         if response.headers().get("set-cookie").is_none() {
             return Err(UniFiError::AuthenticationError(
@@ -580,7 +1314,7 @@ impl UniFiClient {
         // For UniFi Network, a JSON body with { meta: { rc: "ok" }, ... } is returned.
         // For UniFi OS, the response is 200 with a body that does not follow that schema.
         if self.controller_kind == ControllerKind::Network {
-            let login_response: ApiResponse<Value> = response.json().await?;
+            let login_response: ApiResponse<Value> = response.json()?;
             if login_response.meta.rc != "ok" {
                 return Err(UniFiError::AuthenticationError(
                     login_response
@@ -599,15 +1333,245 @@ impl UniFiClient {
         Ok(())
     }
 
-    // Helper to get authentication headers
-    async fn csrf_header_value(&self) -> UniFiResult<Option<HeaderValue>> {
+    /// Ends the current session on the controller.
+    ///
+    /// Posts to `/api/auth/logout` (UniFi OS) or `/api/logout` (Network) and
+    /// resets the client to unauthenticated: the CSRF token is cleared and
+    /// requests fail with [`UniFiError::NotAuthenticated`] until
+    /// [`login`](Self::login) is called again. Clones share the session, so
+    /// they are logged out as well.
+    ///
+    /// Does nothing for API key clients and clients that are not logged in.
+    /// The local session is cleared even if the controller request fails.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "unifi.logout",
+            skip_all,
+            err,
+            fields(controller_kind = ?self.controller_kind, status = tracing::field::Empty)
+        )
+    )]
+    pub async fn logout(&self) -> UniFiResult<()> {
+        if self.api_key.is_some() {
+            return Ok(());
+        }
+
+        // Keep a concurrent re-login from racing the logout.
+        let _guard = self.auth.reauth_lock.lock().await;
         if !self.auth.is_authenticated() {
-            // Unauthenticated client asked for CSRF header.
-            return Err(UniFiError::NotAuthenticated);
+            return Ok(());
         }
 
-        if let Some(token) = self.auth.csrf_token.read().await.as_ref() {
-            let mut hv = HeaderValue::from_str(token.expose_secret())
+        let logout_url = self.logout_url()?;
+        let mut request = TransportRequest::new(Method::POST, logout_url.clone());
+        if self.controller_kind == ControllerKind::Os {
+            if let Some(csrf) = self.csrf_header_value().await? {
+                request = request.header(HEADER_CSRF_TOKEN, csrf);
+            }
+        }
+        let result = self.transport.send(request).await;
+        self.auth.clear_session().await;
+
+        let response = result?;
+        let status = response.status();
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("status", status.as_u16());
+
+        // A session that already expired is as good as a closed one.
+        if status.is_success() || status == StatusCode::UNAUTHORIZED {
+            return Ok(());
+        }
+        Err(UniFiError::ControllerError(Box::new(ControllerError::new(
+            status,
+            logout_url.path(),
+            &response.text(),
+        ))))
+    }
+
+    /// Exports the current authenticated session.
+    ///
+    /// The returned secret contains the session cookies, the CSRF token
+    /// (UniFi OS), the controller kind and the API base URL, encrypted with
+    /// the client's [`session_key`](UniFiClientBuilder::session_key). Pass it
+    /// to [`UniFiClientBuilder::restore_session`], together with the same
+    /// key, to reuse the session from another process without logging in
+    /// again.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UniFiError::ConfigurationError` for API key clients (which have
+    /// no session), for clients without a session key, and for clients whose
+    /// transport does not expose its cookies, such as clients built with a
+    /// custom `http_client`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use unifi_client::{UniFiClient, UniFiError};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), UniFiError> {
+    /// let client = UniFiClient::builder()
+    ///     .controller_url("https://controller.example:8443")
+    ///     .username("admin")
+    ///     .password("secret")
+    ///     .session_key(std::env::var("UNIFI_SESSION_KEY").unwrap())
+    ///     .build()
+    ///     .await?;
+    /// let session = client.export_session().await?;
+    ///
+    /// // Later, e.g. in the next run of a CLI job:
+    /// let client = UniFiClient::builder()
+    ///     .controller_url("https://controller.example:8443")
+    ///     .username("admin")
+    ///     .password("secret")
+    ///     .session_key(std::env::var("UNIFI_SESSION_KEY").unwrap())
+    ///     .restore_session(session)
+    ///     .build()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn export_session(&self) -> UniFiResult<SecretString> {
+        if self.api_key.is_some() {
+            return Err(UniFiError::ConfigurationError(
+                "API key authentication has no session to export".into(),
+            ));
+        }
+        if !self.auth.is_authenticated() {
+            return Err(UniFiError::NotAuthenticated);
+        }
+        let Some(ref session_key) = self.session_key else {
+            return Err(UniFiError::ConfigurationError(
+                "Exporting a session requires a session key".into(),
+            ));
+        };
+        // Cookies may be scoped to the login path (e.g. `/api`), so collect everything the jar
+        // would send to any of the URLs the client talks to. The login URL comes right after the
+        // root since its default cookie path (`/api`) is the one controllers use when they omit
+        // `Path`.
+        let mut cookies: Vec<SessionCookie> = Vec::new();
+        for url in [
+            self.controller_url.clone(),
+            self.login_url()?,
+            self.api_url("/api/")?,
+        ] {
+            let Some(header) = self.transport.cookies(&url)? else {
+                continue;
+            };
+            let header = header
+                .to_str()
+                .map_err(|e| UniFiError::ApiError(format!("Invalid cookie: {e}")))?;
+            for pair in header.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+                let name = pair.split('=').next().unwrap_or_default();
+                if !cookies
+                    .iter()
+                    .any(|c| c.pair.split('=').next() == Some(name))
+                {
+                    cookies.push(SessionCookie {
+                        url: url.to_string(),
+                        pair: pair.to_string(),
+                    });
+                }
+            }
+        }
+
+        let csrf_token = self
+            .auth
+            .csrf_token
+            .read()
+            .await
+            .as_ref()
+            .map(|t| t.expose_secret().to_owned());
+
+        SessionState::new(
+            self.controller_url.to_string(),
+            self.api_base_url.to_string(),
+            self.controller_kind,
+            cookies,
+            csrf_token,
+        )
+        .encode(session_key)
+    }
+
+    // Choose the login URL based on the pre-detected controller kind.
+    fn login_url(&self) -> UniFiResult<Url> {
+        let login_path = match self.controller_kind {
+            ControllerKind::Os => "/api/auth/login",
+            ControllerKind::Network => "/api/login",
+        };
+
+        self.controller_url
+            .join(login_path)
+            .map_err(UniFiError::UrlParseError)
+    }
+
+    // Choose the logout URL based on the pre-detected controller kind.
+    /// Logs the session out once the last clone of this client is dropped.
+    ///
+    /// Does nothing for API key clients, or outside a Tokio runtime, which
+    /// the logout would need to run on.
+    pub(crate) fn logout_on_drop(&self) {
+        if self.api_key.is_some() {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            log::warn!("LogoutGuard created outside a Tokio runtime; it will not log out on drop");
+            return;
+        };
+        let url = match self.logout_url() {
+            Ok(url) => url,
+            Err(e) => {
+                log::warn!("LogoutGuard cannot log out on drop: {e}");
+                return;
+            }
+        };
+        let hook = LogoutOnDrop {
+            runtime,
+            transport: Arc::clone(&self.transport),
+            url,
+            controller_kind: self.controller_kind,
+        };
+        *self
+            .auth
+            .logout_on_drop
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(hook);
+    }
+
+    fn logout_url(&self) -> UniFiResult<Url> {
+        let logout_path = match self.controller_kind {
+            ControllerKind::Os => "/api/auth/logout",
+            ControllerKind::Network => "/api/logout",
+        };
+
+        self.controller_url
+            .join(logout_path)
+            .map_err(UniFiError::UrlParseError)
+    }
+
+    // Helper to get the API key header, if the client uses API key authentication.
+    fn api_key_header_value(&self) -> UniFiResult<Option<HeaderValue>> {
+        self.api_key
+            .as_ref()
+            .map(|key| {
+                let mut hv = HeaderValue::from_str(key.expose_secret())
+                    .map_err(|e| UniFiError::ConfigurationError(format!("Invalid API key: {e}")))?;
+                hv.set_sensitive(true);
+                Ok(hv)
+            })
+            .transpose()
+    }
+
+    // Helper to get authentication headers
+    async fn csrf_header_value(&self) -> UniFiResult<Option<HeaderValue>> {
+        if !self.auth.is_authenticated() {
+            // Unauthenticated client asked for CSRF header.
+            return Err(UniFiError::NotAuthenticated);
+        }
+
+        if let Some(token) = self.auth.csrf_token.read().await.as_ref() {
+            let mut hv = HeaderValue::from_str(token.expose_secret())
                 .map_err(|e| UniFiError::ApiError(format!("Invalid CSRF token: {e}")))?;
             hv.set_sensitive(true);
             Ok(Some(hv))
@@ -615,6 +1579,20 @@ impl UniFiClient {
             Ok(None)
         }
     }
+
+    /// Returns the SHA-256 fingerprint of the pinned controller certificate as
+    /// colon-separated hex.
+    ///
+    /// With [`UniFiClientBuilder::trust_on_first_use`], this is the fingerprint
+    /// learned from the first connection. Returns `None` when pinning is not
+    /// configured.
+    #[cfg(feature = "tls-pinning")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tls-pinning")))]
+    pub fn pinned_certificate_sha256(&self) -> Option<String> {
+        self.certificate_pin
+            .as_ref()
+            .and_then(|pin| pin.fingerprint())
+    }
 }
 
 /// # HTTP Methods
@@ -623,6 +1601,10 @@ impl UniFiClient {
 /// and CSRF handling.
 impl UniFiClient {
     /// Sends a GET request and parses the standard UniFi API response.
+    ///
+    /// `params` are sent as a JSON body, as expected by the classic `stat`
+    /// endpoints. Use [`request_json_with_query`](Self::request_json_with_query)
+    /// for endpoints that take query parameters.
     pub async fn get<T, R>(&self, endpoint: &str, params: Option<T>) -> UniFiResult<R>
     where
         T: Serialize,
@@ -689,37 +1671,166 @@ impl UniFiClient {
     where
         T: Serialize,
     {
-        let response = self.request(method, endpoint, body).await?;
+        let url = self.api_url(endpoint)?;
+        self.execute_json(method, endpoint, url, body).await
+    }
 
-        if !response.status().is_success() {
-            return Err(UniFiError::ApiError(format!(
-                "API request failed with status code: {}",
-                response.status()
-            )));
+    /// Like [`request_json`](Self::request_json), with `query` encoded as the
+    /// URL query string.
+    ///
+    /// See [`request_with_query`](Self::request_with_query) for how the query
+    /// is encoded.
+    pub async fn request_json_with_query<Q, T>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        query: &Q,
+        body: Option<T>,
+    ) -> UniFiResult<serde_json::Value>
+    where
+        Q: Serialize + ?Sized,
+        T: Serialize,
+    {
+        let url = self.api_url_with_query(endpoint, query)?;
+        self.execute_json(method, endpoint, url, body).await
+    }
+
+    // Send to `url` and unwrap the standard `{ meta, data }` envelope.
+    async fn execute_json<T>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        url: Url,
+        body: Option<T>,
+    ) -> UniFiResult<serde_json::Value>
+    where
+        T: Serialize,
+    {
+        let response = self.execute(method, url, body).await?;
+        let status = response.status();
+
+        if !status.is_success() {
+            return Err(status_error(&response, endpoint));
         }
 
-        let api_response: ApiResponse<serde_json::Value> = response.json().await?;
+        let text = response.text();
+        let api_response: ApiResponse<serde_json::Value> = serde_json::from_str(&text)?;
 
         if api_response.meta.rc != "ok" {
-            return Err(UniFiError::ApiError(
-                api_response
-                    .meta
-                    .msg
-                    .unwrap_or_else(|| "Unknown API error".into()),
-            ));
+            let error = ControllerError::new(status, endpoint, &text)
+                .with_meta(Some(api_response.meta.rc), api_response.meta.msg);
+            return Err(UniFiError::ControllerError(Box::new(error)));
         }
 
         Ok(api_response.data.unwrap_or(serde_json::Value::Null))
     }
 
-    /// Makes an HTTP request to the UniFi API and returns the raw response.
+    /// Makes a request and deserializes the whole response body as `R`.
+    ///
+    /// Unlike [`request_json`](Self::request_json), the body is not expected to
+    /// be wrapped in the `{ meta, data }` envelope. This suits the v2 API
+    /// endpoints (see [`v2_endpoint`](Self::v2_endpoint)), which return bare
+    /// arrays and objects. An empty body is read as `null`, so `()` and
+    /// `Option<_>` work for endpoints that return nothing. Non-success
+    /// statuses are returned as [`UniFiError::ControllerError`], with the
+    /// result code when the body carries one.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use reqwest::Method;
+    /// # use serde::Deserialize;
+    /// # use unifi_client::{UniFiClient, UniFiError};
+    /// #[derive(Deserialize)]
+    /// struct TrafficRule {
+    ///     #[serde(rename = "_id")]
+    ///     id: String,
+    ///     description: String,
+    ///     enabled: bool,
+    /// }
+    ///
+    /// # async fn example(unifi_client: &UniFiClient) -> Result<(), UniFiError> {
+    /// let endpoint = unifi_client.v2_endpoint("trafficrules");
+    /// let rules: Vec<TrafficRule> = unifi_client
+    ///     .request_typed(Method::GET, &endpoint, None::<()>)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn request_typed<T, R>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Option<T>,
+    ) -> UniFiResult<R>
+    where
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        let url = self.api_url(endpoint)?;
+        self.execute_typed(method, endpoint, url, body).await
+    }
+
+    /// Like [`request_typed`](Self::request_typed), with `query` encoded as
+    /// the URL query string.
+    ///
+    /// See [`request_with_query`](Self::request_with_query) for how the query
+    /// is encoded.
+    pub async fn request_typed_with_query<Q, T, R>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        query: &Q,
+        body: Option<T>,
+    ) -> UniFiResult<R>
+    where
+        Q: Serialize + ?Sized,
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        let url = self.api_url_with_query(endpoint, query)?;
+        self.execute_typed(method, endpoint, url, body).await
+    }
+
+    // Send to `url` and deserialize the bare response body.
+    async fn execute_typed<T, R>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        url: Url,
+        body: Option<T>,
+    ) -> UniFiResult<R>
+    where
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        let response = self.execute(method, url, body).await?;
+        if !response.status().is_success() {
+            return Err(status_error(&response, endpoint));
+        }
+        if response.body().iter().all(u8::is_ascii_whitespace) {
+            return Ok(serde_json::from_value(Value::Null)?);
+        }
+        response.json()
+    }
+
+    /// Makes an HTTP request to the UniFi API and returns the response with
+    /// its body read.
     ///
     /// Behavior:
     /// - Builds the URL from `api_base_url` and `endpoint`
-    /// - Adds UniFi OS CSRF header if present
-    /// - Sends the request
+    /// - Adds the `X-API-KEY` header when using API key authentication, otherwise adds the UniFi OS
+    ///   CSRF header if present
+    /// - Sends the request through the configured [`Transport`]
     /// - Rotates CSRF if the server provides `x-updated-csrf-token`
-    /// - On 401 (both kinds) or 403 (OS), performs a single-flight re-login and retries once
+    /// - Retries transient failures (connection errors, 429, 502, 503, 504) according to the
+    ///   configured [`RetryPolicy`]; by default they are not retried
+    /// - Waits for the configured rate limit and concurrency cap before each attempt; the total
+    ///   wait is attached to the response as a [`ThrottleDelay`] extension
+    /// - On 401 (both kinds) or 403 (OS), performs a single-flight re-login and retries once. With
+    ///   API key authentication there is no session to refresh, so an `AuthenticationError` is
+    ///   returned instead.
+    /// - Runs the registered [`Middleware`] hooks around all of the above, once per call
     ///
     /// This is the low-level escape hatch; prefer typed methods when available.
     ///
@@ -752,33 +1863,302 @@ impl UniFiClient {
         method: Method,
         endpoint: &str,
         body: Option<T>,
-    ) -> UniFiResult<reqwest::Response>
+    ) -> UniFiResult<Response>
     where
         T: Serialize,
     {
-        debug_assert!(
-            self.auth.is_authenticated(),
-            "Client must be constructed via `build()` which performs an initial login"
-        );
+        let url = self.api_url(endpoint)?;
+        self.execute(method, url, body).await
+    }
+
+    /// Like [`request`](Self::request), with `query` encoded as the URL query
+    /// string.
+    ///
+    /// `query` can be any `Serialize` value that maps to key/value pairs, such
+    /// as a struct, a map or a slice of tuples. Keys and values are
+    /// percent-encoded and `None` fields are omitted. The endpoint itself must
+    /// still not contain a `?` or `#`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use reqwest::Method;
+    /// # use unifi_client::{UniFiClient, UniFiError};
+    /// # async fn example(unifi_client: &UniFiClient) -> Result<(), UniFiError> {
+    /// let events = unifi_client
+    ///     .request_with_query(
+    ///         Method::GET,
+    ///         "/v2/api/site/default/system-log/all",
+    ///         &[("limit", "50"), ("search", "guest & admin")],
+    ///         None::<()>,
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn request_with_query<Q, T>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        query: &Q,
+        body: Option<T>,
+    ) -> UniFiResult<Response>
+    where
+        Q: Serialize + ?Sized,
+        T: Serialize,
+    {
+        let url = self.api_url_with_query(endpoint, query)?;
+        self.execute(method, url, body).await
+    }
+
+    /// Like [`request`](Self::request), with a form, multipart or raw body
+    /// instead of JSON.
+    ///
+    /// Authentication, CSRF rotation, retries and middlewares apply as for
+    /// [`request`](Self::request). Middlewares only see JSON bodies through
+    /// [`MiddlewareRequest::body`]; other bodies are sent unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use reqwest::Method;
+    /// # use unifi_client::{RequestBody, UniFiClient, UniFiError};
+    /// # async fn example(unifi_client: &UniFiClient) -> Result<(), UniFiError> {
+    /// let firmware = std::fs::read("firmware.bin").unwrap();
+    /// let response = unifi_client
+    ///     .request_with_body(
+    ///         Method::POST,
+    ///         "/upload/firmware",
+    ///         RequestBody::bytes("application/octet-stream", firmware),
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn request_with_body(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: impl Into<RequestBody>,
+    ) -> UniFiResult<Response> {
+        let url = self.api_url(endpoint)?;
+        self.execute_body(method, url, Some(body.into()), None)
+            .await
+    }
+
+    /// Downloads `endpoint` (e.g. a backup under `/dl/`) and streams the
+    /// body to `writer`.
+    ///
+    /// Sends a GET request with the same authentication, CSRF handling and
+    /// re-authentication as [`request`](Self::request). Only a successful
+    /// response body is written; other statuses fail with
+    /// [`UniFiError::ControllerError`] and leave `writer` untouched. The
+    /// returned response carries the status and headers (e.g.
+    /// `Content-Disposition`) with an empty body. The client's timeout
+    /// covers the whole download.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use unifi_client::{UniFiClient, UniFiError};
+    /// # async fn example(unifi_client: &UniFiClient) -> Result<(), UniFiError> {
+    /// // Any `tokio::io::AsyncWrite` works, such as a `tokio::fs::File`.
+    /// let mut backup = Vec::new();
+    /// unifi_client
+    ///     .download("/dl/autobackup/autobackup_9.0.114.unf", &mut backup)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download<W>(&self, endpoint: &str, writer: &mut W) -> UniFiResult<Response>
+    where
+        W: AsyncWrite + Send + Unpin,
+    {
+        let url = self.api_url(endpoint)?;
+        let response = self
+            .execute_body(Method::GET, url, None, Some(writer))
+            .await?;
+        if !response.status().is_success() {
+            return Err(status_error(&response, endpoint));
+        }
+        Ok(response)
+    }
+
+    /// Sends a request to a fully built URL, applying middlewares, authentication, CSRF rotation
+    /// and the re-authentication retry described on [`UniFiClient::request`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "unifi.request",
+            skip_all,
+            err,
+            fields(
+                method = %method,
+                endpoint = url.path(),
+                site = %self.site,
+                controller_kind = ?self.controller_kind,
+                status = tracing::field::Empty,
+                attempt = tracing::field::Empty,
+                reauth = tracing::field::Empty,
+                auth_epoch = tracing::field::Empty,
+            )
+        )
+    )]
+    async fn execute_body(
+        &self,
+        method: Method,
+        url: Url,
+        body: Option<RequestBody>,
+        writer: Option<&mut (dyn AsyncWrite + Send + Unpin)>,
+    ) -> UniFiResult<Response> {
+        let start = Instant::now();
+        let (method_label, path) = (method.clone(), url.path().to_string());
+        let result = self
+            .execute_with_middlewares(method, url, body, writer)
+            .await;
+        telemetry::record_request(&method_label, &path, &result, start.elapsed());
+        result
+    }
+
+    /// Like `execute_body`, with `body` serialized as JSON.
+    pub(crate) async fn execute<T>(
+        &self,
+        method: Method,
+        url: Url,
+        body: Option<T>,
+    ) -> UniFiResult<Response>
+    where
+        T: Serialize,
+    {
+        let body = body.map(|b| RequestBody::json(&b)).transpose()?;
+        self.execute_body(method, url, body, None).await
+    }
+
+    // Run the middleware hooks around `send`.
+    async fn execute_with_middlewares(
+        &self,
+        method: Method,
+        url: Url,
+        body: Option<RequestBody>,
+        writer: Option<&mut (dyn AsyncWrite + Send + Unpin)>,
+    ) -> UniFiResult<Response> {
+        if self.middlewares.is_empty() {
+            let body = body.map(RequestBody::encode).transpose()?;
+            return self.send(method, url, body, HeaderMap::new(), writer).await;
+        }
+
+        let mut request = MiddlewareRequest::new(method, url, body)?;
+
+        // Run `on_request` hooks in order; a failure skips the remaining hooks and the send.
+        let mut entered = 0;
+        let mut failure = None;
+        for middleware in self.middlewares.iter() {
+            entered += 1;
+            if let Err(e) = middleware.on_request(&mut request).await {
+                failure = Some(e);
+                break;
+            }
+        }
+
+        let mut result = match failure {
+            Some(e) => Err(e),
+            None => match request.request_body() {
+                Ok(body) => {
+                    self.send(
+                        request.method().clone(),
+                        request.url().clone(),
+                        body,
+                        request.headers().clone(),
+                        writer,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            },
+        };
+
+        for middleware in self.middlewares[..entered].iter().rev() {
+            result = middleware.on_response(&request, result).await;
+        }
+        result
+    }
+
+    // Send with throttling, CSRF handling, transient retries and a single re-login.
+    async fn send(
+        &self,
+        method: Method,
+        url: Url,
+        body: Option<EncodedBody>,
+        headers: HeaderMap,
+        mut writer: Option<&mut (dyn AsyncWrite + Send + Unpin)>,
+    ) -> UniFiResult<Response> {
+        if !self.auth.is_authenticated() {
+            debug_assert!(
+                self.auth.logged_out.load(Ordering::Acquire),
+                "Client must be constructed via `build()` which performs an initial login"
+            );
+            return Err(UniFiError::NotAuthenticated);
+        }
 
         let mut retries = 0u8;
+        let mut attempt = 1u32;
+        let mut throttle_delay = Duration::ZERO;
 
         loop {
-            let url = self.api_url(endpoint)?;
-            let mut request = self.http_client.request(method.clone().into(), url);
+            #[cfg(feature = "tracing")]
+            tracing::Span::current()
+                .record("attempt", attempt)
+                .record("reauth", retries)
+                .record("auth_epoch", self.auth.epoch());
+
+            let (permit, waited) = self.throttle.acquire().await;
+            if !waited.is_zero() {
+                log::debug!("Request to {} throttled for {:?}", url.path(), waited);
+                throttle_delay += waited;
+            }
 
-            if let Some(ref data) = body {
-                request = request.json(data);
+            let mut request = TransportRequest::new(method.clone(), url.clone());
+
+            if let Some(body) = &body {
+                request = request.encoded_body(body.content_type.clone(), body.data.clone());
             }
 
-            // Add CSRF header if present (UniFi OS only)
-            if self.controller_kind == ControllerKind::Os {
+            if let Some(api_key) = self.api_key_header_value()? {
+                // API key authentication is sessionless and never uses CSRF tokens.
+                request = request.header(HEADER_API_KEY, api_key);
+            } else if self.controller_kind == ControllerKind::Os {
+                // Add CSRF header if present (UniFi OS only)
                 if let Some(csrf) = self.csrf_header_value().await? {
                     request = request.header(HEADER_CSRF_TOKEN, csrf);
                 }
             }
 
-            let response = request.send().await?;
+            // Headers added by middlewares replace any set above.
+            if !headers.is_empty() {
+                request = request.override_headers(&headers);
+            }
+
+            let response = match writer.as_deref_mut() {
+                Some(writer) => self.transport.download(request, writer).await,
+                None => self.transport.send(request).await,
+            };
+            drop(permit);
+
+            let mut response = match response {
+                Ok(response) => response,
+                Err(e) => {
+                    if self.retry_policy.has_attempts_left(attempt)
+                        && self
+                            .retry_policy
+                            .should_retry_error(&method, url.path(), &e)
+                    {
+                        tokio::time::sleep(self.retry_policy.delay(attempt, None)).await;
+                        attempt += 1;
+                        continue;
+                    }
+                    return Err(e);
+                }
+            };
 
             // Always rotate CSRF token first if present (UniFi OS can rotate on success or error).
             if let Some(updated_token) = response
@@ -789,16 +2169,41 @@ impl UniFiClient {
                 self.auth.rotate_csrf(updated_token).await;
             }
 
+            // Back off and retry transient failures allowed by the retry policy.
+            if self.retry_policy.has_attempts_left(attempt)
+                && self
+                    .retry_policy
+                    .should_retry_status(&method, url.path(), response.status())
+            {
+                tokio::time::sleep(self.retry_policy.delay(attempt, Some(&response))).await;
+                attempt += 1;
+                continue;
+            }
+
             // Retry if the request failed due to authentication or authorization.
             let should_retry = matches!(
                 (response.status(), self.controller_kind),
                 (StatusCode::UNAUTHORIZED, _) | (StatusCode::FORBIDDEN, ControllerKind::Os)
             );
             if !should_retry {
+                #[cfg(feature = "tracing")]
+                tracing::Span::current().record("status", response.status().as_u16());
+                response
+                    .extensions_mut()
+                    .insert(ThrottleDelay(throttle_delay));
                 return Ok(response);
             }
 
-            if retries >= 1 {
+            // An API key cannot be refreshed by logging in again.
+            if self.api_key.is_some() {
+                return Err(UniFiError::AuthenticationError(format!(
+                    "API key rejected with status code: {}",
+                    response.status()
+                )));
+            }
+
+            // Do not log back in behind a concurrent `logout()`.
+            if retries >= 1 || !self.auth.is_authenticated() {
                 return Err(UniFiError::NotAuthenticated);
             }
 
@@ -813,10 +2218,29 @@ impl UniFiClient {
     }
 }
 
+// Build the error for a non-success response to `endpoint`.
+fn status_error(response: &Response, endpoint: &str) -> UniFiError {
+    // Error responses usually still carry the `meta` envelope, or a `code` on the v2 API.
+    let text = response.text();
+    let error = ControllerError::new(response.status(), endpoint, &text);
+    let error = if let Ok(r) = serde_json::from_str::<ApiResponse<serde_json::Value>>(&text) {
+        error.with_meta(Some(r.meta.rc), r.meta.msg)
+    } else if let Ok(v2) = serde_json::from_str::<V2ErrorResponse>(&text) {
+        match (v2.code, v2.message) {
+            (Some(code), _) => error.with_meta(None, Some(code)),
+            (None, Some(message)) => error.with_message(message),
+            (None, None) => error,
+        }
+    } else {
+        error
+    };
+    UniFiError::ControllerError(Box::new(error))
+}
+
 /// # Utility Methods
 impl UniFiClient {
     // Build the URL for an API endpoint using path segments to avoid trailing slash issues.
-    fn api_url(&self, endpoint: &str) -> UniFiResult<Url> {
+    pub(crate) fn api_url(&self, endpoint: &str) -> UniFiResult<Url> {
         if endpoint.contains(['?', '#']) {
             return Err(UniFiError::InvalidEndpoint(format!(
                 "endpoint must not include query or fragment: {endpoint}"
@@ -836,6 +2260,20 @@ impl UniFiClient {
         }
         Ok(url)
     }
+
+    // Build the URL for an API endpoint with an encoded query string.
+    pub(crate) fn api_url_with_query<Q>(&self, endpoint: &str, query: &Q) -> UniFiResult<Url>
+    where
+        Q: Serialize + ?Sized,
+    {
+        let mut url = self.api_url(endpoint)?;
+        let query = serde_urlencoded::to_string(query)
+            .map_err(|e| UniFiError::InvalidEndpoint(format!("Failed to encode query: {e}")))?;
+        if !query.is_empty() {
+            url.set_query(Some(&query));
+        }
+        Ok(url)
+    }
 }
 
 #[cfg(test)]
@@ -845,12 +2283,21 @@ mod tests {
     fn make_client_with_api_base_url(api_base_url: &str, kind: ControllerKind) -> UniFiClient {
         UniFiClient {
             controller_kind: kind,
+            controller_version: None,
             controller_url: Url::parse("https://example.com/").unwrap(),
             api_base_url: Url::parse(api_base_url).unwrap(),
             username: "user".into(),
-            password: Some(SecretString::from("pass")),
+            password: Some(Arc::new(SecretString::from("pass"))),
+            api_key: None,
+            mfa: None,
             site: "default".into(),
-            http_client: reqwest::Client::new(),
+            transport: Arc::new(ReqwestTransport::new(reqwest::Client::new())),
+            retry_policy: RetryPolicy::none(),
+            throttle: Arc::new(Throttle::unlimited()),
+            middlewares: Vec::new(),
+            #[cfg(feature = "tls-pinning")]
+            certificate_pin: None,
+            session_key: None,
             auth: Arc::new(AuthState::new()),
         }
     }
@@ -922,4 +2369,83 @@ mod tests {
             other => panic!("Expected InvalidEndpoint, got {other:?}"),
         }
     }
+
+    #[tokio::test]
+    async fn logout_advances_epoch() {
+        let auth = AuthState::new();
+        let first = auth.establish_session(None::<SecretString>).await;
+        auth.clear_session().await;
+        assert!(!auth.is_authenticated());
+        assert!(auth.epoch() > first);
+
+        // A later login never reuses an epoch seen before the logout.
+        let second = auth.establish_session(None::<SecretString>).await;
+        assert!(auth.is_authenticated());
+        assert_eq!(second, first + 2);
+    }
+
+    #[test]
+    fn api_url_with_query_encodes_pairs() {
+        #[derive(Serialize)]
+        struct Query<'a> {
+            within: u32,
+            search: &'a str,
+            mac: Option<&'a str>,
+        }
+
+        let client =
+            make_client_with_api_base_url("https://example.com/proxy/network/", ControllerKind::Os);
+
+        let url = client
+            .api_url_with_query(
+                "/api/s/default/stat/event",
+                &Query {
+                    within: 24,
+                    search: "a&b=c d",
+                    mac: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://example.com/proxy/network/api/s/default/stat/event?within=24&search=a%26b%3Dc+d"
+        );
+
+        // An empty query adds no `?`.
+        let url = client
+            .api_url_with_query("/api/self", &[] as &[(&str, &str)])
+            .unwrap();
+        assert_eq!(url.as_str(), "https://example.com/proxy/network/api/self");
+
+        // Smuggled query strings in the endpoint are still rejected.
+        assert!(matches!(
+            client.api_url_with_query("/api/self?x=1", &[("y", "2")]),
+            Err(UniFiError::InvalidEndpoint(_))
+        ));
+        // Only key/value shapes can be encoded.
+        assert!(matches!(
+            client.api_url_with_query("/api/self", "plain"),
+            Err(UniFiError::InvalidEndpoint(_))
+        ));
+    }
+
+    #[test]
+    fn v2_endpoint_resolves_for_both_controller_kinds() {
+        let network =
+            make_client_with_api_base_url("https://example.com/", ControllerKind::Network)
+                .site_scope("lab");
+        let endpoint = network.v2_endpoint("/trafficrules");
+        assert_eq!(endpoint, "/v2/api/site/lab/trafficrules");
+        assert_eq!(
+            network.api_url(&endpoint).unwrap().as_str(),
+            "https://example.com/v2/api/site/lab/trafficrules"
+        );
+
+        let os =
+            make_client_with_api_base_url("https://example.com/proxy/network", ControllerKind::Os);
+        assert_eq!(
+            os.api_url(&os.v2_endpoint("static-dns")).unwrap().as_str(),
+            "https://example.com/proxy/network/v2/api/site/default/static-dns"
+        );
+    }
 }
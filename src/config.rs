@@ -0,0 +1,505 @@
+//! Client configuration loaded from files or environment variables.
+//!
+//! [`UniFiClientConfig`] describes everything a service usually needs to
+//! configure a client: where the controller is, where credentials come from,
+//! TLS, proxy, timeout and retry settings. It deserializes from TOML or JSON and can
+//! be read from prefixed environment variables, then turned into a
+//! [`UniFiClientBuilder`] for anything that cannot be expressed in a file,
+//! such as middlewares.
+
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+use url::Url;
+
+use crate::client::{ControllerKind, UniFiClientBuilder};
+use crate::credentials::{
+    CommandCredentialProvider, EnvCredentialProvider, FileCredentialProvider,
+};
+use crate::retry::RetryPolicy;
+use crate::{UniFiClient, UniFiError, UniFiResult};
+
+/// Settings for building a [`UniFiClient`](crate::UniFiClient).
+///
+/// Unknown keys are rejected. Errors name the offending key, e.g.
+/// ``Invalid `retry.max_attempts`: invalid type: string "three", expected u32``.
+///
+/// # Examples
+///
+/// ```toml
+/// controller_url = "https://controller.example:8443"
+/// site = "default"
+/// username = "admin"
+/// password_file = "/run/secrets/unifi-password"
+/// timeout_secs = 15
+///
+/// [tls]
+/// root_certificates = ["/etc/unifi/ca.pem"]
+///
+/// [retry]
+/// max_attempts = 5
+/// initial_backoff_ms = 500
+/// ```
+///
+/// ```no_run
+/// # use unifi_client::{UniFiClientConfig, UniFiError};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), UniFiError> {
+/// let client = UniFiClientConfig::from_file("unifi.toml")?
+///     .into_builder()?
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+#[cfg_attr(docsrs, doc(cfg(feature = "config")))]
+pub struct UniFiClientConfig {
+    /// The controller URL. Required.
+    pub controller_url: Option<String>,
+    /// Skips detection when set (`"os"` or `"network"`).
+    pub controller_kind: Option<ControllerKind>,
+    /// The site name; `default` when unset.
+    pub site: Option<String>,
+    /// The username for password logins.
+    pub username: Option<String>,
+    /// A literal password.
+    pub password: Option<SecretString>,
+    /// An environment variable holding the password, read on every login.
+    pub password_env: Option<String>,
+    /// A file holding the password, read on every login.
+    pub password_file: Option<PathBuf>,
+    /// A command (program followed by arguments) printing the password, run
+    /// on every login.
+    pub password_command: Option<Vec<String>>,
+    /// A literal UniFi OS API key.
+    pub api_key: Option<SecretString>,
+    /// An environment variable holding a UniFi OS API key.
+    pub api_key_env: Option<String>,
+    /// The request timeout in seconds.
+    pub timeout_secs: Option<u64>,
+    /// TLS settings.
+    #[serde(default)]
+    pub tls: TlsConfig,
+    /// Proxy settings.
+    pub proxy: Option<ProxyConfig>,
+    /// Addresses to use instead of DNS, by hostname.
+    #[serde(default)]
+    pub resolve: BTreeMap<String, IpAddr>,
+    /// Retry settings. Failed requests are not retried when unset.
+    pub retry: Option<RetryConfig>,
+}
+
+/// TLS settings of a [`UniFiClientConfig`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+#[cfg_attr(docsrs, doc(cfg(feature = "config")))]
+pub struct TlsConfig {
+    /// Disables certificate verification. Dangerous; lab use only.
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+    /// Additional root certificates, PEM or DER (`.der`/`.cer`) files.
+    #[serde(default)]
+    pub root_certificates: Vec<PathBuf>,
+    /// The SHA-256 fingerprint of the controller certificate to pin. Requires
+    /// the `tls-pinning` feature.
+    pub pin_sha256: Option<String>,
+    /// Pins the certificate seen on the first connection. Requires the
+    /// `tls-pinning` feature.
+    #[serde(default)]
+    pub trust_on_first_use: bool,
+}
+
+/// Proxy settings of a [`UniFiClientConfig`]; see
+/// [`UniFiClientBuilder::proxy`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+#[cfg_attr(docsrs, doc(cfg(feature = "config")))]
+pub struct ProxyConfig {
+    /// The proxy URL (`http`, `https`, `socks5` or `socks5h`).
+    pub url: String,
+    /// The proxy username.
+    pub username: Option<String>,
+    /// The proxy password. Required with `username`.
+    pub password: Option<SecretString>,
+}
+
+/// Retry settings of a [`UniFiClientConfig`], on top of
+/// [`RetryPolicy::default`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+#[cfg_attr(docsrs, doc(cfg(feature = "config")))]
+pub struct RetryConfig {
+    /// See [`RetryPolicy::max_attempts`].
+    pub max_attempts: Option<u32>,
+    /// See [`RetryPolicy::initial_backoff`], in milliseconds.
+    pub initial_backoff_ms: Option<u64>,
+    /// See [`RetryPolicy::max_backoff`], in milliseconds.
+    pub max_backoff_ms: Option<u64>,
+    /// See [`RetryPolicy::jitter`].
+    pub jitter: Option<bool>,
+    /// See [`RetryPolicy::respect_retry_after`].
+    pub respect_retry_after: Option<bool>,
+}
+
+fn invalid(key: &str, reason: impl std::fmt::Display) -> UniFiError {
+    UniFiError::ConfigurationError(format!("Invalid `{key}`: {reason}"))
+}
+
+impl UniFiClientConfig {
+    /// Reads a config file, as JSON if the file name ends in `.json` and as
+    /// TOML otherwise.
+    pub fn from_file(path: impl AsRef<Path>) -> UniFiResult<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            UniFiError::ConfigurationError(format!(
+                "Failed to read config file {}: {e}",
+                path.display()
+            ))
+        })?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::from_json_str(&contents),
+            _ => Self::from_toml_str(&contents),
+        }
+    }
+
+    /// Parses a TOML document.
+    pub fn from_toml_str(toml: &str) -> UniFiResult<Self> {
+        let deserializer = toml::Deserializer::new(toml);
+        serde_path_to_error::deserialize(deserializer)
+            .map_err(|e| invalid(&e.path().to_string(), e.inner().message().trim()))
+    }
+
+    /// Parses a JSON document.
+    pub fn from_json_str(json: &str) -> UniFiResult<Self> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        serde_path_to_error::deserialize(&mut deserializer)
+            .map_err(|e| invalid(&e.path().to_string(), e.inner()))
+    }
+
+    /// Reads settings from environment variables named after the keys with a
+    /// prefix, e.g. `UNIFI_CONTROLLER_URL`, `UNIFI_PASSWORD_FILE`,
+    /// `UNIFI_TLS_ACCEPT_INVALID_CERTS` or `UNIFI_RETRY_MAX_ATTEMPTS` for the
+    /// prefix `UNIFI`.
+    ///
+    /// `PASSWORD_COMMAND` is split on whitespace,
+    /// `TLS_ROOT_CERTIFICATES` like `PATH`, and `RESOLVE` is a comma-separated
+    /// list of `host=address` pairs. Unset variables leave the setting unset.
+    pub fn from_env(prefix: &str) -> UniFiResult<Self> {
+        let env = Env {
+            prefix: prefix.trim_end_matches('_'),
+        };
+        let retry = RetryConfig {
+            max_attempts: env.parse("RETRY_MAX_ATTEMPTS")?,
+            initial_backoff_ms: env.parse("RETRY_INITIAL_BACKOFF_MS")?,
+            max_backoff_ms: env.parse("RETRY_MAX_BACKOFF_MS")?,
+            jitter: env.parse("RETRY_JITTER")?,
+            respect_retry_after: env.parse("RETRY_RESPECT_RETRY_AFTER")?,
+        };
+        let any_retry = retry.max_attempts.is_some()
+            || retry.initial_backoff_ms.is_some()
+            || retry.max_backoff_ms.is_some()
+            || retry.jitter.is_some()
+            || retry.respect_retry_after.is_some();
+
+        Ok(Self {
+            controller_url: env.get("CONTROLLER_URL")?,
+            controller_kind: env
+                .get("CONTROLLER_KIND")?
+                .map(|kind| match kind.to_ascii_lowercase().as_str() {
+                    "os" => Ok(ControllerKind::Os),
+                    "network" => Ok(ControllerKind::Network),
+                    _ => Err(invalid(
+                        &env.name("CONTROLLER_KIND"),
+                        "expected `os` or `network`",
+                    )),
+                })
+                .transpose()?,
+            site: env.get("SITE")?,
+            username: env.get("USERNAME")?,
+            password: env.get("PASSWORD")?.map(SecretString::from),
+            password_env: env.get("PASSWORD_ENV")?,
+            password_file: env.get("PASSWORD_FILE")?.map(PathBuf::from),
+            password_command: env
+                .get("PASSWORD_COMMAND")?
+                .map(|command| command.split_whitespace().map(str::to_owned).collect()),
+            api_key: env.get("API_KEY")?.map(SecretString::from),
+            api_key_env: env.get("API_KEY_ENV")?,
+            timeout_secs: env.parse("TIMEOUT_SECS")?,
+            tls: TlsConfig {
+                accept_invalid_certs: env.parse("TLS_ACCEPT_INVALID_CERTS")?.unwrap_or_default(),
+                root_certificates: env
+                    .get("TLS_ROOT_CERTIFICATES")?
+                    .map(|paths| std::env::split_paths(&paths).collect())
+                    .unwrap_or_default(),
+                pin_sha256: env.get("TLS_PIN_SHA256")?,
+                trust_on_first_use: env.parse("TLS_TRUST_ON_FIRST_USE")?.unwrap_or_default(),
+            },
+            proxy: env
+                .get("PROXY_URL")?
+                .map(|url| -> UniFiResult<_> {
+                    Ok(ProxyConfig {
+                        url,
+                        username: env.get("PROXY_USERNAME")?,
+                        password: env.get("PROXY_PASSWORD")?.map(SecretString::from),
+                    })
+                })
+                .transpose()?,
+            resolve: env
+                .get("RESOLVE")?
+                .map(|pairs| {
+                    pairs
+                        .split(',')
+                        .filter(|pair| !pair.trim().is_empty())
+                        .map(|pair| {
+                            let (host, addr) = pair.split_once('=').ok_or_else(|| {
+                                invalid(&env.name("RESOLVE"), "expected `host=address` pairs")
+                            })?;
+                            let addr = addr
+                                .trim()
+                                .parse()
+                                .map_err(|e| invalid(&env.name("RESOLVE"), e))?;
+                            Ok((host.trim().to_owned(), addr))
+                        })
+                        .collect::<UniFiResult<_>>()
+                })
+                .transpose()?
+                .unwrap_or_default(),
+            retry: any_retry.then_some(retry),
+        })
+    }
+
+    /// Validates the settings and applies them to a new builder.
+    ///
+    /// Root certificate files are read here; password files and commands are
+    /// read on every login.
+    pub fn into_builder(self) -> UniFiResult<UniFiClientBuilder> {
+        let mut builder = UniFiClient::builder();
+
+        let controller_url = self
+            .controller_url
+            .ok_or_else(|| UniFiError::ConfigurationError("Missing `controller_url`".into()))?;
+        Url::parse(&controller_url).map_err(|e| invalid("controller_url", e))?;
+        builder = builder.controller_url(controller_url);
+
+        if let Some(kind) = self.controller_kind {
+            builder = builder.controller_kind(kind);
+        }
+        if let Some(site) = self.site {
+            if site.trim().is_empty() {
+                return Err(invalid("site", "must not be empty"));
+            }
+            builder = builder.site(site);
+        }
+
+        let api_key = match (self.api_key, self.api_key_env) {
+            (Some(_), Some(_)) => {
+                return Err(UniFiError::ConfigurationError(
+                    "Only one of `api_key` and `api_key_env` may be set".into(),
+                ))
+            }
+            (Some(key), None) => Some(key),
+            (None, Some(var)) => {
+                Some(SecretString::from(std::env::var(&var).map_err(|e| {
+                    invalid("api_key_env", format!("{var}: {e}"))
+                })?))
+            }
+            (None, None) => None,
+        };
+
+        let password_sources = [
+            self.password.is_some(),
+            self.password_env.is_some(),
+            self.password_file.is_some(),
+            self.password_command.is_some(),
+        ];
+        if password_sources.iter().filter(|set| **set).count() > 1 {
+            return Err(UniFiError::ConfigurationError(
+                "Only one of `password`, `password_env`, `password_file` and `password_command` \
+                 may be set"
+                    .into(),
+            ));
+        }
+
+        match api_key {
+            Some(api_key) => {
+                if self.username.is_some() || password_sources.contains(&true) {
+                    return Err(invalid(
+                        "api_key",
+                        "cannot be combined with `username` or a password",
+                    ));
+                }
+                builder = builder.api_key(api_key.expose_secret());
+            }
+            None => {
+                let username = self
+                    .username
+                    .ok_or_else(|| UniFiError::ConfigurationError("Missing `username`".into()))?;
+                builder = builder.username(username);
+                builder = if let Some(password) = self.password {
+                    builder.password(password.expose_secret())
+                } else if let Some(var) = self.password_env {
+                    builder.password_provider(EnvCredentialProvider::new(var))
+                } else if let Some(path) = self.password_file {
+                    builder.password_provider(FileCredentialProvider::new(path))
+                } else if let Some(command) = self.password_command {
+                    let (program, args) = command
+                        .split_first()
+                        .ok_or_else(|| invalid("password_command", "must not be empty"))?;
+                    builder.password_provider(
+                        CommandCredentialProvider::new(program.clone()).args(args.iter().cloned()),
+                    )
+                } else {
+                    return Err(UniFiError::ConfigurationError(
+                        "Missing `password`, `password_env`, `password_file` or `password_command`"
+                            .into(),
+                    ));
+                };
+            }
+        }
+
+        if let Some(timeout) = self.timeout_secs {
+            if timeout == 0 {
+                return Err(invalid("timeout_secs", "must be greater than zero"));
+            }
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+
+        let tls = self.tls;
+        builder = builder.accept_invalid_certs(tls.accept_invalid_certs);
+        for path in tls.root_certificates {
+            let contents = std::fs::read(&path).map_err(|e| {
+                invalid("tls.root_certificates", format!("{}: {e}", path.display()))
+            })?;
+            builder = match path.extension().and_then(|e| e.to_str()) {
+                Some("der" | "cer") => builder.add_root_certificate_der(contents),
+                _ => builder.add_root_certificate_pem(contents),
+            };
+        }
+        #[cfg(feature = "tls-pinning")]
+        {
+            builder = builder.trust_on_first_use(tls.trust_on_first_use);
+            if let Some(pin) = tls.pin_sha256 {
+                builder = builder.pin_certificate_sha256(pin);
+            }
+        }
+        #[cfg(not(feature = "tls-pinning"))]
+        if tls.pin_sha256.is_some() || tls.trust_on_first_use {
+            return Err(invalid(
+                "tls.pin_sha256",
+                "certificate pinning requires the `tls-pinning` feature",
+            ));
+        }
+
+        if let Some(proxy) = self.proxy {
+            Url::parse(&proxy.url).map_err(|e| invalid("proxy.url", e))?;
+            builder = builder.proxy(proxy.url);
+            match (proxy.username, proxy.password) {
+                (Some(username), Some(password)) => {
+                    builder = builder.proxy_auth(username, password.expose_secret());
+                }
+                (None, None) => {}
+                (_, None) => return Err(invalid("proxy.password", "required with a username")),
+                (None, _) => return Err(invalid("proxy.username", "required with a password")),
+            }
+        }
+        for (host, addr) in self.resolve {
+            builder = builder.resolve(host, addr);
+        }
+
+        if let Some(retry) = self.retry {
+            let mut policy = RetryPolicy::default();
+            if let Some(max_attempts) = retry.max_attempts {
+                if max_attempts == 0 {
+                    return Err(invalid("retry.max_attempts", "must be at least 1"));
+                }
+                policy = policy.max_attempts(max_attempts);
+            }
+            if let Some(ms) = retry.initial_backoff_ms {
+                policy = policy.initial_backoff(Duration::from_millis(ms));
+            }
+            if let Some(ms) = retry.max_backoff_ms {
+                policy = policy.max_backoff(Duration::from_millis(ms));
+            }
+            if let Some(jitter) = retry.jitter {
+                policy = policy.jitter(jitter);
+            }
+            if let Some(respect) = retry.respect_retry_after {
+                policy = policy.respect_retry_after(respect);
+            }
+            builder = builder.retry_policy(policy);
+        }
+
+        Ok(builder)
+    }
+}
+
+/// Reads prefixed environment variables, naming them in errors.
+struct Env<'a> {
+    prefix: &'a str,
+}
+
+impl Env<'_> {
+    fn name(&self, key: &str) -> String {
+        if self.prefix.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{}_{key}", self.prefix)
+        }
+    }
+
+    fn get(&self, key: &str) -> UniFiResult<Option<String>> {
+        let name = self.name(key);
+        match std::env::var(&name) {
+            Ok(value) => Ok(Some(value)),
+            Err(std::env::VarError::NotPresent) => Ok(None),
+            Err(e) => Err(invalid(&name, e)),
+        }
+    }
+
+    fn parse<T>(&self, key: &str) -> UniFiResult<Option<T>>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.get(key)?
+            .map(|value| {
+                value
+                    .trim()
+                    .parse()
+                    .map_err(|e| invalid(&self.name(key), e))
+            })
+            .transpose()
+    }
+}
+
+impl UniFiClientBuilder {
+    /// Creates a builder from prefixed environment variables; see
+    /// [`UniFiClientConfig::from_env`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use unifi_client::{UniFiClientBuilder, UniFiError};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), UniFiError> {
+    /// // UNIFI_CONTROLLER_URL, UNIFI_USERNAME, UNIFI_PASSWORD_FILE, ...
+    /// let client = UniFiClientBuilder::from_env("UNIFI")?.build().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "config")))]
+    pub fn from_env(prefix: &str) -> UniFiResult<Self> {
+        UniFiClientConfig::from_env(prefix)?.into_builder()
+    }
+}
@@ -0,0 +1,207 @@
+//! Password sources for username/password authentication.
+//!
+//! The client asks its [`CredentialProvider`] for the password on every
+//! (re-)authentication instead of keeping a copy for its whole lifetime, so
+//! passwords rotated by a secret manager are picked up on the next login. The
+//! password is only held for the duration of the login request.
+
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use secrecy::SecretString;
+
+use crate::{UniFiError, UniFiResult};
+
+/// Supplies the password used to log in.
+///
+/// Implement this trait to fetch passwords asynchronously (e.g., from a
+/// secret manager API). Plain closures returning `UniFiResult<SecretString>`
+/// and [`SecretString`] itself implement it as well.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use unifi_client::{FileCredentialProvider, UniFiClient, UniFiError};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), UniFiError> {
+/// let client = UniFiClient::builder()
+///     .controller_url("https://controller.example")
+///     .username("admin")
+///     .password_provider(FileCredentialProvider::new("/run/secrets/unifi-password"))
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[async_trait]
+pub trait CredentialProvider: Send + Sync {
+    /// Returns the current password.
+    async fn password(&self) -> UniFiResult<SecretString>;
+}
+
+#[async_trait]
+impl<F> CredentialProvider for F
+where
+    F: Fn() -> UniFiResult<SecretString> + Send + Sync,
+{
+    async fn password(&self) -> UniFiResult<SecretString> {
+        self()
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for SecretString {
+    async fn password(&self) -> UniFiResult<SecretString> {
+        Ok(self.clone())
+    }
+}
+
+/// Reads the password from an environment variable on every login.
+#[derive(Debug, Clone)]
+pub struct EnvCredentialProvider {
+    var_name: String,
+}
+
+impl EnvCredentialProvider {
+    /// Reads the password from the environment variable `var_name`.
+    pub fn new(var_name: impl Into<String>) -> Self {
+        Self {
+            var_name: var_name.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for EnvCredentialProvider {
+    async fn password(&self) -> UniFiResult<SecretString> {
+        std::env::var(&self.var_name)
+            .map(SecretString::from)
+            .map_err(|e| {
+                UniFiError::ConfigurationError(format!(
+                    "Failed to read environment variable '{}': {e}",
+                    self.var_name
+                ))
+            })
+    }
+}
+
+/// Reads the password from a file on every login, such as a secret mounted
+/// by a secret manager.
+///
+/// Trailing line breaks are removed.
+#[derive(Debug, Clone)]
+pub struct FileCredentialProvider {
+    path: PathBuf,
+}
+
+impl FileCredentialProvider {
+    /// Reads the password from the file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for FileCredentialProvider {
+    async fn password(&self) -> UniFiResult<SecretString> {
+        let contents = tokio::fs::read_to_string(&self.path).await.map_err(|e| {
+            UniFiError::ConfigurationError(format!(
+                "Failed to read password file {}: {e}",
+                self.path.display()
+            ))
+        })?;
+        Ok(SecretString::from(trim_line_breaks(contents)))
+    }
+}
+
+/// Runs a command on every login and uses its standard output as the
+/// password, e.g. `pass show unifi` or a secret manager CLI.
+///
+/// Trailing line breaks are removed. A non-zero exit status fails the login,
+/// as does a command still running after the [`timeout`](Self::timeout),
+/// which is then killed.
+#[derive(Debug, Clone)]
+pub struct CommandCredentialProvider {
+    program: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl CommandCredentialProvider {
+    /// Runs `program` without arguments; add them with [`arg`](Self::arg).
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Appends an argument.
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Appends several arguments.
+    pub fn args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets how long the command may run before it is killed. Defaults to
+    /// 30 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for CommandCredentialProvider {
+    async fn password(&self) -> UniFiResult<SecretString> {
+        let mut command = tokio::process::Command::new(&self.program);
+        command
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .kill_on_drop(true);
+        // Dropping the `output` future on timeout kills the child.
+        let output = tokio::time::timeout(self.timeout, command.output())
+            .await
+            .map_err(|_| {
+                UniFiError::ConfigurationError(format!(
+                    "Password command '{}' timed out after {:?}",
+                    self.program, self.timeout
+                ))
+            })?
+            .map_err(|e| {
+                UniFiError::ConfigurationError(format!(
+                    "Failed to run password command '{}': {e}",
+                    self.program
+                ))
+            })?;
+        if !output.status.success() {
+            return Err(UniFiError::ConfigurationError(format!(
+                "Password command '{}' failed with {}: {}",
+                self.program,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        let stdout = String::from_utf8(output.stdout).map_err(|_| {
+            UniFiError::ConfigurationError(format!(
+                "Password command '{}' printed invalid UTF-8",
+                self.program
+            ))
+        })?;
+        Ok(SecretString::from(trim_line_breaks(stdout)))
+    }
+}
+
+fn trim_line_breaks(mut s: String) -> String {
+    let len = s.trim_end_matches(['\r', '\n']).len();
+    s.truncate(len);
+    s
+}
@@ -1,6 +1,15 @@
+use std::fmt;
+
+use http::StatusCode;
 use thiserror::Error;
 pub use url::ParseError as UrlParseError;
 
+use crate::retry::is_transient_status;
+use crate::transport::{TransportError, TransportErrorKind};
+
+/// Maximum number of bytes of the response body kept in a [`ControllerError`].
+const BODY_SNIPPET_LEN: usize = 512;
+
 /// Error types for the UniFi API client.
 #[derive(Error, Debug)]
 pub enum UniFiError {
@@ -12,10 +21,23 @@ pub enum UniFiError {
     #[error("API error: {0}")]
     ApiError(String),
 
+    /// The controller rejected a request; see [`ControllerError`] for the
+    /// status, result code and response details.
+    #[error("API error: {0}")]
+    ControllerError(Box<ControllerError>),
+
     /// HTTP request failed.
     #[error("HTTP error: {0}")]
     HttpError(#[from] reqwest::Error),
 
+    /// A custom [`Transport`](crate::Transport) failed to send a request.
+    #[error("Transport error: {0}")]
+    TransportError(#[from] TransportError),
+
+    /// Writing a download to its destination failed.
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
     /// Error parsing URL.
     #[error("URL parse error: {0}")]
     UrlParseError(#[from] UrlParseError),
@@ -28,6 +50,11 @@ pub enum UniFiError {
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
 
+    /// The controller requires a two-factor code but no MFA code provider is
+    /// configured.
+    #[error("Two-factor authentication required")]
+    MfaRequired,
+
     /// Client is not authenticated.
     #[error("Not authenticated")]
     NotAuthenticated,
@@ -36,10 +63,237 @@ pub enum UniFiError {
     #[error("Site not found: {0}")]
     SiteNotFound(String),
 
+    /// No client is registered under the given name.
+    #[error("Client not registered: {0}")]
+    ClientNotRegistered(String),
+
     /// Invalid client configuration.
     #[error("Invalid configuration: {0}")]
     ConfigurationError(String),
 }
 
+impl UniFiError {
+    /// Returns the HTTP status the controller answered with, if the error
+    /// came from a controller response.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            UniFiError::ControllerError(e) => Some(e.status),
+            UniFiError::HttpError(e) => e.status(),
+            _ => None,
+        }
+    }
+
+    /// Returns the typed UniFi result code, if the controller reported one.
+    pub fn code(&self) -> Option<&UniFiErrorCode> {
+        match self {
+            UniFiError::ControllerError(e) => e.code.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the same request may succeed when sent again later:
+    /// connection failures, timeouts and the statuses `429`, `502`, `503`
+    /// and `504`.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            UniFiError::ControllerError(e) => e.is_retryable(),
+            _ => self.is_connect() || self.is_timeout(),
+        }
+    }
+
+    /// Returns true if no connection to the controller could be made, so
+    /// the request never reached it.
+    pub(crate) fn is_connect(&self) -> bool {
+        match self {
+            UniFiError::HttpError(e) => e.is_connect(),
+            UniFiError::TransportError(e) => e.kind() == TransportErrorKind::Connect,
+            _ => false,
+        }
+    }
+
+    /// Returns true if the request timed out, possibly after reaching the
+    /// controller.
+    pub(crate) fn is_timeout(&self) -> bool {
+        match self {
+            UniFiError::HttpError(e) => e.is_timeout(),
+            UniFiError::TransportError(e) => e.kind() == TransportErrorKind::Timeout,
+            _ => false,
+        }
+    }
+
+    /// Returns true if the requested site or object does not exist.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            UniFiError::ControllerError(e) => e.is_not_found(),
+            UniFiError::SiteNotFound(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns true if the authenticated account may not perform the request.
+    pub fn is_permission_denied(&self) -> bool {
+        match self {
+            UniFiError::ControllerError(e) => e.is_permission_denied(),
+            _ => false,
+        }
+    }
+}
+
+/// Details of a request the controller rejected, either with a non-success
+/// HTTP status or with `meta.rc` other than `"ok"`.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ControllerError {
+    /// HTTP status of the response.
+    pub status: StatusCode,
+
+    /// Path of the request, e.g. `/api/s/default/cmd/stamgr`.
+    pub endpoint: String,
+
+    /// Raw `meta.rc` result code, usually `"error"`.
+    pub rc: Option<String>,
+
+    /// Raw `meta.msg` or v2 API `code` (e.g. `api.err.InvalidPayload`), or
+    /// the Integration API error message.
+    pub msg: Option<String>,
+
+    /// Typed form of `msg` for codes of the legacy and v2 APIs.
+    pub code: Option<UniFiErrorCode>,
+
+    /// The start of the response body, for diagnostics.
+    pub body: String,
+}
+
+impl ControllerError {
+    pub(crate) fn new(status: StatusCode, endpoint: impl Into<String>, body: &str) -> Self {
+        let mut end = body.len().min(BODY_SNIPPET_LEN);
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        Self {
+            status,
+            endpoint: endpoint.into(),
+            rc: None,
+            msg: None,
+            code: None,
+            body: body[..end].to_string(),
+        }
+    }
+
+    /// Sets `rc` and `msg` from the `meta` object of a legacy API response.
+    pub(crate) fn with_meta(mut self, rc: Option<String>, msg: Option<String>) -> Self {
+        self.code = msg.as_deref().map(UniFiErrorCode::from);
+        self.rc = rc;
+        self.msg = msg;
+        self
+    }
+
+    /// Sets the message of an Integration API error response.
+    pub(crate) fn with_message(mut self, msg: String) -> Self {
+        self.msg = Some(msg);
+        self
+    }
+
+    /// Returns true for `429`, `502`, `503` and `504` responses.
+    pub fn is_retryable(&self) -> bool {
+        is_transient_status(self.status)
+    }
+
+    /// Returns true for `404` responses and unknown site, device, client or
+    /// object IDs.
+    pub fn is_not_found(&self) -> bool {
+        self.status == StatusCode::NOT_FOUND
+            || matches!(
+                self.code,
+                Some(
+                    UniFiErrorCode::NoSiteContext
+                        | UniFiErrorCode::UnknownDevice
+                        | UniFiErrorCode::UnknownUser
+                        | UniFiErrorCode::IdInvalid
+                )
+            )
+    }
+
+    /// Returns true for `403` responses and `api.err.NoPermission`.
+    pub fn is_permission_denied(&self) -> bool {
+        self.status == StatusCode::FORBIDDEN || self.code == Some(UniFiErrorCode::NoPermission)
+    }
+}
+
+impl fmt::Display for ControllerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} returned {}", self.endpoint, self.status)?;
+        if let Some(msg) = &self.msg {
+            write!(f, ": {msg}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Known `meta.msg` result codes of the legacy UniFi Network API.
+///
+/// Messages this crate does not know yet are kept as [`Other`](Self::Other).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum UniFiErrorCode {
+    /// `api.err.LoginRequired`: the session expired or was never established.
+    LoginRequired,
+    /// `api.err.NoPermission`: the account lacks the required role.
+    NoPermission,
+    /// `api.err.NoSiteContext`: the site in the path does not exist.
+    NoSiteContext,
+    /// `api.err.InvalidPayload`: the request body was rejected.
+    InvalidPayload,
+    /// `api.err.IdInvalid`: the object ID in the path is malformed or unknown.
+    IdInvalid,
+    /// `api.err.UnknownDevice`: no adopted device has the given MAC address.
+    UnknownDevice,
+    /// `api.err.UnknownUser`: no client has the given MAC address.
+    UnknownUser,
+    /// `api.err.Ubic2faTokenRequired`: a two-factor code is required to log in.
+    TwoFactorRequired,
+    /// Any other message, verbatim.
+    Other(String),
+}
+
+impl UniFiErrorCode {
+    /// Returns the message as reported by the controller.
+    pub fn as_str(&self) -> &str {
+        match self {
+            UniFiErrorCode::LoginRequired => "api.err.LoginRequired",
+            UniFiErrorCode::NoPermission => "api.err.NoPermission",
+            UniFiErrorCode::NoSiteContext => "api.err.NoSiteContext",
+            UniFiErrorCode::InvalidPayload => "api.err.InvalidPayload",
+            UniFiErrorCode::IdInvalid => "api.err.IdInvalid",
+            UniFiErrorCode::UnknownDevice => "api.err.UnknownDevice",
+            UniFiErrorCode::UnknownUser => "api.err.UnknownUser",
+            UniFiErrorCode::TwoFactorRequired => "api.err.Ubic2faTokenRequired",
+            UniFiErrorCode::Other(msg) => msg,
+        }
+    }
+}
+
+impl From<&str> for UniFiErrorCode {
+    fn from(msg: &str) -> Self {
+        match msg {
+            "api.err.LoginRequired" => UniFiErrorCode::LoginRequired,
+            "api.err.NoPermission" => UniFiErrorCode::NoPermission,
+            "api.err.NoSiteContext" => UniFiErrorCode::NoSiteContext,
+            "api.err.InvalidPayload" => UniFiErrorCode::InvalidPayload,
+            "api.err.IdInvalid" => UniFiErrorCode::IdInvalid,
+            "api.err.UnknownDevice" => UniFiErrorCode::UnknownDevice,
+            "api.err.UnknownUser" => UniFiErrorCode::UnknownUser,
+            "api.err.Ubic2faTokenRequired" => UniFiErrorCode::TwoFactorRequired,
+            other => UniFiErrorCode::Other(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for UniFiErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Result type for UniFi API operations.
 pub type UniFiResult<T> = Result<T, UniFiError>;
@@ -22,6 +22,8 @@
 //! available.
 //!
 //! - [`guest`] - Guest access management.
+//! - [`integration`] - The official UniFi Network Integration API (v1) for UniFi OS consoles:
+//!   sites, devices, clients and hotspot vouchers with `offset`/`limit` paging.
 //!
 //! ### Examples
 //!
@@ -67,13 +69,57 @@
 //! allowing you to easily specify parameters.
 
 mod api;
+#[cfg(feature = "blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+pub mod blocking;
+mod body;
+#[cfg(feature = "cassettes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cassettes")))]
+pub mod cassette;
 mod client;
+#[cfg(feature = "config")]
+mod config;
+mod credentials;
 mod error;
+mod mfa;
+mod middleware;
+#[cfg(feature = "default-client")]
+#[cfg_attr(docsrs, doc(cfg(feature = "default-client")))]
+pub mod registry;
+mod retry;
+mod session;
+mod telemetry;
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
+mod throttle;
+mod tls;
+mod transport;
 
 pub mod models;
 
-pub use self::api::guests;
+pub use secrecy;
+
+pub use self::api::{guests, integration};
+pub use self::body::{Multipart, RequestBody};
+pub use self::client::{ControllerKind, UniFiClient, UniFiClientBuilder};
+#[cfg(feature = "config")]
+pub use self::config::{ProxyConfig, RetryConfig, TlsConfig, UniFiClientConfig};
+pub use self::credentials::{
+    CommandCredentialProvider, CredentialProvider, EnvCredentialProvider, FileCredentialProvider,
+};
+pub use self::error::{ControllerError, UniFiError, UniFiErrorCode, UniFiResult};
+pub use self::mfa::MfaCodeProvider;
+#[cfg(feature = "totp")]
+pub use self::mfa::TotpCodeProvider;
+pub use self::middleware::{
+    LoggingMiddleware, Middleware, MiddlewareRequest, RequestTiming, TimingMiddleware,
+};
 #[cfg(feature = "default-client")]
-pub use self::client::{initialize, instance};
-pub use self::client::{UniFiClient, UniFiClientBuilder};
-pub use self::error::{UniFiError, UniFiResult};
+pub use self::registry::{initialize, instance};
+pub use self::retry::RetryPolicy;
+pub use self::session::LogoutGuard;
+pub use self::throttle::{RateLimit, ThrottleDelay};
+pub use self::transport::{
+    ReqwestTransport, Response, Transport, TransportError, TransportErrorKind, TransportRequest,
+};
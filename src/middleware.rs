@@ -0,0 +1,252 @@
+//! Hooks around requests sent by [`UniFiClient`](crate::UniFiClient).
+//!
+//! Middlewares run around every call to
+//! [`UniFiClient::request`](crate::UniFiClient::request) and the typed APIs
+//! built on it. They wrap the whole call, including CSRF handling, retries and
+//! re-authentication, so a middleware sees exactly one request and one result
+//! per call.
+
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use http::{Extensions, HeaderMap, Method};
+use serde_json::Value;
+use url::Url;
+
+use crate::body::EncodedBody;
+use crate::{RequestBody, Response, UniFiResult};
+
+/// A request as seen by [`Middleware`] hooks.
+///
+/// Headers added here are sent on every attempt and take precedence over the
+/// headers set by the client.
+#[derive(Debug)]
+pub struct MiddlewareRequest {
+    method: Method,
+    url: Url,
+    body: Option<Value>,
+    /// An encoded form, multipart or raw body, which middlewares cannot see.
+    other_body: Option<EncodedBody>,
+    headers: HeaderMap,
+    extensions: Extensions,
+}
+
+impl MiddlewareRequest {
+    pub(crate) fn new(method: Method, url: Url, body: Option<RequestBody>) -> UniFiResult<Self> {
+        let (body, other_body) = match body {
+            Some(RequestBody::Json(value)) => (Some(value), None),
+            Some(other) => (None, Some(other.encode()?)),
+            None => (None, None),
+        };
+        Ok(Self {
+            method,
+            url,
+            body,
+            other_body,
+            headers: HeaderMap::new(),
+            extensions: Extensions::new(),
+        })
+    }
+
+    /// Returns the HTTP method.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Returns the full request URL.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the endpoint path, including any `/proxy/network` prefix.
+    pub fn endpoint(&self) -> &str {
+        self.url.path()
+    }
+
+    /// Returns the JSON body, if any.
+    ///
+    /// Form, multipart and raw bodies sent with
+    /// [`UniFiClient::request_with_body`](crate::UniFiClient::request_with_body)
+    /// are not exposed. Setting a JSON body replaces them.
+    pub fn body(&self) -> Option<&Value> {
+        self.body.as_ref()
+    }
+
+    /// Returns the JSON body for modification.
+    pub fn body_mut(&mut self) -> &mut Option<Value> {
+        &mut self.body
+    }
+
+    /// Returns the encoded body to send.
+    pub(crate) fn request_body(&self) -> UniFiResult<Option<EncodedBody>> {
+        match &self.body {
+            Some(value) => EncodedBody::json(value).map(Some),
+            None => Ok(self.other_body.clone()),
+        }
+    }
+
+    /// Returns the extra headers added by middlewares.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the extra headers for modification.
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        &mut self.headers
+    }
+
+    /// Returns per-request state shared between hooks.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    /// Returns per-request state for modification.
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
+}
+
+/// Async hooks around requests sent by the client.
+///
+/// Register middlewares with
+/// [`UniFiClientBuilder::middleware`](crate::UniFiClientBuilder::middleware).
+/// `on_request` hooks run in registration order before the request is sent;
+/// `on_response` hooks run in reverse order afterwards, onion-style. Returning
+/// an error from `on_request` aborts the request; the error is then passed to
+/// the `on_response` hooks of the middlewares that already ran.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use async_trait::async_trait;
+/// # use http::HeaderValue;
+/// # use unifi_client::{Middleware, MiddlewareRequest, UniFiClient, UniFiError, UniFiResult};
+/// struct RequestId;
+///
+/// #[async_trait]
+/// impl Middleware for RequestId {
+///     async fn on_request(&self, request: &mut MiddlewareRequest) -> UniFiResult<()> {
+///         request
+///             .headers_mut()
+///             .insert("x-request-id", HeaderValue::from_static("hotspot-1"));
+///         Ok(())
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), UniFiError> {
+/// let client = UniFiClient::builder()
+///     .controller_url("https://controller.example:8443")
+///     .username("admin")
+///     .password("secret")
+///     .middleware(RequestId)
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    /// Called before the request is sent. May modify the body and headers, or
+    /// fail the request.
+    async fn on_request(&self, _request: &mut MiddlewareRequest) -> UniFiResult<()> {
+        Ok(())
+    }
+
+    /// Called with the outcome of the request. May inspect, replace or
+    /// rewrite the response or error.
+    async fn on_response(
+        &self,
+        _request: &MiddlewareRequest,
+        result: UniFiResult<Response>,
+    ) -> UniFiResult<Response> {
+        result
+    }
+}
+
+/// Logs every request and its outcome through the `log` crate.
+///
+/// Requests are logged at `debug` level, responses at `info` level and
+/// failures at `warn` level. Bodies are not logged, as they may contain
+/// credentials.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoggingMiddleware;
+
+#[async_trait]
+impl Middleware for LoggingMiddleware {
+    async fn on_request(&self, request: &mut MiddlewareRequest) -> UniFiResult<()> {
+        log::debug!("--> {} {}", request.method(), request.endpoint());
+        Ok(())
+    }
+
+    async fn on_response(
+        &self,
+        request: &MiddlewareRequest,
+        result: UniFiResult<Response>,
+    ) -> UniFiResult<Response> {
+        match result {
+            Ok(ref response) => log::info!(
+                "<-- {} {} {}",
+                request.method(),
+                request.endpoint(),
+                response.status()
+            ),
+            Err(ref e) => log::warn!(
+                "<-- {} {} failed: {}",
+                request.method(),
+                request.endpoint(),
+                e
+            ),
+        }
+        result
+    }
+}
+
+/// Time a request took end to end, including retries and re-authentication.
+///
+/// Attached to responses by [`TimingMiddleware`] and readable through
+/// `response.extensions().get::<RequestTiming>()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestTiming(pub Duration);
+
+/// Measures how long each request takes.
+///
+/// The duration is attached to the response as a [`RequestTiming`] extension
+/// and logged at `debug` level.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimingMiddleware;
+
+#[derive(Clone, Copy)]
+struct RequestStart(Instant);
+
+#[async_trait]
+impl Middleware for TimingMiddleware {
+    async fn on_request(&self, request: &mut MiddlewareRequest) -> UniFiResult<()> {
+        request
+            .extensions_mut()
+            .insert(RequestStart(Instant::now()));
+        Ok(())
+    }
+
+    async fn on_response(
+        &self,
+        request: &MiddlewareRequest,
+        result: UniFiResult<Response>,
+    ) -> UniFiResult<Response> {
+        let Some(RequestStart(start)) = request.extensions().get::<RequestStart>().copied() else {
+            return result;
+        };
+        let elapsed = start.elapsed();
+        log::debug!(
+            "{} {} took {:?}",
+            request.method(),
+            request.endpoint(),
+            elapsed
+        );
+
+        result.map(|mut response| {
+            response.extensions_mut().insert(RequestTiming(elapsed));
+            response
+        })
+    }
+}
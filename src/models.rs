@@ -2,6 +2,7 @@ use serde::Deserialize;
 
 pub mod auth;
 pub mod guests;
+pub mod integration;
 
 /// Standard API response envelope from the UniFi controller.
 #[derive(Debug, Deserialize)]
@@ -23,6 +24,16 @@ pub struct ApiMeta {
     pub msg: Option<String>,
 }
 
+/// Error body returned by `/v2/api` endpoints for non-success responses.
+#[derive(Debug, Deserialize)]
+pub struct V2ErrorResponse {
+    /// Result code (e.g., `api.err.InvalidPayload`).
+    pub code: Option<String>,
+
+    /// Human-readable error message, if any.
+    pub message: Option<String>,
+}
+
 /// Empty response type for endpoints that don't return meaningful data
 #[derive(Debug, Deserialize)]
 pub struct EmptyResponse {}
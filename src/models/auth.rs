@@ -1,4 +1,5 @@
-use serde::Serialize;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Serialize, Serializer};
 
 /// Request to login to the UniFi controller.
 #[derive(Debug, Serialize)]
@@ -6,6 +7,22 @@ pub struct LoginRequest {
     /// The username to authenticate with.
     pub username: String,
 
-    /// The password to authenticate with.
-    pub password: String,
+    /// The password to authenticate with. Only exposed while serializing.
+    #[serde(serialize_with = "serialize_secret")]
+    pub password: SecretString,
+
+    /// One-time two-factor code (UniFi OS).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    /// One-time two-factor code (classic Network controllers).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ubic_2fa_token: Option<String>,
+}
+
+fn serialize_secret<S: Serializer>(
+    secret: &SecretString,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(secret.expose_secret())
 }
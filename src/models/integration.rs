@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Paged response envelope used by the UniFi Network Integration API.
+///
+/// Unlike the legacy `{ meta, data }` envelope, list endpoints of the
+/// Integration API return the page window alongside the items.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    /// Offset of the first item in this page.
+    pub offset: u32,
+
+    /// Maximum number of items requested for this page.
+    pub limit: u32,
+
+    /// Number of items in this page.
+    pub count: u32,
+
+    /// Total number of items available across all pages.
+    pub total_count: u32,
+
+    /// The items in this page.
+    pub data: Vec<T>,
+}
+
+impl<T> Page<T> {
+    /// Returns true if more items are available after this page.
+    pub fn has_more(&self) -> bool {
+        self.count > 0 && self.offset.saturating_add(self.count) < self.total_count
+    }
+}
+
+/// Error body returned by the Integration API for non-success responses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrationErrorResponse {
+    /// HTTP status code (e.g., 400).
+    pub status_code: u16,
+
+    /// Symbolic status name (e.g., "BAD_REQUEST").
+    pub status_name: String,
+
+    /// Human-readable error message.
+    pub message: String,
+
+    /// When the error occurred (ISO 8601).
+    pub timestamp: Option<String>,
+
+    /// The request path that produced the error.
+    pub request_path: Option<String>,
+
+    /// Identifier of the failed request, useful when reporting issues.
+    pub request_id: Option<String>,
+}
+
+/// Information about the UniFi Network application.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationInfo {
+    /// The Network application version (e.g., "9.1.120").
+    pub application_version: String,
+}
+
+/// A site as represented by the Integration API.
+///
+/// Note that the Integration API identifies sites by `id` (a UUID), whereas the
+/// legacy API uses the short site name (`internal_reference`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrationSite {
+    /// The unique identifier (UUID) used in Integration API paths.
+    pub id: String,
+
+    /// The legacy site name (e.g., "default").
+    pub internal_reference: String,
+
+    /// The human-readable site name.
+    pub name: String,
+}
+
+/// An adopted UniFi device (access point, switch, gateway, ...).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Device {
+    /// The unique identifier of the device.
+    pub id: String,
+
+    /// The device name.
+    pub name: String,
+
+    /// The device model (e.g., "U6 Pro").
+    pub model: String,
+
+    /// The device MAC address.
+    pub mac_address: String,
+
+    /// The device IP address, if known.
+    pub ip_address: Option<String>,
+
+    /// The device state (e.g., "ONLINE", "OFFLINE").
+    pub state: String,
+
+    /// Supported features (e.g., "switching", "accessPoint").
+    #[serde(default)]
+    pub features: Vec<String>,
+
+    /// Available interface kinds (e.g., "ports", "radios").
+    #[serde(default)]
+    pub interfaces: Vec<String>,
+
+    /// Additional attributes returned by detailed device lookups.
+    #[serde(flatten)]
+    pub attributes: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// A client connected to the network.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkClient {
+    /// The unique identifier of the client.
+    pub id: String,
+
+    /// The client type (e.g., "WIRED", "WIRELESS", "VPN").
+    #[serde(rename = "type")]
+    pub client_type: String,
+
+    /// The client name.
+    pub name: Option<String>,
+
+    /// When the client connected (ISO 8601).
+    pub connected_at: Option<String>,
+
+    /// The client IP address, if known.
+    pub ip_address: Option<String>,
+
+    /// The client MAC address (absent for VPN clients).
+    pub mac_address: Option<String>,
+
+    /// The device the client is connected through.
+    pub uplink_device_id: Option<String>,
+
+    /// Additional attributes not explicitly defined.
+    #[serde(flatten)]
+    pub attributes: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// A hotspot voucher.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Voucher {
+    /// The unique identifier of the voucher.
+    pub id: String,
+
+    /// When the voucher was created (ISO 8601).
+    pub created_at: String,
+
+    /// The voucher name (note).
+    pub name: String,
+
+    /// The code guests enter on the portal.
+    pub code: String,
+
+    /// How many guests may use this voucher, if limited.
+    pub authorized_guest_limit: Option<u32>,
+
+    /// How many guests have used this voucher.
+    pub authorized_guest_count: u32,
+
+    /// When the voucher was first used (ISO 8601).
+    pub activated_at: Option<String>,
+
+    /// When the voucher expires (ISO 8601).
+    pub expires_at: Option<String>,
+
+    /// Whether the voucher has expired.
+    pub expired: bool,
+
+    /// How long guest access lasts once the voucher is used.
+    pub time_limit_minutes: u32,
+
+    /// Data transfer limit in megabytes, if any.
+    #[serde(rename = "dataUsageLimitMBytes")]
+    pub data_usage_limit_megabytes: Option<u64>,
+
+    /// Download rate limit in Kbps, if any.
+    pub rx_rate_limit_kbps: Option<u32>,
+
+    /// Upload rate limit in Kbps, if any.
+    pub tx_rate_limit_kbps: Option<u32>,
+}
+
+/// Request to generate one or more hotspot vouchers.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateVouchersRequest {
+    /// Number of vouchers to generate.
+    pub count: u32,
+    /// Voucher name (note).
+    pub name: String,
+    /// How many guests may use each voucher.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorized_guest_limit: Option<u32>,
+    /// How long guest access lasts once a voucher is used.
+    pub time_limit_minutes: u32,
+    /// Data transfer limit in megabytes.
+    #[serde(
+        rename = "dataUsageLimitMBytes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub data_usage_limit_megabytes: Option<u64>,
+    /// Download rate limit in Kbps.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rx_rate_limit_kbps: Option<u32>,
+    /// Upload rate limit in Kbps.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_rate_limit_kbps: Option<u32>,
+}
+
+/// Response to a voucher generation request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateVouchersResponse {
+    /// The generated vouchers.
+    pub vouchers: Vec<Voucher>,
+}
+
+/// Response to a voucher deletion request.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteVouchersResponse {
+    /// Number of vouchers deleted.
+    pub vouchers_deleted: u32,
+}
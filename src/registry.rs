@@ -0,0 +1,195 @@
+//! Named clients for applications that talk to several controllers.
+//!
+//! Clients are registered under a name (`"production"`, `"lab"`, a customer
+//! ID, ...) and looked up from anywhere in the application. Lookups are
+//! lock-free; registering a client under a name that is already taken
+//! atomically replaces it, so requests holding the previous client finish on
+//! it while new lookups get the new one.
+//!
+//! [`initialize`] and [`instance`] manage the client registered as
+//! [`DEFAULT`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use unifi_client::{registry, UniFiClient, UniFiError};
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), UniFiError> {
+//! let lab = UniFiClient::builder()
+//!     .controller_url("https://lab.example:8443")
+//!     .username("admin")
+//!     .password("secret")
+//!     .build()
+//!     .await?;
+//! registry::register("lab", lab);
+//!
+//! let guests = registry::get("lab")?.guests().list().send().await?;
+//! for (name, client) in registry::clients() {
+//!     println!("{name}: {}", client.site());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use once_cell::sync::Lazy;
+
+use crate::{UniFiClient, UniFiError, UniFiResult};
+
+/// Name of the client managed by [`initialize`] and [`instance`].
+pub const DEFAULT: &str = "default";
+
+type Clients = HashMap<String, Arc<UniFiClient>>;
+
+// Copy-on-write map: writers replace the whole map, readers never block.
+static REGISTRY: Lazy<ArcSwap<Clients>> = Lazy::new(|| ArcSwap::from_pointee(Clients::new()));
+
+// Returned by `instance()` until `initialize()` is called.
+static INERT: Lazy<Arc<UniFiClient>> = Lazy::new(|| Arc::new(UniFiClient::default()));
+
+/// Registers `client` under `name`.
+///
+/// Returns the client previously registered under that name, if any.
+pub fn register(name: impl Into<String>, client: UniFiClient) -> Option<Arc<UniFiClient>> {
+    let name = name.into();
+    let client = Arc::new(client);
+    let previous = REGISTRY.rcu(|clients| {
+        let mut clients = Clients::clone(clients);
+        clients.insert(name.clone(), Arc::clone(&client));
+        clients
+    });
+    previous.get(&name).cloned()
+}
+
+/// Removes the client registered under `name` and returns it.
+pub fn unregister(name: &str) -> Option<Arc<UniFiClient>> {
+    let previous = REGISTRY.rcu(|clients| {
+        let mut clients = Clients::clone(clients);
+        clients.remove(name);
+        clients
+    });
+    previous.get(name).cloned()
+}
+
+/// Returns the client registered under `name`.
+///
+/// # Errors
+///
+/// Returns `UniFiError::ClientNotRegistered` if no client has that name.
+pub fn get(name: &str) -> UniFiResult<Arc<UniFiClient>> {
+    REGISTRY
+        .load()
+        .get(name)
+        .cloned()
+        .ok_or_else(|| UniFiError::ClientNotRegistered(name.to_string()))
+}
+
+/// Returns all registered clients with their names, sorted by name.
+///
+/// The result is a snapshot; later registrations do not affect it.
+pub fn clients() -> Vec<(String, Arc<UniFiClient>)> {
+    let mut clients: Vec<_> = REGISTRY
+        .load()
+        .iter()
+        .map(|(name, client)| (name.clone(), Arc::clone(client)))
+        .collect();
+    clients.sort_by(|a, b| a.0.cmp(&b.0));
+    clients
+}
+
+/// Initializes the global UniFi client instance.
+///
+/// # Warning
+///
+/// Call this exactly once at application startup. Calling it again will
+/// replace the existing instance used by `instance()`.
+///
+/// This registers `client` under the name [`DEFAULT`]; use [`register`] to
+/// manage further controllers.
+///
+/// # Arguments
+///
+/// - `client` - A fully constructed `UniFiClient`.
+///
+/// # Returns
+///
+/// - `Arc<UniFiClient>`: The previously configured global client instance. On the first call this
+///   will be the inert default instance.
+///
+/// # Examples
+///
+/// Basic initialization:
+/// ```no_run
+/// # use unifi_client::UniFiClient;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = UniFiClient::builder()
+///     .controller_url("https://controller.example:8443")
+///     .username("admin")
+///     .password("secret")
+///     // .accept_invalid_certs(true) // only for lab/test
+///     .build()
+///     .await?;
+/// let _prev = unifi_client::initialize(client);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Swapping instances (e.g., tests or hot-reload scenarios):
+/// ```no_run
+/// # use unifi_client::UniFiClient;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client_a = UniFiClient::builder()
+///     .controller_url("https://a.example:8443")
+///     .username("user_a")
+///     .password("pass_a")
+///     .build()
+///     .await?;
+/// let _ = unifi_client::initialize(client_a);
+///
+/// let client_b = UniFiClient::builder()
+///     .controller_url("https://b.example:8443")
+///     .username("user_b")
+///     .password("pass_b")
+///     .build()
+///     .await?;
+/// let previous = unifi_client::initialize(client_b);
+/// // `previous` is the prior global client (client_a).
+/// # Ok(())
+/// # }
+/// ```
+pub fn initialize(client: UniFiClient) -> Arc<UniFiClient> {
+    // Swap in the provided client and return the previous instance.
+    register(DEFAULT, client).unwrap_or_else(|| Arc::clone(&INERT))
+}
+
+/// Returns a reference to the global UniFi client instance.
+///
+/// # Returns
+///
+/// - `Arc<UniFiClient>`: A thread-safe handle to the current client. If `initialize()` hasn't been
+///   called, a default (unauthenticated) client is returned. Use [`get`] to fail with an error
+///   instead.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use unifi_client::UniFiError;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), UniFiError> {
+/// let client = unifi_client::instance();
+/// // Use `client` to perform requests...
+/// # Ok(())
+/// # }
+/// ```
+pub fn instance() -> Arc<UniFiClient> {
+    REGISTRY
+        .load()
+        .get(DEFAULT)
+        .cloned()
+        .unwrap_or_else(|| Arc::clone(&INERT))
+}
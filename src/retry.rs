@@ -0,0 +1,286 @@
+//! Retry policy for transient request failures.
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use http::header::RETRY_AFTER;
+use http::{Method, StatusCode};
+
+use crate::{Response, UniFiError};
+
+/// Decides whether a request may be replayed, given its method and URL path.
+type IdempotencyRule = dyn Fn(&Method, &str) -> bool + Send + Sync;
+
+/// Controls how [`UniFiClient`](crate::UniFiClient) retries requests that fail
+/// for transient reasons.
+///
+/// Transient failures are connection errors, timeouts, and the statuses `429
+/// Too Many Requests`, `502 Bad Gateway`, `503 Service Unavailable` and `504
+/// Gateway Timeout` that controllers return while restarting or under load.
+/// Retries wait with exponential backoff (optionally with jitter) and honor
+/// the `Retry-After` header.
+///
+/// Only idempotent requests are replayed after they may have reached the
+/// controller. By default that means every method except `POST` and `PATCH`,
+/// so `cmd/*` commands are never sent twice by accident; use
+/// [`idempotency_rule`](Self::idempotency_rule) to mark specific POST
+/// endpoints as safe. Requests that could not connect at all are always
+/// retried.
+///
+/// This is independent of the single re-login performed on `401`/`403`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use unifi_client::{RetryPolicy, UniFiClient, UniFiError};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), UniFiError> {
+/// let client = UniFiClient::builder()
+///     .controller_url("https://controller.example:8443")
+///     .username("admin")
+///     .password("secret")
+///     .retry_policy(
+///         RetryPolicy::default()
+///             .max_attempts(5)
+///             .initial_backoff(Duration::from_millis(200))
+///             // `stat/*` endpoints only read data, even when sent as POST.
+///             .idempotency_rule(|method, path| method.is_idempotent() || path.contains("/stat/")),
+///     )
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    respect_retry_after: bool,
+    idempotency_rule: Arc<IdempotencyRule>,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries transient failures.
+    ///
+    /// This is what clients use unless a policy is configured.
+    pub fn none() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// Sets the maximum number of attempts, including the first one.
+    ///
+    /// Default is `3`. Values below `1` are treated as `1`.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the delay before the first retry. Each further retry doubles it.
+    ///
+    /// Default is 250 milliseconds.
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Sets the upper bound for any single delay, including delays requested
+    /// through `Retry-After`.
+    ///
+    /// Default is 10 seconds.
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Enables or disables random jitter on backoff delays.
+    ///
+    /// With jitter, each delay is picked uniformly between half and the full
+    /// backoff so that many clients do not retry in lockstep. Default is `true`.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Enables or disables honoring the `Retry-After` header of `429` and
+    /// `503` responses. Default is `true`.
+    pub fn respect_retry_after(mut self, respect: bool) -> Self {
+        self.respect_retry_after = respect;
+        self
+    }
+
+    /// Sets the rule deciding which requests may be replayed after they may
+    /// have reached the controller.
+    ///
+    /// The rule receives the request method and URL path (including any
+    /// `/proxy/network` prefix). Default is [`Method::is_idempotent`].
+    pub fn idempotency_rule<F>(mut self, rule: F) -> Self
+    where
+        F: Fn(&Method, &str) -> bool + Send + Sync + 'static,
+    {
+        self.idempotency_rule = Arc::new(rule);
+        self
+    }
+
+    /// Returns true if another attempt is allowed after `attempt` attempts.
+    pub(crate) fn has_attempts_left(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+
+    /// Returns true if a failed send may be retried.
+    pub(crate) fn should_retry_error(
+        &self,
+        method: &Method,
+        path: &str,
+        error: &UniFiError,
+    ) -> bool {
+        let may_have_reached =
+            error.is_timeout() || matches!(error, UniFiError::HttpError(e) if e.is_request());
+        // Nothing reached the controller if we could not connect.
+        error.is_connect() || (may_have_reached && (self.idempotency_rule)(method, path))
+    }
+
+    /// Returns true if a response status is transient and the request may be
+    /// retried.
+    pub(crate) fn should_retry_status(
+        &self,
+        method: &Method,
+        path: &str,
+        status: StatusCode,
+    ) -> bool {
+        is_transient_status(status) && (self.idempotency_rule)(method, path)
+    }
+
+    /// Computes the delay before the next attempt, given the number of
+    /// attempts made so far and the response that failed (if any).
+    pub(crate) fn delay(&self, attempt: u32, response: Option<&Response>) -> Duration {
+        if self.respect_retry_after {
+            if let Some(retry_after) = response.and_then(retry_after) {
+                return retry_after.min(self.max_backoff);
+            }
+        }
+        self.backoff(attempt)
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+
+        if self.jitter {
+            let half = backoff / 2;
+            let spread = half.as_millis() as u64;
+            half + Duration::from_millis(random_u64() % (spread + 1))
+        } else {
+            backoff
+        }
+    }
+}
+
+/// Defaults: 3 attempts, 250ms initial backoff doubling up to 10s, jitter on,
+/// `Retry-After` honored, and only idempotent methods replayed.
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            respect_retry_after: true,
+            idempotency_rule: Arc::new(|method, _| method.is_idempotent()),
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("jitter", &self.jitter)
+            .field("respect_retry_after", &self.respect_retry_after)
+            .finish_non_exhaustive()
+    }
+}
+
+pub(crate) fn is_transient_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+// Parse `Retry-After` given in delta-seconds on 429/503 responses.
+fn retry_after(response: &Response) -> Option<Duration> {
+    if !matches!(
+        response.status(),
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+    ) {
+        return None;
+    }
+    parse_retry_after(response.headers().get(RETRY_AFTER)?.to_str().ok()?)
+}
+
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+// Cheap randomness for jitter; `RandomState` is seeded randomly per instance.
+fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_and_is_capped() {
+        let policy = RetryPolicy::default()
+            .jitter(false)
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(350));
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+        assert_eq!(policy.backoff(60), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn jitter_stays_within_half_and_full_backoff() {
+        let policy = RetryPolicy::default().initial_backoff(Duration::from_millis(100));
+        for _ in 0..100 {
+            let delay = policy.backoff(1);
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn parses_retry_after_seconds_only() {
+        assert_eq!(parse_retry_after("3"), Some(Duration::from_secs(3)));
+        assert_eq!(parse_retry_after(" 0 "), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+
+    #[test]
+    fn default_rule_does_not_replay_post() {
+        let policy = RetryPolicy::default();
+        let path = "/api/s/default/cmd/stamgr";
+        assert!(!policy.should_retry_status(&Method::POST, path, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(policy.should_retry_status(&Method::GET, path, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!policy.should_retry_status(&Method::GET, path, StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!RetryPolicy::none().has_attempts_left(1));
+    }
+}
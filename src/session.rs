@@ -0,0 +1,199 @@
+//! Session lifecycle: export and restore of authenticated sessions, and
+//! logout when a client is no longer used.
+//!
+//! A session snapshot captures everything needed to reuse a live login from a
+//! different process: the session cookies, the UniFi OS CSRF token, and the
+//! detected controller layout. It is encrypted with ChaCha20-Poly1305 under a
+//! key derived from the caller's session key, and encoded as the format
+//! version and the base64-encoded nonce and ciphertext, separated by a dot.
+
+use std::ops::Deref;
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use data_encoding::BASE64URL_NOPAD;
+use hmac::{Hmac, Mac};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::client::ControllerKind;
+use crate::{UniFiClient, UniFiError, UniFiResult};
+
+/// Version of the snapshot format; bumped on incompatible changes.
+const SESSION_FORMAT_VERSION: &str = "v1";
+
+/// Length of the ChaCha20-Poly1305 nonce that prefixes the ciphertext.
+const NONCE_LEN: usize = 12;
+
+/// Serializable state of an authenticated session.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SessionState {
+    pub(crate) controller_url: String,
+    pub(crate) api_base_url: String,
+    pub(crate) controller_kind: ControllerKind,
+    pub(crate) cookies: Vec<SessionCookie>,
+    pub(crate) csrf_token: Option<String>,
+}
+
+/// A session cookie and the URL it was collected from.
+///
+/// Restoring the cookie against the same URL reproduces its original default
+/// path, so a fresh cookie set by a later login replaces it instead of
+/// shadowing it.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SessionCookie {
+    pub(crate) url: String,
+    /// The cookie as a `name=value` pair.
+    pub(crate) pair: String,
+}
+
+impl SessionState {
+    pub(crate) fn new(
+        controller_url: String,
+        api_base_url: String,
+        controller_kind: ControllerKind,
+        cookies: Vec<SessionCookie>,
+        csrf_token: Option<String>,
+    ) -> Self {
+        Self {
+            controller_url,
+            api_base_url,
+            controller_kind,
+            cookies,
+            csrf_token,
+        }
+    }
+
+    /// Serializes the session and encrypts it with `key`.
+    pub(crate) fn encode(&self, key: &SecretString) -> UniFiResult<SecretString> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = session_cipher(key)
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &serde_json::to_vec(self)?,
+                    aad: SESSION_FORMAT_VERSION.as_bytes(),
+                },
+            )
+            .map_err(|_| UniFiError::ConfigurationError("Failed to encrypt session".into()))?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(SecretString::from(format!(
+            "{SESSION_FORMAT_VERSION}.{}",
+            BASE64URL_NOPAD.encode(&sealed)
+        )))
+    }
+
+    /// Decrypts and parses a blob produced by [`SessionState::encode`].
+    ///
+    /// Blobs that were modified or encrypted with another key are rejected.
+    pub(crate) fn decode(blob: &SecretString, key: &SecretString) -> UniFiResult<Self> {
+        let invalid = || UniFiError::ConfigurationError("Invalid session data".into());
+        let (version, payload) = blob.expose_secret().split_once('.').ok_or_else(invalid)?;
+        if version != SESSION_FORMAT_VERSION {
+            return match version.strip_prefix('v').map(str::parse::<u32>) {
+                Some(Ok(_)) => Err(UniFiError::ConfigurationError(format!(
+                    "Unsupported session format version: {version}"
+                ))),
+                _ => Err(invalid()),
+            };
+        }
+        let sealed = BASE64URL_NOPAD
+            .decode(payload.as_bytes())
+            .map_err(|_| invalid())?;
+        if sealed.len() < NONCE_LEN {
+            return Err(invalid());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let json = session_cipher(key)
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: SESSION_FORMAT_VERSION.as_bytes(),
+                },
+            )
+            .map_err(|_| {
+                UniFiError::ConfigurationError(
+                    "Session data was modified or encrypted with a different key".into(),
+                )
+            })?;
+        serde_json::from_slice(&json).map_err(|_| invalid())
+    }
+}
+
+// Derive the 256-bit cipher key from the caller's session key, which may be a passphrase of any
+// length (HKDF-extract with a fixed salt).
+fn session_cipher(key: &SecretString) -> ChaCha20Poly1305 {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(b"unifi-client session")
+        .expect("HMAC accepts keys of any length");
+    mac.update(key.expose_secret().as_bytes());
+    ChaCha20Poly1305::new(&mac.finalize().into_bytes())
+}
+
+/// Logs the client out when the last clone of it is dropped.
+///
+/// Long-running services otherwise leave an admin session behind on the
+/// controller every time they restart. The guard derefs to [`UniFiClient`].
+/// Wrapping registers the logout on the session the client clones share, so
+/// it runs once the last of them is gone: clones of the guard, clones of the
+/// client taken before or after wrapping, [`site_scope`](UniFiClient::site_scope)
+/// clients and the API handlers all keep the session alive.
+///
+/// Dropping cannot wait, so that logout is spawned on the Tokio runtime that
+/// created the guard and is lost if the runtime is shutting down, as it is
+/// when `main` returns. Call [`shutdown`](Self::shutdown) before exiting to
+/// log out deterministically; the drop is only a fallback.
+///
+/// # Example
+///
+/// ```no_run
+/// # use unifi_client::{LogoutGuard, UniFiClient, UniFiError};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), UniFiError> {
+/// let client = LogoutGuard::new(
+///     UniFiClient::builder()
+///         .controller_url("https://controller.example:8443")
+///         .username("admin")
+///         .password("secret")
+///         .build()
+///         .await?,
+/// );
+/// let guests = client.guests().list().send().await?;
+/// client.shutdown().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct LogoutGuard {
+    client: UniFiClient,
+}
+
+impl LogoutGuard {
+    /// Wraps a client so that its session is logged out when the last clone
+    /// of the client is dropped.
+    ///
+    /// Must be called within a Tokio runtime for the logout on drop to run.
+    /// API key clients have no session and are wrapped unchanged.
+    pub fn new(client: UniFiClient) -> Self {
+        client.logout_on_drop();
+        Self { client }
+    }
+
+    /// Logs out and waits for the controller to confirm.
+    ///
+    /// Every clone of the client is logged out, and nothing is left for the
+    /// drop to do. See [`UniFiClient::logout`].
+    pub async fn shutdown(self) -> UniFiResult<()> {
+        self.client.logout().await
+    }
+}
+
+impl Deref for LogoutGuard {
+    type Target = UniFiClient;
+
+    fn deref(&self) -> &UniFiClient {
+        &self.client
+    }
+}
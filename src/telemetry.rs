@@ -0,0 +1,163 @@
+//! Metrics emitted through the [`metrics`](https://docs.rs/metrics) facade.
+//!
+//! Enabled with the `metrics` feature; without it every function here is a
+//! no-op. Install any `metrics` recorder (Prometheus, StatsD, ...) to collect:
+//!
+//! - `unifi_requests_total` (counter) and `unifi_request_duration_seconds` (histogram), labeled
+//!   with `method`, `endpoint` and `status`
+//! - `unifi_logins_total` (counter), labeled with `kind` (`initial` or `relogin`) and `result`
+//!   (`success` or `failure`)
+//! - `unifi_csrf_rotations_total` (counter)
+//! - `unifi_errors_total` (counter), labeled with `operation` (`request` or `login`) and `error`
+//!   (the [`UniFiError`] variant)
+//!
+//! Endpoints are reported as templates (e.g. `/api/s/{site}/stat/guest`) so
+//! that site names, MAC addresses and IDs do not explode label cardinality.
+
+use std::time::Duration;
+
+use http::Method;
+
+#[cfg(feature = "metrics")]
+use crate::UniFiError;
+use crate::{Response, UniFiResult};
+
+/// Records a completed call to `UniFiClient::request`.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_request(
+    method: &Method,
+    path: &str,
+    result: &UniFiResult<Response>,
+    elapsed: Duration,
+) {
+    #[cfg(feature = "metrics")]
+    {
+        let status = match result {
+            Ok(response) => response.status().as_u16().to_string(),
+            Err(_) => "error".to_string(),
+        };
+        let labels = [
+            ("method", method.to_string()),
+            ("endpoint", endpoint_template(path)),
+            ("status", status),
+        ];
+        metrics::counter!("unifi_requests_total", &labels).increment(1);
+        metrics::histogram!("unifi_request_duration_seconds", &labels).record(elapsed);
+
+        if let Err(e) = result {
+            record_error("request", e);
+        }
+    }
+}
+
+/// Records a login attempt; `relogin` is true when an existing session was refreshed.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_login(relogin: bool, result: &UniFiResult<()>) {
+    #[cfg(feature = "metrics")]
+    {
+        let kind = if relogin { "relogin" } else { "initial" };
+        let outcome = if result.is_ok() { "success" } else { "failure" };
+        metrics::counter!("unifi_logins_total", "kind" => kind, "result" => outcome).increment(1);
+
+        if let Err(e) = result {
+            record_error("login", e);
+        }
+    }
+}
+
+/// Records a CSRF token rotated by the controller.
+pub(crate) fn record_csrf_rotation() {
+    #[cfg(feature = "metrics")]
+    metrics::counter!("unifi_csrf_rotations_total").increment(1);
+}
+
+#[cfg(feature = "metrics")]
+fn record_error(operation: &'static str, error: &UniFiError) {
+    metrics::counter!(
+        "unifi_errors_total",
+        "operation" => operation,
+        "error" => error_label(error)
+    )
+    .increment(1);
+}
+
+#[cfg(feature = "metrics")]
+fn error_label(error: &UniFiError) -> &'static str {
+    match error {
+        UniFiError::AuthenticationError(_) => "authentication",
+        UniFiError::ApiError(_) => "api",
+        UniFiError::ControllerError(_) => "controller",
+        UniFiError::HttpError(_) => "http",
+        UniFiError::TransportError(_) => "transport",
+        UniFiError::IoError(_) => "io",
+        UniFiError::UrlParseError(_) => "url_parse",
+        UniFiError::InvalidEndpoint(_) => "invalid_endpoint",
+        UniFiError::SerializationError(_) => "serialization",
+        UniFiError::MfaRequired => "mfa_required",
+        UniFiError::NotAuthenticated => "not_authenticated",
+        UniFiError::SiteNotFound(_) => "site_not_found",
+        UniFiError::ClientNotRegistered(_) => "client_not_registered",
+        UniFiError::ConfigurationError(_) => "configuration",
+    }
+}
+
+/// Replaces site names, MAC addresses and IDs in a request path with placeholders.
+#[cfg(feature = "metrics")]
+fn endpoint_template(path: &str) -> String {
+    let mut template = String::with_capacity(path.len());
+    let mut previous = "";
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        template.push('/');
+        if matches!(previous, "s" | "site") {
+            template.push_str("{site}");
+        } else if is_identifier(segment) {
+            template.push_str("{id}");
+        } else {
+            template.push_str(segment);
+        }
+        previous = segment;
+    }
+    if template.is_empty() {
+        template.push('/');
+    }
+    template
+}
+
+// Numeric IDs, MAC addresses, 24-digit hex object IDs and UUIDs.
+#[cfg(feature = "metrics")]
+fn is_identifier(segment: &str) -> bool {
+    let hex_or = |extra: char| segment.chars().all(|c| c.is_ascii_hexdigit() || c == extra);
+    segment.bytes().all(|b| b.is_ascii_digit())
+        || (segment.len() == 17 && hex_or(':'))
+        || (segment.len() == 24 && hex_or('-'))
+        || (segment.len() == 36 && hex_or('-'))
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoint_template_hides_sites_and_ids() {
+        assert_eq!(
+            endpoint_template("/proxy/network/api/s/default/stat/guest"),
+            "/proxy/network/api/s/{site}/stat/guest"
+        );
+        assert_eq!(
+            endpoint_template("/api/s/hq/stat/user/00:11:22:aa:bb:cc"),
+            "/api/s/{site}/stat/user/{id}"
+        );
+        assert_eq!(
+            endpoint_template("/api/s/default/rest/user/5f1b2c3d4e5f6a7b8c9d0e1f"),
+            "/api/s/{site}/rest/user/{id}"
+        );
+        assert_eq!(
+            endpoint_template(
+                "/proxy/network/integration/v1/sites/88f7af54-98f8-306a-a1c7-c9349722b1f6/devices"
+            ),
+            "/proxy/network/integration/v1/sites/{id}/devices"
+        );
+        assert_eq!(endpoint_template("/api/self/sites"), "/api/self/sites");
+        assert_eq!(endpoint_template("/"), "/");
+    }
+}
@@ -0,0 +1,572 @@
+//! A stateful fake UniFi controller for testing code built on this crate.
+//!
+//! [`FakeController`] serves a small but faithful subset of the controller
+//! API on a random localhost port: controller detection, login and logout for
+//! both controller kinds, CSRF tokens and their rotation on UniFi OS, session
+//! expiry, and a guest store behind `cmd/stamgr` (`authorize-guest`,
+//! `unauthorize-guest`) and `stat/guest`. Time only moves when the test calls
+//! [`FakeController::advance`], so expiry is deterministic, and [`Fault`]s
+//! make chosen requests fail.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use std::time::Duration;
+//! # use unifi_client::testing::FakeController;
+//! # use unifi_client::{ControllerKind, UniFiError};
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), UniFiError> {
+//! let controller = FakeController::start(ControllerKind::Os).await;
+//! let client = controller.client().await?;
+//!
+//! client
+//!     .guests()
+//!     .authorize("00:11:22:33:44:55")
+//!     .duration_minutes(60)
+//!     .send()
+//!     .await?;
+//!
+//! controller.advance(Duration::from_secs(61 * 60));
+//! assert!(controller.guests("default")[0].is_expired());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use http::{HeaderMap, StatusCode};
+use serde_json::{json, Value};
+use wiremock::matchers::any;
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+use crate::models::guests::GuestEntry;
+use crate::{ControllerKind, UniFiClient, UniFiClientBuilder, UniFiResult};
+
+/// Guest authorizations last this long unless `minutes` is given.
+const DEFAULT_GUEST_MINUTES: i64 = 480;
+
+/// A fake UniFi controller listening on localhost.
+///
+/// The controller shuts down when dropped. All methods take `&self`, so a
+/// test can reconfigure the controller while clients are talking to it.
+pub struct FakeController {
+    server: MockServer,
+    kind: ControllerKind,
+    state: Arc<Mutex<State>>,
+}
+
+impl FakeController {
+    /// Username accepted by a new controller.
+    pub const USERNAME: &'static str = "admin";
+    /// Password accepted by a new controller.
+    pub const PASSWORD: &'static str = "password";
+    /// Network application version reported during detection.
+    pub const VERSION: &'static str = "9.0.114";
+
+    /// Starts a controller of the given kind on a random localhost port.
+    pub async fn start(kind: ControllerKind) -> Self {
+        let server = MockServer::start().await;
+        let state = Arc::new(Mutex::new(State::new()));
+        Mock::given(any())
+            .respond_with(Responder {
+                kind,
+                state: Arc::clone(&state),
+            })
+            .mount(&server)
+            .await;
+        Self {
+            server,
+            kind,
+            state,
+        }
+    }
+
+    /// Returns the controller URL, e.g. `http://127.0.0.1:39213`.
+    pub fn uri(&self) -> String {
+        self.server.uri()
+    }
+
+    /// Returns the kind of controller being emulated.
+    pub fn kind(&self) -> ControllerKind {
+        self.kind
+    }
+
+    /// Returns a client builder with this controller's URL and credentials.
+    pub fn client_builder(&self) -> UniFiClientBuilder {
+        let state = self.state();
+        UniFiClient::builder()
+            .controller_url(self.uri())
+            .username(state.username.clone())
+            .password(state.password.clone())
+    }
+
+    /// Builds and logs in a client for the `default` site.
+    pub async fn client(&self) -> UniFiResult<UniFiClient> {
+        self.client_builder().build().await
+    }
+
+    /// Changes the accepted username and password. Existing sessions stay
+    /// valid.
+    pub fn set_credentials(&self, username: impl Into<String>, password: impl Into<String>) {
+        let mut state = self.state();
+        state.username = username.into();
+        state.password = password.into();
+    }
+
+    /// Returns the controller's current time as a Unix timestamp.
+    ///
+    /// The clock starts at the wall-clock time when the controller starts and
+    /// then only moves through [`advance`](Self::advance).
+    pub fn now(&self) -> i64 {
+        self.state().now
+    }
+
+    /// Moves the controller's clock forward.
+    pub fn advance(&self, by: Duration) {
+        self.state().now += by.as_secs() as i64;
+    }
+
+    /// Makes sessions expire `ttl` after login, measured on the controller's
+    /// clock. Sessions never expire by default.
+    pub fn set_session_ttl(&self, ttl: Duration) {
+        self.state().session_ttl = Some(ttl.as_secs() as i64);
+    }
+
+    /// Ends all sessions; the next request of every client gets a `401`.
+    pub fn expire_sessions(&self) {
+        self.state().sessions.clear();
+    }
+
+    /// Rotates the CSRF token of the session used by the next authenticated
+    /// request. The new token is returned in `X-Updated-CSRF-Token` and the
+    /// old one is rejected afterwards. Ignored by Network controllers.
+    pub fn rotate_csrf(&self) {
+        self.state().rotate_csrf = true;
+    }
+
+    /// Queues a fault; see [`Fault`].
+    pub fn inject_fault(&self, fault: Fault) {
+        self.state().faults.push(fault);
+    }
+
+    /// Returns the guest authorizations of `site`, oldest first.
+    pub fn guests(&self, site: &str) -> Vec<GuestEntry> {
+        let state = self.state();
+        state
+            .guests
+            .get(site)
+            .map(|guests| guests.iter().map(|g| g.entry(site, state.now)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the number of successful logins so far.
+    pub fn login_count(&self) -> usize {
+        self.state().logins
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A failure injected into the [`FakeController`].
+///
+/// Faults apply, in the order they were injected, to requests under the
+/// Network API (not to detection, login or logout) unless narrowed with
+/// [`path`](Self::path).
+///
+/// ```no_run
+/// # use unifi_client::testing::{FakeController, Fault};
+/// # fn example(controller: &FakeController) {
+/// // The next two guest listings fail while the controller "restarts".
+/// controller.inject_fault(Fault::status(503).path("/stat/guest").times(2));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Fault {
+    status: Option<StatusCode>,
+    path: Option<String>,
+    times: usize,
+    delay: Duration,
+    retry_after: Option<u64>,
+}
+
+impl Fault {
+    /// Answers with `status` and an error envelope instead of handling the
+    /// request.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `status` is not a valid HTTP status code.
+    pub fn status(status: u16) -> Self {
+        Self {
+            status: Some(StatusCode::from_u16(status).expect("invalid status code")),
+            ..Self::delay(Duration::ZERO)
+        }
+    }
+
+    /// Handles the request normally but answers after `delay`, e.g. to
+    /// trigger client timeouts.
+    pub fn delay(delay: Duration) -> Self {
+        Self {
+            status: None,
+            path: None,
+            times: 1,
+            delay,
+            retry_after: None,
+        }
+    }
+
+    /// Only applies to requests whose path contains `path`, including login
+    /// and logout requests.
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Applies to the next `times` matching requests. Default is `1`;
+    /// values below `1` are treated as `1`.
+    pub fn times(mut self, times: usize) -> Self {
+        self.times = times.max(1);
+        self
+    }
+
+    /// Adds a `Retry-After` header with the given number of seconds.
+    pub fn retry_after(mut self, seconds: u64) -> Self {
+        self.retry_after = Some(seconds);
+        self
+    }
+
+    fn matches(&self, path: &str, is_api: bool) -> bool {
+        match self.path {
+            Some(ref fragment) => path.contains(fragment.as_str()),
+            None => is_api,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Session {
+    csrf_token: String,
+    expires_at: Option<i64>,
+}
+
+#[derive(Debug)]
+struct Guest {
+    id: String,
+    mac: String,
+    start: i64,
+    end: i64,
+    unauthorized: bool,
+    up: Option<u32>,
+    down: Option<u32>,
+    bytes: Option<i64>,
+}
+
+impl Guest {
+    fn entry(&self, site: &str, now: i64) -> GuestEntry {
+        GuestEntry::Inactive {
+            id: self.id.clone(),
+            authorized_by: "api".into(),
+            end: self.end,
+            expired: self.unauthorized || now >= self.end,
+            mac: self.mac.clone(),
+            site_id: site.into(),
+            start: self.start,
+            unauthorized_by: self.unauthorized.then(|| "api".into()),
+            qos_overwrite: self.has_qos().then_some(true),
+            qos_rate_max_down: self.down,
+            qos_rate_max_up: self.up,
+            qos_usage_quota: self.bytes,
+        }
+    }
+
+    fn has_qos(&self) -> bool {
+        self.up.is_some() || self.down.is_some() || self.bytes.is_some()
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    username: String,
+    password: String,
+    now: i64,
+    session_ttl: Option<i64>,
+    sessions: HashMap<String, Session>,
+    rotate_csrf: bool,
+    faults: Vec<Fault>,
+    guests: HashMap<String, Vec<Guest>>,
+    logins: usize,
+    next_id: u64,
+}
+
+impl State {
+    fn new() -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        Self {
+            username: FakeController::USERNAME.into(),
+            password: FakeController::PASSWORD.into(),
+            now,
+            session_ttl: None,
+            sessions: HashMap::new(),
+            rotate_csrf: false,
+            faults: Vec::new(),
+            guests: HashMap::new(),
+            logins: 0,
+            next_id: 0,
+        }
+    }
+
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Removes and returns the first fault matching the request.
+    fn take_fault(&mut self, path: &str, is_api: bool) -> Option<Fault> {
+        let index = self.faults.iter().position(|f| f.matches(path, is_api))?;
+        let fault = self.faults[index].clone();
+        self.faults[index].times -= 1;
+        if self.faults[index].times == 0 {
+            self.faults.remove(index);
+        }
+        Some(fault)
+    }
+}
+
+/// Answers every request on the mock server from the shared state.
+struct Responder {
+    kind: ControllerKind,
+    state: Arc<Mutex<State>>,
+}
+
+impl Respond for Responder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let path = request.url.path();
+        let api_path = match self.kind {
+            ControllerKind::Os => path.strip_prefix("/proxy/network"),
+            ControllerKind::Network => Some(path),
+        }
+        .filter(|p| p.starts_with("/api/s/") || *p == "/api/self");
+
+        match state.take_fault(path, api_path.is_some()) {
+            Some(Fault {
+                status: Some(status),
+                delay,
+                retry_after,
+                ..
+            }) => {
+                let mut response = error(status, "api.err.Fault").set_delay(delay);
+                if let Some(seconds) = retry_after {
+                    response = response.insert_header("retry-after", seconds.to_string());
+                }
+                response
+            }
+            Some(fault) => self
+                .handle(&mut state, request, api_path)
+                .set_delay(fault.delay),
+            None => self.handle(&mut state, request, api_path),
+        }
+    }
+}
+
+impl Responder {
+    fn handle(
+        &self,
+        state: &mut State,
+        request: &Request,
+        api_path: Option<&str>,
+    ) -> ResponseTemplate {
+        let method = request.method.as_str();
+        let path = request.url.path();
+        match (self.kind, method, path) {
+            (ControllerKind::Os, "GET", "/api/system") => ResponseTemplate::new(200).set_body_json(
+                json!({ "name": "Fake UniFi OS", "hardware": { "shortname": "FAKE" } }),
+            ),
+            (ControllerKind::Os, "GET", "/proxy/network/status")
+            | (ControllerKind::Network, "GET", "/status") => ResponseTemplate::new(200)
+                .set_body_json(json!({
+                    "meta": { "rc": "ok", "up": true, "server_version": FakeController::VERSION },
+                    "data": []
+                })),
+            (ControllerKind::Os, "POST", "/api/auth/login")
+            | (ControllerKind::Network, "POST", "/api/login") => self.login(state, request),
+            (ControllerKind::Os, "POST", "/api/auth/logout")
+            | (ControllerKind::Network, "POST", "/api/logout") => {
+                if let Some(id) = session_id(self.kind, &request.headers) {
+                    state.sessions.remove(&id);
+                }
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "meta": { "rc": "ok" }, "data": [] }))
+            }
+            _ => match api_path {
+                Some(api_path) => self.api(state, request, api_path),
+                None => error(StatusCode::NOT_FOUND, "api.err.NotFound"),
+            },
+        }
+    }
+
+    fn login(&self, state: &mut State, request: &Request) -> ResponseTemplate {
+        let body: Value = request.body_json().unwrap_or_default();
+        if body["username"] != state.username.as_str()
+            || body["password"] != state.password.as_str()
+        {
+            return match self.kind {
+                ControllerKind::Os => ResponseTemplate::new(401)
+                    .set_body_json(json!({ "code": "AUTHENTICATION_FAILED_INVALID_CREDENTIALS" })),
+                ControllerKind::Network => error(StatusCode::BAD_REQUEST, "api.err.Invalid"),
+            };
+        }
+
+        let n = state.next_id();
+        let id = format!("fake-session-{n}");
+        let csrf_token = format!("fake-csrf-{n}");
+        let expires_at = state.session_ttl.map(|ttl| state.now + ttl);
+        state.sessions.insert(
+            id.clone(),
+            Session {
+                csrf_token: csrf_token.clone(),
+                expires_at,
+            },
+        );
+        state.logins += 1;
+
+        match self.kind {
+            ControllerKind::Os => ResponseTemplate::new(200)
+                .insert_header("set-cookie", format!("TOKEN={id}; Path=/; HttpOnly"))
+                .insert_header("x-csrf-token", csrf_token)
+                .set_body_json(json!({ "username": state.username })),
+            ControllerKind::Network => ResponseTemplate::new(200)
+                .insert_header("set-cookie", format!("unifises={id}; Path=/; HttpOnly"))
+                .set_body_json(json!({ "meta": { "rc": "ok" }, "data": [] })),
+        }
+    }
+
+    fn api(&self, state: &mut State, request: &Request, api_path: &str) -> ResponseTemplate {
+        let now = state.now;
+        let Some(id) = session_id(self.kind, &request.headers) else {
+            return error(StatusCode::UNAUTHORIZED, "api.err.LoginRequired");
+        };
+        let Some(session) = state.sessions.get_mut(&id) else {
+            return error(StatusCode::UNAUTHORIZED, "api.err.LoginRequired");
+        };
+        if session.expires_at.is_some_and(|at| now >= at) {
+            state.sessions.remove(&id);
+            return error(StatusCode::UNAUTHORIZED, "api.err.LoginRequired");
+        }
+
+        let mut rotated = None;
+        if self.kind == ControllerKind::Os {
+            let csrf = request
+                .headers
+                .get("x-csrf-token")
+                .and_then(|v| v.to_str().ok());
+            if csrf != Some(session.csrf_token.as_str()) {
+                return error(StatusCode::FORBIDDEN, "api.err.InvalidCsrfToken");
+            }
+            if std::mem::take(&mut state.rotate_csrf) {
+                session.csrf_token = format!("{}-rotated", session.csrf_token);
+                rotated = Some(session.csrf_token.clone());
+            }
+        }
+
+        let response = self.endpoint(state, request, api_path);
+        match rotated {
+            Some(token) => response.insert_header("x-updated-csrf-token", token),
+            None => response,
+        }
+    }
+
+    fn endpoint(&self, state: &mut State, request: &Request, api_path: &str) -> ResponseTemplate {
+        if api_path == "/api/self" {
+            return ok(json!([{ "name": state.username }]));
+        }
+
+        let segments: Vec<&str> = api_path.trim_start_matches('/').split('/').collect();
+        let body: Value = request.body_json().unwrap_or_default();
+        match segments.as_slice() {
+            ["api", "s", site, "cmd", "stamgr"] if request.method.as_str() == "POST" => {
+                let mac = body["mac"].as_str().unwrap_or_default().to_lowercase();
+                match body["cmd"].as_str() {
+                    Some("authorize-guest") if !mac.is_empty() => {
+                        let minutes = body["minutes"].as_i64().unwrap_or(DEFAULT_GUEST_MINUTES);
+                        let guest = Guest {
+                            id: format!("{:024x}", state.next_id()),
+                            mac,
+                            start: state.now,
+                            end: state.now + minutes * 60,
+                            unauthorized: false,
+                            up: body["up"].as_u64().map(|v| v as u32),
+                            down: body["down"].as_u64().map(|v| v as u32),
+                            bytes: body["bytes"].as_i64(),
+                        };
+                        let entry = guest.entry(site, state.now);
+                        state
+                            .guests
+                            .entry(site.to_string())
+                            .or_default()
+                            .push(guest);
+                        ok(json!([entry]))
+                    }
+                    Some("unauthorize-guest") if !mac.is_empty() => {
+                        let now = state.now;
+                        for guest in state.guests.entry(site.to_string()).or_default() {
+                            if guest.mac == mac && !guest.unauthorized && now < guest.end {
+                                guest.unauthorized = true;
+                                guest.end = now;
+                            }
+                        }
+                        ok(json!([]))
+                    }
+                    _ => error(StatusCode::BAD_REQUEST, "api.err.InvalidPayload"),
+                }
+            }
+            ["api", "s", site, "stat", "guest"] => {
+                let since = body["within"]
+                    .as_i64()
+                    .map(|hours| state.now - hours * 3600);
+                let guests: Vec<GuestEntry> = state
+                    .guests
+                    .get(*site)
+                    .into_iter()
+                    .flatten()
+                    .filter(|g| since.map_or(true, |since| g.start >= since))
+                    .map(|g| g.entry(site, state.now))
+                    .collect();
+                ok(json!(guests))
+            }
+            _ => error(StatusCode::NOT_FOUND, "api.err.NotFound"),
+        }
+    }
+}
+
+/// Extracts the session ID from the request's session cookie.
+fn session_id(kind: ControllerKind, headers: &HeaderMap) -> Option<String> {
+    let name = match kind {
+        ControllerKind::Os => "TOKEN",
+        ControllerKind::Network => "unifises",
+    };
+    headers
+        .get_all("cookie")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(n, _)| *n == name)
+        .map(|(_, value)| value.to_string())
+}
+
+fn ok(data: Value) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({ "meta": { "rc": "ok" }, "data": data }))
+}
+
+fn error(status: StatusCode, msg: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status.as_u16())
+        .set_body_json(json!({ "meta": { "rc": "error", "msg": msg }, "data": [] }))
+}
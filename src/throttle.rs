@@ -0,0 +1,224 @@
+//! Client-side rate limiting and concurrency control.
+//!
+//! A [`Throttle`] is shared by all clones of a [`UniFiClient`](crate::UniFiClient)
+//! so that fan-out from many tasks still respects a single budget per
+//! controller.
+
+use std::time::Duration;
+
+use tokio::sync::{Mutex, Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+
+use crate::{UniFiError, UniFiResult};
+
+/// A token-bucket rate limit for requests sent to the controller.
+///
+/// Up to `burst` requests may be sent back to back; after that, requests are
+/// spaced out to the configured rate.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use unifi_client::{RateLimit, UniFiClient, UniFiError};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), UniFiError> {
+/// let client = UniFiClient::builder()
+///     .controller_url("https://controller.example:8443")
+///     .username("admin")
+///     .password("secret")
+///     .rate_limit(RateLimit::per_second(10).burst(20))
+///     .max_concurrent_requests(4)
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    requests: u32,
+    per: Duration,
+    burst: u32,
+}
+
+impl RateLimit {
+    /// Allows `requests` requests per `per` interval, with a burst of
+    /// `requests`.
+    pub fn new(requests: u32, per: Duration) -> Self {
+        Self {
+            requests,
+            per,
+            burst: requests,
+        }
+    }
+
+    /// Allows `requests` requests per second, with a burst of `requests`.
+    pub fn per_second(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(1))
+    }
+
+    /// Sets how many requests may be sent back to back before the rate
+    /// applies.
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    fn validate(&self) -> UniFiResult<()> {
+        if self.requests == 0 || self.per.is_zero() || self.burst == 0 {
+            return Err(UniFiError::ConfigurationError(
+                "Rate limit requests, interval and burst must be greater than zero".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn tokens_per_sec(&self) -> f64 {
+        f64::from(self.requests) / self.per.as_secs_f64()
+    }
+}
+
+/// Time a request spent waiting for the client-side rate limiter and
+/// concurrency limit before it was sent.
+///
+/// Attached to every response returned by
+/// [`UniFiClient::request`](crate::UniFiClient::request) and readable through
+/// `response.extensions().get::<ThrottleDelay>()`. Includes the waits of all
+/// attempts when a request was retried.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ThrottleDelay(pub Duration);
+
+/// Shared rate limiter and in-flight cap.
+#[derive(Debug)]
+pub(crate) struct Throttle {
+    bucket: Option<Mutex<TokenBucket>>,
+    in_flight: Option<Semaphore>,
+}
+
+impl Throttle {
+    pub(crate) fn new(
+        rate_limit: Option<RateLimit>,
+        max_concurrent_requests: Option<usize>,
+    ) -> UniFiResult<Self> {
+        if let Some(ref rate_limit) = rate_limit {
+            rate_limit.validate()?;
+        }
+        if max_concurrent_requests == Some(0) {
+            return Err(UniFiError::ConfigurationError(
+                "Maximum concurrent requests must be greater than zero".into(),
+            ));
+        }
+
+        Ok(Self {
+            bucket: rate_limit.map(|r| Mutex::new(TokenBucket::new(r))),
+            in_flight: max_concurrent_requests.map(Semaphore::new),
+        })
+    }
+
+    /// A throttle that never waits.
+    pub(crate) fn unlimited() -> Self {
+        Self {
+            bucket: None,
+            in_flight: None,
+        }
+    }
+
+    /// Waits for a rate-limit token and an in-flight slot.
+    ///
+    /// Returns the permit, which must be held while the request is in flight,
+    /// and how long the caller waited.
+    pub(crate) async fn acquire(&self) -> (Option<SemaphorePermit<'_>>, Duration) {
+        let mut waited = Duration::ZERO;
+
+        if let Some(ref bucket) = self.bucket {
+            let wait = bucket.lock().await.reserve(Instant::now());
+            if !wait.is_zero() {
+                let start = Instant::now();
+                tokio::time::sleep(wait).await;
+                waited += start.elapsed();
+            }
+        }
+
+        let permit = match self.in_flight {
+            Some(ref semaphore) => match semaphore.try_acquire() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    let start = Instant::now();
+                    // The semaphore is never closed, so acquiring cannot fail.
+                    let permit = semaphore.acquire().await.ok();
+                    waited += start.elapsed();
+                    permit
+                }
+            },
+            None => None,
+        };
+
+        (permit, waited)
+    }
+}
+
+/// Token bucket that hands out reservations, so waiters are served in order
+/// without holding the lock while sleeping.
+#[derive(Debug)]
+struct TokenBucket {
+    tokens_per_sec: f64,
+    capacity: f64,
+    /// Available tokens; negative when future tokens are already reserved.
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate_limit: RateLimit) -> Self {
+        let capacity = f64::from(rate_limit.burst);
+        Self {
+            tokens_per_sec: rate_limit.tokens_per_sec(),
+            capacity,
+            tokens: capacity,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Takes one token and returns how long to wait until it is available.
+    fn reserve(&mut self, now: Instant) -> Duration {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.tokens_per_sec).min(self.capacity);
+        self.last_refill = now;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.tokens_per_sec)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_allows_burst_then_spaces_requests() {
+        let mut bucket = TokenBucket::new(RateLimit::per_second(10).burst(2));
+        let now = bucket.last_refill;
+
+        assert_eq!(bucket.reserve(now), Duration::ZERO);
+        assert_eq!(bucket.reserve(now), Duration::ZERO);
+        assert_eq!(bucket.reserve(now), Duration::from_millis(100));
+        assert_eq!(bucket.reserve(now), Duration::from_millis(200));
+
+        // After a second the reservations are paid back and the burst is refilled.
+        let later = now + Duration::from_secs(1);
+        assert_eq!(bucket.reserve(later), Duration::ZERO);
+    }
+
+    #[test]
+    fn rejects_zero_limits() {
+        assert!(Throttle::new(Some(RateLimit::per_second(0)), None).is_err());
+        assert!(Throttle::new(Some(RateLimit::per_second(1).burst(0)), None).is_err());
+        assert!(Throttle::new(None, Some(0)).is_err());
+    }
+}
@@ -0,0 +1,40 @@
+//! TLS trust configuration: custom root certificates and certificate pinning.
+//!
+//! Custom roots are handed to the default TLS backend. Pinning needs to
+//! inspect the certificate during the handshake, before any request (and any
+//! credential) is sent, so pinned clients use rustls with a verifier that
+//! accepts exactly the pinned leaf certificate. Pinning requires the
+//! `tls-pinning` feature.
+
+use reqwest::ClientBuilder;
+
+use crate::{UniFiError, UniFiResult};
+
+#[cfg(feature = "tls-pinning")]
+mod pin;
+
+#[cfg(feature = "tls-pinning")]
+pub(crate) use self::pin::{configure_pin, CertificatePin};
+
+/// A trusted root certificate supplied by the caller.
+#[derive(Clone)]
+pub(crate) enum RootCertificate {
+    Pem(Vec<u8>),
+    Der(Vec<u8>),
+}
+
+/// Adds custom root certificates to a reqwest client builder.
+pub(crate) fn configure(
+    mut builder: ClientBuilder,
+    root_certificates: &[RootCertificate],
+) -> UniFiResult<ClientBuilder> {
+    for root in root_certificates {
+        let certificate = match root {
+            RootCertificate::Pem(pem) => reqwest::Certificate::from_pem(pem),
+            RootCertificate::Der(der) => reqwest::Certificate::from_der(der),
+        }
+        .map_err(|e| UniFiError::ConfigurationError(format!("Invalid root certificate: {e}")))?;
+        builder = builder.add_root_certificate(certificate);
+    }
+    Ok(builder)
+}
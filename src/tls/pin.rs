@@ -0,0 +1,208 @@
+//! Certificate pinning with a rustls verifier that accepts exactly the pinned
+//! leaf certificate.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use reqwest::ClientBuilder;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{CertificateError, DigitallySignedStruct, SignatureScheme};
+use sha2::{Digest, Sha256};
+
+use crate::{UniFiError, UniFiResult};
+
+/// The SHA-256 fingerprint the controller's leaf certificate must match.
+///
+/// With trust-on-first-use and no configured fingerprint, the first
+/// certificate presented is pinned for the lifetime of the client.
+pub(crate) struct CertificatePin {
+    fingerprint: Mutex<Option<[u8; 32]>>,
+    trust_on_first_use: bool,
+}
+
+impl CertificatePin {
+    pub(crate) fn new(fingerprint: Option<&str>, trust_on_first_use: bool) -> UniFiResult<Self> {
+        Ok(Self {
+            fingerprint: Mutex::new(fingerprint.map(parse_fingerprint).transpose()?),
+            trust_on_first_use,
+        })
+    }
+
+    /// Returns the pinned fingerprint as colon-separated uppercase hex, if known.
+    pub(crate) fn fingerprint(&self) -> Option<String> {
+        self.lock().as_ref().map(format_fingerprint)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<[u8; 32]>> {
+        // The pin is plain data; a panic elsewhere cannot leave it inconsistent.
+        self.fingerprint
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn verify(&self, certificate: &[u8]) -> Result<(), rustls::Error> {
+        let actual: [u8; 32] = Sha256::digest(certificate).into();
+        let mut pinned = self.lock();
+        match *pinned {
+            Some(expected) if expected == actual => Ok(()),
+            Some(_) => Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            )),
+            None if self.trust_on_first_use => {
+                *pinned = Some(actual);
+                Ok(())
+            }
+            None => Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            )),
+        }
+    }
+}
+
+impl fmt::Debug for CertificatePin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CertificatePin")
+            .field("sha256", &self.fingerprint())
+            .field("trust_on_first_use", &self.trust_on_first_use)
+            .finish()
+    }
+}
+
+/// Makes a reqwest client builder accept only the pinned certificate.
+pub(crate) fn configure_pin(
+    builder: ClientBuilder,
+    pin: &Arc<CertificatePin>,
+) -> UniFiResult<ClientBuilder> {
+    Ok(builder.use_preconfigured_tls(pinned_tls_config(Arc::clone(pin))?))
+}
+
+fn pinned_tls_config(pin: Arc<CertificatePin>) -> UniFiResult<rustls::ClientConfig> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier = PinnedCertVerifier {
+        pin,
+        provider: Arc::clone(&provider),
+    };
+
+    Ok(rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| UniFiError::ConfigurationError(format!("TLS configuration error: {e}")))?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth())
+}
+
+/// Accepts exactly the pinned leaf certificate, and still checks that the
+/// server proves possession of its private key.
+#[derive(Debug)]
+struct PinnedCertVerifier {
+    pin: Arc<CertificatePin>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        self.pin.verify(end_entity.as_ref())?;
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+// Accepts 64 hex digits, optionally colon-separated as printed by `openssl x509 -fingerprint`.
+fn parse_fingerprint(fingerprint: &str) -> UniFiResult<[u8; 32]> {
+    let hex: String = fingerprint
+        .chars()
+        .filter(|c| *c != ':' && !c.is_whitespace())
+        .collect();
+    let invalid = || {
+        UniFiError::ConfigurationError(format!(
+            "Invalid SHA-256 certificate fingerprint: {fingerprint}"
+        ))
+    };
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).map_err(|_| invalid())?;
+    }
+    Ok(bytes)
+}
+
+fn format_fingerprint(fingerprint: &[u8; 32]) -> String {
+    fingerprint
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FINGERPRINT: &str =
+        "D8:F5:E6:D1:CE:F0:2D:30:C2:43:6F:D8:E4:73:5A:D0:27:4B:C0:27:71:79:D5:0D:7F:05:EE:0E:36:74:2A:D2";
+
+    #[test]
+    fn fingerprints_round_trip_with_or_without_colons() {
+        let bytes = parse_fingerprint(FINGERPRINT).unwrap();
+        assert_eq!(format_fingerprint(&bytes), FINGERPRINT);
+        assert_eq!(
+            parse_fingerprint(&FINGERPRINT.replace(':', "").to_lowercase()).unwrap(),
+            bytes
+        );
+        assert!(parse_fingerprint("D8:F5").is_err());
+        assert!(parse_fingerprint(&"zz".repeat(32)).is_err());
+    }
+
+    #[test]
+    fn trust_on_first_use_pins_first_certificate() {
+        let pin = CertificatePin::new(None, true).unwrap();
+        assert!(pin.verify(b"first").is_ok());
+        assert!(pin.verify(b"first").is_ok());
+        assert!(pin.verify(b"second").is_err());
+    }
+}
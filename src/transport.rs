@@ -0,0 +1,433 @@
+//! The HTTP layer underneath [`UniFiClient`](crate::UniFiClient).
+//!
+//! The client builds a [`TransportRequest`] for every request, login and
+//! detection probe and hands it to a [`Transport`], which returns a buffered
+//! [`Response`]. Authentication, CSRF rotation, retries, throttling and
+//! middlewares all run on top of the transport, so plugging in a different
+//! HTTP stack, an in-process fake or a recording transport keeps that
+//! behavior intact.
+//!
+//! Downloads go through [`Transport::download`] instead, which writes a
+//! successful response body to an [`AsyncWrite`] as it arrives.
+//!
+//! [`ReqwestTransport`] is the default. Install another one with
+//! [`UniFiClientBuilder::transport`](crate::UniFiClientBuilder::transport).
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::header::CONTENT_TYPE;
+use http::{Extensions, HeaderMap, HeaderValue, Method, StatusCode};
+use reqwest::cookie::{CookieStore, Jar};
+use serde::de::DeserializeOwned;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use url::Url;
+
+use crate::{UniFiError, UniFiResult};
+
+/// A request handed to a [`Transport`].
+#[derive(Debug, Clone)]
+pub struct TransportRequest {
+    method: Method,
+    url: Url,
+    headers: HeaderMap,
+    body: Option<Bytes>,
+}
+
+impl TransportRequest {
+    /// Creates a request without headers or body.
+    pub fn new(method: Method, url: Url) -> Self {
+        Self {
+            method,
+            url,
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+
+    /// Sets a JSON body and the matching `Content-Type` header.
+    pub(crate) fn json(mut self, body: &impl serde::Serialize) -> UniFiResult<Self> {
+        self.body = Some(serde_json::to_vec(body)?.into());
+        self.headers
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        Ok(self)
+    }
+
+    /// Sets an encoded body and its `Content-Type` header.
+    pub(crate) fn encoded_body(mut self, content_type: HeaderValue, body: Bytes) -> Self {
+        self.body = Some(body);
+        self.headers.insert(CONTENT_TYPE, content_type);
+        self
+    }
+
+    /// Sets a header, replacing any previous value.
+    pub(crate) fn header(mut self, name: &'static str, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Adds `headers`, replacing all values of headers that are already set.
+    pub(crate) fn override_headers(mut self, headers: &HeaderMap) -> Self {
+        for name in headers.keys() {
+            self.headers.remove(name);
+        }
+        for (name, value) in headers {
+            self.headers.append(name.clone(), value.clone());
+        }
+        self
+    }
+
+    /// Returns the HTTP method.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Returns the full request URL.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the request headers.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the request headers for modification.
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        &mut self.headers
+    }
+
+    /// Returns the body, if any.
+    pub fn body(&self) -> Option<&[u8]> {
+        self.body.as_deref()
+    }
+
+    /// Splits the request into method, URL, headers and body.
+    pub fn into_parts(self) -> (Method, Url, HeaderMap, Option<Bytes>) {
+        (self.method, self.url, self.headers, self.body)
+    }
+}
+
+/// A controller response with its body fully read.
+///
+/// Returned by [`UniFiClient::request`](crate::UniFiClient::request) and by
+/// [`Transport`] implementations. Extensions carry per-request data such as
+/// [`ThrottleDelay`](crate::ThrottleDelay) and
+/// [`RequestTiming`](crate::RequestTiming).
+#[derive(Debug)]
+pub struct Response {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+    extensions: Extensions,
+}
+
+impl Response {
+    /// Creates a response.
+    pub fn new(status: StatusCode, headers: HeaderMap, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers,
+            body: body.into(),
+            extensions: Extensions::new(),
+        }
+    }
+
+    /// Returns the HTTP status.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the response headers.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the raw body.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Consumes the response and returns the raw body.
+    pub fn into_body(self) -> Vec<u8> {
+        self.body
+    }
+
+    /// Returns the body as text, replacing invalid UTF-8.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Parses the body as JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> UniFiResult<T> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    /// Returns per-response data attached by the client and middlewares.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    /// Returns per-response data for modification.
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
+}
+
+impl<T: Into<Vec<u8>>> From<http::Response<T>> for Response {
+    fn from(response: http::Response<T>) -> Self {
+        let (parts, body) = response.into_parts();
+        Self {
+            status: parts.status,
+            headers: parts.headers,
+            body: body.into(),
+            extensions: parts.extensions,
+        }
+    }
+}
+
+/// Sends HTTP requests for a [`UniFiClient`](crate::UniFiClient).
+///
+/// Implementations must keep cookies set by the controller and send them back
+/// on later requests, as UniFi sessions are cookie based. The cookie accessors
+/// are only used to export and restore sessions; transports without access to
+/// their cookies keep the default implementations, which fail with a
+/// configuration error.
+///
+/// Transport failures should be reported as [`UniFiError::TransportError`] so
+/// that the client's [`RetryPolicy`](crate::RetryPolicy) can tell connection
+/// failures and timeouts apart.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use async_trait::async_trait;
+/// # use unifi_client::{ReqwestTransport, Response, Transport, TransportRequest, UniFiResult};
+/// /// Logs every request before delegating to reqwest.
+/// #[derive(Debug)]
+/// struct Audited(ReqwestTransport);
+///
+/// #[async_trait]
+/// impl Transport for Audited {
+///     async fn send(&self, request: TransportRequest) -> UniFiResult<Response> {
+///         println!("{} {}", request.method(), request.url());
+///         self.0.send(request).await
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait Transport: Send + Sync + fmt::Debug {
+    /// Sends a request and reads the whole response.
+    async fn send(&self, request: TransportRequest) -> UniFiResult<Response>;
+
+    /// Sends a request and writes a successful (2xx) response body to
+    /// `writer`.
+    ///
+    /// The returned response carries the status and headers; its body is
+    /// empty when it was written to `writer`. Other responses are returned
+    /// with their body buffered and nothing is written, so the client can
+    /// retry or re-authenticate. The client retries connection failures and
+    /// timeouts, so failures after writing has started should be reported
+    /// with [`TransportErrorKind::Other`].
+    ///
+    /// The default implementation buffers the body with
+    /// [`send`](Self::send) before writing it.
+    async fn download(
+        &self,
+        request: TransportRequest,
+        writer: &mut (dyn AsyncWrite + Send + Unpin),
+    ) -> UniFiResult<Response> {
+        let response = self.send(request).await?;
+        if !response.status().is_success() {
+            return Ok(response);
+        }
+        writer.write_all(response.body()).await?;
+        writer.flush().await?;
+        Ok(Response::new(
+            response.status(),
+            response.headers().clone(),
+            Vec::new(),
+        ))
+    }
+
+    /// Returns the `Cookie` header the transport would send to `url`.
+    fn cookies(&self, _url: &Url) -> UniFiResult<Option<HeaderValue>> {
+        Err(UniFiError::ConfigurationError(
+            "Transport does not expose its cookies".into(),
+        ))
+    }
+
+    /// Stores a cookie (`name=value`) as if `url` had set it.
+    fn add_cookie(&self, _cookie: &str, _url: &Url) -> UniFiResult<()> {
+        Err(UniFiError::ConfigurationError(
+            "Transport does not expose its cookies".into(),
+        ))
+    }
+}
+
+/// Lets callers keep a handle on a transport they hand to the client.
+#[async_trait]
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    async fn send(&self, request: TransportRequest) -> UniFiResult<Response> {
+        (**self).send(request).await
+    }
+
+    async fn download(
+        &self,
+        request: TransportRequest,
+        writer: &mut (dyn AsyncWrite + Send + Unpin),
+    ) -> UniFiResult<Response> {
+        (**self).download(request, writer).await
+    }
+
+    fn cookies(&self, url: &Url) -> UniFiResult<Option<HeaderValue>> {
+        (**self).cookies(url)
+    }
+
+    fn add_cookie(&self, cookie: &str, url: &Url) -> UniFiResult<()> {
+        (**self).add_cookie(cookie, url)
+    }
+}
+
+/// The default [`Transport`], backed by a [`reqwest::Client`].
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+    /// The cookie store backing `client`; `None` when a custom client was supplied.
+    cookie_jar: Option<Arc<Jar>>,
+}
+
+impl ReqwestTransport {
+    /// Wraps a reqwest client.
+    ///
+    /// The client should have a cookie store enabled. Its cookies are not
+    /// accessible, so sessions cannot be exported or restored.
+    pub fn new(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            cookie_jar: None,
+        }
+    }
+
+    /// Wraps a reqwest client whose cookie provider is `cookie_jar`.
+    pub(crate) fn with_cookie_jar(client: reqwest::Client, cookie_jar: Arc<Jar>) -> Self {
+        Self {
+            client,
+            cookie_jar: Some(cookie_jar),
+        }
+    }
+
+    fn build(&self, request: TransportRequest) -> reqwest::RequestBuilder {
+        let (method, url, headers, body) = request.into_parts();
+        let request = self.client.request(method, url).headers(headers);
+        match body {
+            Some(body) => request.body(body),
+            None => request,
+        }
+    }
+
+    fn cookie_jar(&self) -> UniFiResult<&Jar> {
+        self.cookie_jar.as_deref().ok_or_else(|| {
+            UniFiError::ConfigurationError(
+                "Exporting or restoring a session requires the built-in cookie store".into(),
+            )
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for ReqwestTransport {
+    async fn send(&self, request: TransportRequest) -> UniFiResult<Response> {
+        let response = self.build(request).send().await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+        Ok(Response::new(status, headers, body))
+    }
+
+    async fn download(
+        &self,
+        request: TransportRequest,
+        writer: &mut (dyn AsyncWrite + Send + Unpin),
+    ) -> UniFiResult<Response> {
+        let mut response = self.build(request).send().await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        if !status.is_success() {
+            let body = response.bytes().await?;
+            return Ok(Response::new(status, headers, body));
+        }
+
+        // Part of the body may already be written, so a failed read must not be retried.
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| TransportError::new(TransportErrorKind::Other, e))?
+        {
+            writer.write_all(&chunk).await?;
+        }
+        writer.flush().await?;
+        Ok(Response::new(status, headers, Vec::new()))
+    }
+
+    fn cookies(&self, url: &Url) -> UniFiResult<Option<HeaderValue>> {
+        Ok(self.cookie_jar()?.cookies(url))
+    }
+
+    fn add_cookie(&self, cookie: &str, url: &Url) -> UniFiResult<()> {
+        self.cookie_jar()?.add_cookie_str(cookie, url);
+        Ok(())
+    }
+}
+
+/// What went wrong in a [`TransportError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TransportErrorKind {
+    /// No connection could be established; nothing reached the controller.
+    Connect,
+    /// The request timed out and may have reached the controller.
+    Timeout,
+    /// Any other failure.
+    Other,
+}
+
+/// A failure reported by a custom [`Transport`].
+#[derive(Debug)]
+pub struct TransportError {
+    kind: TransportErrorKind,
+    source: Box<dyn StdError + Send + Sync>,
+}
+
+impl TransportError {
+    /// Creates an error of the given kind.
+    pub fn new(
+        kind: TransportErrorKind,
+        source: impl Into<Box<dyn StdError + Send + Sync>>,
+    ) -> Self {
+        Self {
+            kind,
+            source: source.into(),
+        }
+    }
+
+    /// Returns what went wrong.
+    pub fn kind(&self) -> TransportErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.source.fmt(f)
+    }
+}
+
+impl StdError for TransportError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
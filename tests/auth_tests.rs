@@ -325,3 +325,362 @@ async fn test_csrf_cleared_after_relogin_without_csrf_header() -> Result<(), Uni
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_api_key_authentication_skips_login() -> Result<(), UniFiError> {
+    // What it tests: A client configured with an API key never calls the login endpoint and sends
+    // the key as `x-api-key` on every request, without any CSRF header.
+    //
+    // Why it's valuable: API keys are sessionless; accidentally running the login flow or leaking
+    // session material alongside the key would break UniFi OS consoles that only issue API keys.
+    let mock_server = MockServer::start().await;
+    setup_probe(&mock_server, TestControllerKind::Os).await;
+
+    Mock::given(method("POST"))
+        .and(path(TestControllerKind::Os.login_path()))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let endpoint = api_path(TestControllerKind::Os, "/api/self");
+    Mock::given(method("GET"))
+        .and(path(endpoint.as_str()))
+        .and(header("x-api-key", "test-api-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "meta": { "rc": "ok" },
+            "data": []
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // Poison pill: a CSRF header must never be sent with API key authentication.
+    Mock::given(method("GET"))
+        .and(path(endpoint.as_str()))
+        .and(header_exists("x-csrf-token"))
+        .respond_with(ResponseTemplate::new(418))
+        .mount(&mock_server)
+        .await;
+
+    let client = UniFiClient::builder()
+        .controller_url(mock_server.uri())
+        .api_key("test-api-key")
+        .build()
+        .await?;
+
+    let result = client
+        .request_json(Method::GET, "/api/self", None::<()>)
+        .await?;
+    assert!(result.is_array());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_api_key_rejected_returns_authentication_error() -> Result<(), UniFiError> {
+    // What it tests: When the controller rejects an API key (401), the client surfaces an
+    // AuthenticationError immediately instead of attempting to log in and retry.
+    //
+    // Why it's valuable: There is no session to refresh for API keys, so a re-login attempt would
+    // fail confusingly (no credentials) and mask the real problem: a revoked or invalid key.
+    let mock_server = MockServer::start().await;
+    setup_probe(&mock_server, TestControllerKind::Os).await;
+
+    Mock::given(method("POST"))
+        .and(path(TestControllerKind::Os.login_path()))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let endpoint = api_path(TestControllerKind::Os, "/api/self");
+    Mock::given(method("GET"))
+        .and(path(endpoint.as_str()))
+        .respond_with(ResponseTemplate::new(401))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = UniFiClient::builder()
+        .controller_url(mock_server.uri())
+        .api_key("revoked-api-key")
+        .build()
+        .await?;
+
+    match client.request(Method::GET, "/api/self", None::<()>).await {
+        Err(UniFiError::AuthenticationError(msg)) => {
+            assert_eq!(msg, "API key rejected with status code: 401 Unauthorized");
+        }
+        other => panic!("Expected AuthenticationError, got {other:?}"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mfa_challenge_answered_on_login_and_relogin() -> Result<(), UniFiError> {
+    // What it tests: On UniFi OS, a 499 `MFA_AUTH_REQUIRED` login response makes the client ask the
+    // MFA code provider for a code and repeat the login with `token`. A later 401 triggers a
+    // re-login that consults the provider again for a fresh code.
+    //
+    // Why it's valuable: Accounts with enforced 2FA must keep working past the first session;
+    // reusing a stale one-time code on re-login would lock long-running services out.
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let mock_server = MockServer::start().await;
+    setup_probe(&mock_server, TestControllerKind::Os).await;
+    let login_path = TestControllerKind::Os.login_path();
+
+    Mock::given(method("POST"))
+        .and(path(login_path))
+        .and(body_json(json!({
+            "username": "test-user",
+            "password": "test-password"
+        })))
+        .respond_with(ResponseTemplate::new(499).set_body_json(json!({
+            "code": "MFA_AUTH_REQUIRED",
+            "message": "MFA required"
+        })))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    for code in ["000001", "000002"] {
+        Mock::given(method("POST"))
+            .and(path(login_path))
+            .and(body_json(json!({
+                "username": "test-user",
+                "password": "test-password",
+                "token": code
+            })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("set-cookie", "TOKEN=test-token; path=/")
+                    .insert_header("x-csrf-token", "test-csrf"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+    }
+
+    let endpoint = api_path(TestControllerKind::Os, "/api/self");
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls_clone = Arc::clone(&calls);
+    Mock::given(method("GET"))
+        .and(path(endpoint.as_str()))
+        .respond_with(move |_: &wiremock::Request| {
+            if calls_clone.fetch_add(1, Ordering::SeqCst) == 0 {
+                ResponseTemplate::new(401)
+            } else {
+                ResponseTemplate::new(200).set_body_json(json!({
+                    "meta": { "rc": "ok" },
+                    "data": []
+                }))
+            }
+        })
+        .mount(&mock_server)
+        .await;
+
+    let codes_issued = Arc::new(AtomicUsize::new(0));
+    let codes_issued_clone = Arc::clone(&codes_issued);
+    let client = UniFiClient::builder()
+        .controller_url(mock_server.uri())
+        .username("test-user")
+        .password("test-password")
+        .mfa_code_provider(move || {
+            let n = codes_issued_clone.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(format!("{n:06}"))
+        })
+        .build()
+        .await?;
+    assert_eq!(codes_issued.load(Ordering::SeqCst), 1);
+
+    client
+        .request_json(Method::GET, "/api/self", None::<()>)
+        .await?;
+    assert_eq!(codes_issued.load(Ordering::SeqCst), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mfa_challenge_without_provider() {
+    // What it tests: On a classic Network controller, the `api.err.Ubic2faTokenRequired` login
+    // response surfaces as `UniFiError::MfaRequired` when no MFA code provider is configured.
+    //
+    // Why it's valuable: Callers can distinguish "2FA is enforced" from bad credentials and
+    // prompt for a code instead of reporting a generic authentication failure.
+    let mock_server = MockServer::start().await;
+    setup_probe(&mock_server, TestControllerKind::Network).await;
+
+    Mock::given(method("POST"))
+        .and(path(TestControllerKind::Network.login_path()))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "meta": { "rc": "error", "msg": "api.err.Ubic2faTokenRequired" },
+            "data": []
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let result = UniFiClient::builder()
+        .controller_url(mock_server.uri())
+        .username("test-user")
+        .password("test-password")
+        .build()
+        .await;
+
+    match result {
+        Err(UniFiError::MfaRequired) => {}
+        other => panic!("Expected MfaRequired, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_password_provider_consulted_on_relogin() -> Result<(), UniFiError> {
+    // What it tests: A `FileCredentialProvider` is read on the initial login and again on the
+    // re-login triggered by a 401, so a password rotated in the file between the two is used.
+    //
+    // Why it's valuable: Secret managers rotate mounted passwords; a client that cached the first
+    // password would be locked out of long-running services after rotation.
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use unifi_client::FileCredentialProvider;
+
+    let mock_server = MockServer::start().await;
+    let kind = TestControllerKind::Network;
+    setup_probe_and_login(&mock_server, kind).await;
+    Mock::given(method("POST"))
+        .and(path(kind.login_path()))
+        .and(body_json(json!({
+            "username": "test-user",
+            "password": "rotated-password"
+        })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "meta": { "rc": "ok" }, "data": [] }))
+                .insert_header("set-cookie", "unifises=test-cookie"),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls_clone = Arc::clone(&calls);
+    Mock::given(method("GET"))
+        .and(path("/api/self"))
+        .respond_with(move |_: &wiremock::Request| {
+            if calls_clone.fetch_add(1, Ordering::SeqCst) == 0 {
+                ResponseTemplate::new(401)
+            } else {
+                ResponseTemplate::new(200).set_body_json(json!({
+                    "meta": { "rc": "ok" },
+                    "data": []
+                }))
+            }
+        })
+        .mount(&mock_server)
+        .await;
+
+    let password_file =
+        std::env::temp_dir().join(format!("unifi-client-password-{}", std::process::id()));
+    std::fs::write(&password_file, "test-password\n").unwrap();
+    let client = UniFiClient::builder()
+        .controller_url(mock_server.uri())
+        .username("test-user")
+        .password_provider(FileCredentialProvider::new(&password_file))
+        .build()
+        .await?;
+
+    std::fs::write(&password_file, "rotated-password\n").unwrap();
+    client
+        .request_json(Method::GET, "/api/self", None::<()>)
+        .await?;
+    std::fs::remove_file(&password_file).ok();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_password_provider_errors() {
+    // What it tests: Provider failures surface as configuration errors from `build()`, and a
+    // fixed password cannot be combined with a provider.
+    //
+    // Why it's valuable: A missing secret should be reported as such rather than as a rejected
+    // login, and ambiguous configurations should fail fast.
+    use unifi_client::{CommandCredentialProvider, EnvCredentialProvider};
+
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Network).await;
+    let builder = || {
+        UniFiClient::builder()
+            .controller_url(mock_server.uri())
+            .username("test-user")
+    };
+
+    let missing_env = builder()
+        .password_provider(EnvCredentialProvider::new(
+            "UNIFI_CLIENT_TEST_UNSET_PASSWORD",
+        ))
+        .build()
+        .await;
+    assert!(matches!(
+        missing_env,
+        Err(UniFiError::ConfigurationError(_))
+    ));
+
+    let failing_command = builder()
+        .password_provider(CommandCredentialProvider::new("false"))
+        .build()
+        .await;
+    assert!(matches!(
+        failing_command,
+        Err(UniFiError::ConfigurationError(_))
+    ));
+
+    let command = builder()
+        .password_provider(CommandCredentialProvider::new("echo").arg("test-password"))
+        .build()
+        .await;
+    assert!(command.is_ok(), "{command:?}");
+
+    let both = builder()
+        .password("test-password")
+        .password_provider(EnvCredentialProvider::new(
+            "UNIFI_CLIENT_TEST_UNSET_PASSWORD",
+        ))
+        .build()
+        .await;
+    assert!(matches!(both, Err(UniFiError::ConfigurationError(_))));
+}
+
+#[tokio::test]
+async fn test_password_command_timeout() {
+    // What it tests: A password command that outlives its timeout fails the build with a
+    // configuration error instead of waiting for the command to exit.
+    //
+    // Why it's valuable: A hung secret manager CLI (e.g. waiting for an unlock prompt) must not
+    // stall every login forever.
+    use std::time::{Duration, Instant};
+    use unifi_client::CommandCredentialProvider;
+
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Network).await;
+    let started = Instant::now();
+    let result = UniFiClient::builder()
+        .controller_url(mock_server.uri())
+        .username("test-user")
+        .password_provider(
+            CommandCredentialProvider::new("sleep")
+                .arg("10")
+                .timeout(Duration::from_millis(100)),
+        )
+        .build()
+        .await;
+    assert!(
+        matches!(&result, Err(UniFiError::ConfigurationError(msg)) if msg.contains("timed out")),
+        "{result:?}"
+    );
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
@@ -0,0 +1,233 @@
+#![cfg(feature = "blocking")]
+
+use http::Method;
+use serde_json::json;
+use tokio::runtime::Runtime;
+use wiremock::matchers::{body_json, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use common::{add_auth_headers, api_path, setup_probe_and_login, TestControllerKind};
+use unifi_client::blocking::UniFiClient;
+use unifi_client::UniFiError;
+
+/// Starts a mock controller on a runtime of its own, so the test thread stays synchronous.
+fn start_controller(kind: TestControllerKind) -> (Runtime, MockServer) {
+    let runtime = Runtime::new().unwrap();
+    let server = runtime.block_on(async {
+        let server = MockServer::start().await;
+        setup_probe_and_login(&server, kind).await;
+        server
+    });
+    (runtime, server)
+}
+
+fn build_client(uri: &str) -> UniFiClient {
+    UniFiClient::builder()
+        .username("test-user")
+        .password("test-password")
+        .controller_url(uri)
+        .build()
+        .expect("Failed to build blocking UniFiClient")
+}
+
+#[test]
+fn test_blocking_guest_lifecycle() {
+    // What it tests: The blocking client logs in, authorizes, lists and unauthorizes guests without
+    // the caller running a Tokio runtime.
+    //
+    // Why it's valuable: Synchronous tools rely on the blocking facade behaving like the async API.
+    for &flavor in &[TestControllerKind::Network, TestControllerKind::Os] {
+        let (runtime, server) = start_controller(flavor);
+        let guest = json!({
+            "_id": "guest1",
+            "mac": "00:11:22:33:44:55",
+            "authorized_by": "api",
+            "start": 1622548800,
+            "end": 1622550600,
+            "expired": false,
+            "site_id": "default"
+        });
+        runtime.block_on(async {
+            let stamgr = api_path(flavor, "/api/s/default/cmd/stamgr");
+            add_auth_headers(
+                Mock::given(method("POST")).and(path(stamgr.as_str())),
+                flavor,
+            )
+            .and(body_json(json!({
+                "cmd": "authorize-guest",
+                "mac": "00:11:22:33:44:55",
+                "minutes": 30,
+                "ap_mac": "00:00:00:00:00:00",
+            })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "meta": { "rc": "ok" }, "data": [guest] })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+            add_auth_headers(
+                Mock::given(method("POST")).and(path(stamgr.as_str())),
+                flavor,
+            )
+            .and(body_json(json!({
+                "cmd": "unauthorize-guest",
+                "mac": "00:11:22:33:44:55",
+            })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "meta": { "rc": "ok" }, "data": [] })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+            let list = api_path(flavor, "/api/s/default/stat/guest");
+            add_auth_headers(Mock::given(method("GET")).and(path(list.as_str())), flavor)
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(json!({ "meta": { "rc": "ok" }, "data": [guest] })),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+        });
+
+        let client = build_client(&server.uri());
+        let guests = client.guests();
+
+        let authorized = guests
+            .authorize("00:11:22:33:44:55")
+            .duration_minutes(30)
+            .send()
+            .unwrap();
+        assert_eq!(authorized.mac(), "00:11:22:33:44:55");
+
+        let listed = guests.list().send().unwrap();
+        assert_eq!(listed.len(), 1);
+
+        guests.unauthorize("00:11:22:33:44:55").send().unwrap();
+
+        drop(client);
+        runtime.block_on(server.verify());
+    }
+}
+
+#[test]
+fn test_blocking_errors_keep_async_types() {
+    // What it tests: Failures surface as the same `UniFiError` variants as in the async API.
+    //
+    // Why it's valuable: Callers share error handling between blocking and async code.
+    let (runtime, server) = start_controller(TestControllerKind::Network);
+    runtime.block_on(
+        Mock::given(method("GET"))
+            .and(path("/api/s/default/stat/guest"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "meta": { "rc": "error", "msg": "api.err.Invalid" },
+                "data": []
+            })))
+            .mount(&server),
+    );
+
+    let client = build_client(&server.uri());
+    match client.guests().list().send() {
+        Err(UniFiError::ControllerError(e)) => assert_eq!(e.status.as_u16(), 400),
+        other => panic!("expected ControllerError, got {other:?}"),
+    }
+
+    let missing = UniFiClient::builder().username("test-user").build();
+    assert!(matches!(missing, Err(UniFiError::ConfigurationError(_))));
+}
+
+#[test]
+fn test_blocking_raw_requests_downloads_and_integration() {
+    // What it tests: The blocking client sends raw and query requests, streams a download into a
+    // `std::io::Write`, and pages through the Integration API.
+    //
+    // Why it's valuable: Synchronous tools need the same escape hatches as async callers, not
+    // just the typed guest API.
+    let kind = TestControllerKind::Os;
+    let (runtime, server) = start_controller(kind);
+    runtime.block_on(async {
+        let sysinfo = api_path(kind, "/api/s/default/stat/sysinfo");
+        add_auth_headers(Mock::given(method("GET")).and(path(sysinfo.as_str())), kind)
+            .and(query_param("limit", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("sysinfo"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let backup = api_path(kind, "/dl/backup/backup.unf");
+        add_auth_headers(Mock::given(method("GET")).and(path(backup.as_str())), kind)
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![9u8; 64 * 1024]))
+            .expect(1)
+            .mount(&server)
+            .await;
+        add_auth_headers(
+            Mock::given(method("GET")).and(path("/proxy/network/integration/v1/sites")),
+            kind,
+        )
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "offset": 0,
+            "limit": 25,
+            "count": 1,
+            "totalCount": 1,
+            "data": [{ "id": "site-1", "internalReference": "default", "name": "Default" }]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    });
+
+    let client = build_client(&server.uri());
+    let response = client
+        .request_with_query(
+            Method::GET,
+            "/api/s/default/stat/sysinfo",
+            &[("limit", "1")],
+            None::<()>,
+        )
+        .unwrap();
+    assert_eq!(response.text(), "sysinfo");
+
+    let mut backup = Vec::new();
+    let response = client
+        .download("/dl/backup/backup.unf", &mut backup)
+        .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(backup, vec![9u8; 64 * 1024]);
+
+    let sites = client.integration().sites().send_all().unwrap();
+    assert_eq!(sites[0].internal_reference, "default");
+
+    drop(client);
+    runtime.block_on(server.verify());
+}
+
+#[cfg(feature = "cassettes")]
+#[test]
+fn test_blocking_builder_records_cassette() {
+    // What it tests: `record_cassette` on the blocking builder records the session, and the
+    // cassette is written when the client is dropped.
+    //
+    // Why it's valuable: Synchronous tools must be able to capture fixtures like async ones.
+    let (_runtime, server) = start_controller(TestControllerKind::Network);
+    let cassette =
+        std::env::temp_dir().join(format!("unifi-client-{}-blocking.json", std::process::id()));
+
+    let client = UniFiClient::builder()
+        .username("test-user")
+        .password("test-password")
+        .controller_url(server.uri())
+        .record_cassette(&cassette)
+        .build()
+        .expect("Failed to build blocking UniFiClient");
+    drop(client);
+
+    let recorded = unifi_client::cassette::Cassette::load(&cassette).unwrap();
+    assert!(recorded
+        .interactions
+        .iter()
+        .any(|i| i.request.path == "/api/login"));
+    std::fs::remove_file(cassette).unwrap();
+}
@@ -0,0 +1,216 @@
+use http::Method;
+use wiremock::matchers::{header, header_regex, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use common::{add_auth_headers, api_path, setup_probe_and_login, TestControllerKind};
+use unifi_client::{Multipart, RequestBody, UniFiClient, UniFiError};
+
+async fn login(server: &MockServer, kind: TestControllerKind) -> Result<UniFiClient, UniFiError> {
+    setup_probe_and_login(server, kind).await;
+    UniFiClient::builder()
+        .controller_url(server.uri())
+        .username("test-user")
+        .password("test-password")
+        .build()
+        .await
+}
+
+fn login_count(requests: &[wiremock::Request], kind: TestControllerKind) -> usize {
+    requests
+        .iter()
+        .filter(|r| r.method == Method::POST && r.url.path() == kind.login_path())
+        .count()
+}
+
+#[tokio::test]
+async fn test_multipart_upload_keeps_csrf() -> Result<(), UniFiError> {
+    // What it tests: A multipart body is sent with its boundary in the `Content-Type` header,
+    // its text and file parts encoded, and the session cookie and CSRF header of UniFi OS.
+    //
+    // Why it's valuable: Portal bundles and backup restores are uploaded as multipart forms and
+    // are rejected by UniFi OS without the CSRF token.
+    let kind = TestControllerKind::Os;
+    let server = MockServer::start().await;
+    let client = login(&server, kind).await?;
+    let upload = api_path(kind, "/upload/backup");
+    add_auth_headers(Mock::given(method("POST")).and(path(upload.as_str())), kind)
+        .and(header_regex(
+            "content-type",
+            "^multipart/form-data; boundary=unifi-client-",
+        ))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let form = Multipart::new().text("site", "default").file(
+        "file",
+        "restore.unf",
+        "application/octet-stream",
+        b"\x00backup".to_vec(),
+    );
+    let boundary = form.boundary().to_string();
+    client
+        .request_with_body(Method::POST, "/upload/backup", form)
+        .await?;
+
+    let requests = server.received_requests().await.unwrap();
+    let body = &requests.last().unwrap().body;
+    let expected_file =
+        b"filename=\"restore.unf\"\r\nContent-Type: application/octet-stream\r\n\r\n\x00backup\r\n";
+    assert!(body
+        .windows(expected_file.len())
+        .any(|w| w == expected_file));
+    assert!(body.ends_with(format!("--{boundary}--\r\n").as_bytes()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_raw_body_resent_after_reauthentication() -> Result<(), UniFiError> {
+    // What it tests: A raw body rejected with 401 is sent again, byte for byte and with its
+    // content type, after the client logs back in.
+    //
+    // Why it's valuable: Firmware uploads are large and slow; an expired session must not turn
+    // them into a failure or a truncated retry.
+    let kind = TestControllerKind::Network;
+    let server = MockServer::start().await;
+    let client = login(&server, kind).await?;
+    Mock::given(method("POST"))
+        .and(path("/upload/firmware"))
+        .respond_with(ResponseTemplate::new(401))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/upload/firmware"))
+        .and(header("content-type", "application/octet-stream"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let firmware = vec![0xAB; 64 * 1024];
+    let response = client
+        .request_with_body(
+            Method::POST,
+            "/upload/firmware",
+            RequestBody::bytes("application/octet-stream", firmware.clone()),
+        )
+        .await?;
+    assert!(response.status().is_success());
+
+    let requests = server.received_requests().await.unwrap();
+    let uploads: Vec<_> = requests
+        .iter()
+        .filter(|r| r.url.path() == "/upload/firmware")
+        .collect();
+    assert_eq!(uploads.len(), 2);
+    assert!(uploads.iter().all(|r| r.body == firmware));
+    assert_eq!(login_count(&requests, kind), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_form_body_is_url_encoded() -> Result<(), UniFiError> {
+    // What it tests: A form body is sent as `application/x-www-form-urlencoded` with its values
+    // escaped.
+    //
+    // Why it's valuable: Some legacy controller endpoints only accept form posts.
+    let kind = TestControllerKind::Network;
+    let server = MockServer::start().await;
+    let client = login(&server, kind).await?;
+    Mock::given(method("POST"))
+        .and(path("/api/s/default/form"))
+        .and(header("content-type", "application/x-www-form-urlencoded"))
+        .respond_with(|request: &wiremock::Request| {
+            assert_eq!(request.body, b"name=guest+one&note=a%26b");
+            ResponseTemplate::new(200)
+        })
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client
+        .request_with_body(
+            Method::POST,
+            "/api/s/default/form",
+            RequestBody::Form(vec![
+                ("name".into(), "guest one".into()),
+                ("note".into(), "a&b".into()),
+            ]),
+        )
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_streams_body_after_reauthentication() -> Result<(), UniFiError> {
+    // What it tests: `download` re-authenticates after a 403 on UniFi OS, writes only the body
+    // of the successful retry to the writer, and returns the response headers.
+    //
+    // Why it's valuable: Backups must be written exactly once and never mixed with the body of
+    // an error response.
+    let kind = TestControllerKind::Os;
+    let server = MockServer::start().await;
+    let client = login(&server, kind).await?;
+    let backup = api_path(kind, "/dl/autobackup/autobackup.unf");
+    Mock::given(method("GET"))
+        .and(path(backup.as_str()))
+        .respond_with(ResponseTemplate::new(403).set_body_string("forbidden"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    add_auth_headers(Mock::given(method("GET")).and(path(backup.as_str())), kind)
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-disposition", "attachment; filename=autobackup.unf")
+                .set_body_bytes(vec![7u8; 256 * 1024]),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut out = Vec::new();
+    let response = client
+        .download("/dl/autobackup/autobackup.unf", &mut out)
+        .await?;
+    assert_eq!(out, vec![7u8; 256 * 1024]);
+    assert!(response.body().is_empty());
+    assert_eq!(
+        response.headers()["content-disposition"],
+        "attachment; filename=autobackup.unf"
+    );
+    assert_eq!(
+        login_count(&server.received_requests().await.unwrap(), kind),
+        2
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_error_leaves_writer_untouched() -> Result<(), UniFiError> {
+    // What it tests: A download answered with 404 fails with a controller error carrying the
+    // status, and nothing is written.
+    //
+    // Why it's valuable: Callers streaming into a file must not end up with an error page
+    // saved as a backup.
+    let kind = TestControllerKind::Network;
+    let server = MockServer::start().await;
+    let client = login(&server, kind).await?;
+    Mock::given(method("GET"))
+        .and(path("/dl/backup/missing.unf"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("not found"))
+        .mount(&server)
+        .await;
+
+    let mut out = Vec::new();
+    let err = client
+        .download("/dl/backup/missing.unf", &mut out)
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(http::StatusCode::NOT_FOUND));
+    assert!(out.is_empty());
+    Ok(())
+}
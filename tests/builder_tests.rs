@@ -120,3 +120,37 @@ async fn test_default_client_panics_in_debug_without_build() {
         .request_json::<()>(Method::GET, "/api/self", None)
         .await;
 }
+
+#[tokio::test]
+async fn test_builder_rejects_invalid_api_key_configuration() {
+    // What it tests: API key authentication is mutually exclusive with username/password, and an
+    // empty key is rejected.
+    //
+    // Why it's valuable: Mixing both credential types makes it ambiguous which one the client uses
+    // for re-authentication; failing at build time keeps the contract explicit.
+    let err = UniFiClient::builder()
+        .controller_url("https://example.com")
+        .api_key("key")
+        .username("user")
+        .password("pass")
+        .build()
+        .await
+        .unwrap_err();
+    match err {
+        UniFiError::ConfigurationError(msg) => {
+            assert_eq!(msg, "API key cannot be combined with username/password")
+        }
+        other => panic!("Expected ConfigurationError for mixed credentials, got {other:?}"),
+    }
+
+    let err = UniFiClient::builder()
+        .controller_url("https://example.com")
+        .api_key("   ")
+        .build()
+        .await
+        .unwrap_err();
+    match err {
+        UniFiError::ConfigurationError(msg) => assert_eq!(msg, "API key must not be empty"),
+        other => panic!("Expected ConfigurationError for empty API key, got {other:?}"),
+    }
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "cassettes")]
+
+use std::path::PathBuf;
+
+use http::Method;
+use serde_json::json;
+use unifi_client::cassette::{Cassette, MatchOn, ReplayTransport};
+use unifi_client::{UniFiClient, UniFiError};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use common::{add_auth_headers, api_path, setup_probe_and_login, TestControllerKind};
+
+fn cassette_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("unifi-client-{}-{name}", std::process::id()))
+}
+
+async fn record(server: &MockServer, cassette: &PathBuf) -> Result<(), UniFiError> {
+    let kind = TestControllerKind::Os;
+    setup_probe_and_login(server, kind).await;
+    let list = api_path(kind, "/api/s/default/stat/guest");
+    add_auth_headers(Mock::given(method("GET")).and(path(list.as_str())), kind)
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "meta": { "rc": "ok" },
+            "data": [{ "mac": "00:11:22:33:44:55", "x_passphrase": "wifi-secret" }]
+        })))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/proxy/network/api/s/default/stat/sta"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("not json"))
+        .mount(server)
+        .await;
+
+    let client = UniFiClient::builder()
+        .controller_url(server.uri())
+        .username("test-user")
+        .password("test-password")
+        .record_cassette(cassette)
+        .build()
+        .await?;
+    client
+        .request_json(Method::GET, "/api/s/default/stat/guest", None::<()>)
+        .await?;
+    let response = client
+        .request(Method::GET, "/api/s/default/stat/sta", None::<()>)
+        .await?;
+    assert_eq!(response.text(), "not json");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_recording_redacts_secrets() -> Result<(), UniFiError> {
+    // What it tests: A recorded cassette contains every interaction, including detection and
+    // login, but none of the password, session cookie, CSRF token or sensitive JSON fields.
+    //
+    // Why it's valuable: Cassettes are meant to be committed, so leaking credentials into them
+    // would be a security problem.
+    let server = MockServer::start().await;
+    let path = cassette_path("redact.json");
+    record(&server, &path).await?;
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    for secret in ["test-password", "test-token", "test-csrf", "wifi-secret"] {
+        assert!(!contents.contains(secret), "cassette leaks {secret}");
+    }
+    let cassette = Cassette::load(&path)?;
+    let paths: Vec<_> = cassette
+        .interactions
+        .iter()
+        .map(|i| (i.request.method.as_str(), i.request.path.as_str()))
+        .collect();
+    // Detection probes come first, then the login and the two requests.
+    assert_eq!(
+        paths[paths.len() - 3..],
+        [
+            ("POST", "/api/auth/login"),
+            ("GET", "/proxy/network/api/s/default/stat/guest"),
+            ("GET", "/proxy/network/api/s/default/stat/sta"),
+        ]
+    );
+    assert_eq!(
+        cassette.interactions[paths.len() - 3]
+            .request
+            .body
+            .json
+            .as_ref()
+            .unwrap()["username"],
+        "test-user"
+    );
+    assert_eq!(
+        cassette.interactions[paths.len() - 1]
+            .response
+            .body
+            .text
+            .as_deref(),
+        Some("not json")
+    );
+    std::fs::remove_file(&path).ok();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_replay_serves_recorded_responses() -> Result<(), UniFiError> {
+    // What it tests: A client replaying a cassette logs in and receives the recorded responses
+    // without a controller, and requests that were never recorded fail.
+    //
+    // Why it's valuable: Replay is what lets integrations captured against real hardware run in
+    // CI.
+    let server = MockServer::start().await;
+    let path = cassette_path("replay.json");
+    record(&server, &path).await?;
+    drop(server);
+
+    let client = UniFiClient::builder()
+        .controller_url("https://replay.invalid")
+        .username("test-user")
+        .password("another-password")
+        .transport(ReplayTransport::load(&path)?.match_on([MatchOn::Method, MatchOn::Path]))
+        .build()
+        .await?;
+    let guests = client
+        .request_json(Method::GET, "/api/s/default/stat/guest", None::<()>)
+        .await?;
+    assert_eq!(guests[0]["mac"], json!("00:11:22:33:44:55"));
+    assert_eq!(guests[0]["x_passphrase"], json!("[REDACTED]"));
+
+    // The only recorded guest listing has been used up.
+    let again = client
+        .request_json(Method::GET, "/api/s/default/stat/guest", None::<()>)
+        .await;
+    assert!(matches!(again, Err(UniFiError::TransportError(_))));
+    std::fs::remove_file(&path).ok();
+    Ok(())
+}
@@ -15,6 +15,16 @@ pub async fn setup_test_client(mock_server_uri: &str) -> UniFiClient {
         .expect("Failed to build UniFiClient")
 }
 
+pub async fn setup_api_key_test_client(mock_server_uri: &str) -> UniFiClient {
+    UniFiClient::builder()
+        .api_key("test-api-key")
+        .controller_url(mock_server_uri)
+        .site("default")
+        .build()
+        .await
+        .expect("Failed to build UniFiClient")
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum TestControllerKind {
     Network,
@@ -39,19 +49,22 @@ impl TestControllerKind {
 
 pub async fn setup_probe(server: &MockServer, kind: TestControllerKind) {
     match kind {
-        // For UniFi OS, a HEAD "/" returns 200 which the client uses to detect OS.
+        // UniFi OS describes the console at `/api/system`.
         TestControllerKind::Os => {
-            Mock::given(method("HEAD"))
-                .and(path("/"))
-                .respond_with(ResponseTemplate::new(200))
+            Mock::given(method("GET"))
+                .and(path("/api/system"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "name": "Test" })))
                 .mount(server)
                 .await;
         }
-        // For UniFi Network, a HEAD "/" returns 304.
+        // Classic Network controllers report their status at `/status`.
         TestControllerKind::Network => {
-            Mock::given(method("HEAD"))
-                .and(path("/"))
-                .respond_with(ResponseTemplate::new(304))
+            Mock::given(method("GET"))
+                .and(path("/status"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "meta": { "rc": "ok", "up": true, "server_version": "8.0.0" },
+                    "data": []
+                })))
                 .mount(server)
                 .await;
         }
@@ -60,7 +73,10 @@ pub async fn setup_probe(server: &MockServer, kind: TestControllerKind) {
 
 pub async fn setup_probe_and_login(server: &MockServer, kind: TestControllerKind) {
     setup_probe(server, kind).await;
+    setup_login(server, kind).await;
+}
 
+pub async fn setup_login(server: &MockServer, kind: TestControllerKind) {
     // Login endpoint differs by kind. Always set a cookie and CSRF header.
     let mut resp = ResponseTemplate::new(200);
     match kind {
@@ -16,6 +16,16 @@ async fn test_concurrent_client_usage() -> Result<(), Box<dyn std::error::Error>
     // Set up WireMock server
     let mock_server = MockServer::start().await;
 
+    // Mock the status endpoint that identifies a classic Network controller
+    Mock::given(method("GET"))
+        .and(path("/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "meta": { "rc": "ok", "up": true, "server_version": "8.0.0" },
+            "data": []
+        })))
+        .mount(&mock_server)
+        .await;
+
     // Mock successful login
     Mock::given(method("POST"))
         .and(path("/api/login"))
@@ -123,7 +133,7 @@ async fn test_concurrent_client_usage() -> Result<(), Box<dyn std::error::Error>
 
     // Build the client
     let client = UniFiClient::builder()
-        .controller_url(&mock_server.uri())
+        .controller_url(mock_server.uri())
         .username("admin")
         .password("password")
         .build()
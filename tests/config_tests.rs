@@ -0,0 +1,140 @@
+#![cfg(feature = "config")]
+
+use wiremock::MockServer;
+
+mod common;
+
+use common::{setup_probe_and_login, TestControllerKind};
+use unifi_client::{UniFiClientBuilder, UniFiClientConfig, UniFiError};
+
+fn config_error<T>(result: Result<T, UniFiError>) -> String {
+    match result {
+        Err(UniFiError::ConfigurationError(msg)) => msg,
+        Err(other) => panic!("Expected ConfigurationError, got {other:?}"),
+        Ok(_) => panic!("Expected ConfigurationError, got Ok"),
+    }
+}
+
+#[tokio::test]
+async fn test_client_from_toml_and_json() -> Result<(), UniFiError> {
+    // What it tests: TOML and JSON documents covering credentials, site, TLS, timeout and retry
+    // settings produce a builder that logs in against the controller.
+    //
+    // Why it's valuable: Services should be able to replace their hand-written config mapping
+    // with a single call.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Network).await;
+    let password_file =
+        std::env::temp_dir().join(format!("unifi-client-config-{}", std::process::id()));
+    std::fs::write(&password_file, "test-password\n").unwrap();
+
+    let toml = format!(
+        r#"
+        controller_url = "{}"
+        site = "lab"
+        username = "test-user"
+        password_file = {:?}
+        timeout_secs = 5
+
+        [tls]
+        accept_invalid_certs = true
+
+        [retry]
+        max_attempts = 2
+        jitter = false
+
+        [resolve]
+        "unifi.invalid" = "127.0.0.1"
+        "#,
+        mock_server.uri(),
+        password_file.display().to_string(),
+    );
+    let client = UniFiClientConfig::from_toml_str(&toml)?
+        .into_builder()?
+        .build()
+        .await?;
+    assert_eq!(client.site(), "lab");
+
+    let json = serde_json::json!({
+        "controller_url": mock_server.uri(),
+        "controller_kind": "network",
+        "username": "test-user",
+        "password": "test-password",
+    });
+    let client = UniFiClientConfig::from_json_str(&json.to_string())?
+        .into_builder()?
+        .build()
+        .await?;
+    assert_eq!(client.site(), "default");
+
+    std::fs::remove_file(&password_file).ok();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_builder_from_env() -> Result<(), UniFiError> {
+    // What it tests: `UniFiClientBuilder::from_env` reads prefixed variables, including nested
+    // TLS and retry keys, and reports unparsable values by variable name.
+    //
+    // Why it's valuable: Twelve-factor services configure the client entirely from the
+    // environment and need to know which variable is wrong.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Network).await;
+
+    std::env::set_var("CONFIG_TEST_CONTROLLER_URL", mock_server.uri());
+    std::env::set_var("CONFIG_TEST_USERNAME", "test-user");
+    std::env::set_var("CONFIG_TEST_PASSWORD_ENV", "CONFIG_TEST_SECRET");
+    std::env::set_var("CONFIG_TEST_SECRET", "test-password");
+    std::env::set_var("CONFIG_TEST_SITE", "lab");
+    std::env::set_var("CONFIG_TEST_TLS_ACCEPT_INVALID_CERTS", "true");
+    std::env::set_var("CONFIG_TEST_RETRY_MAX_ATTEMPTS", "3");
+
+    let client = UniFiClientBuilder::from_env("CONFIG_TEST")?.build().await?;
+    assert_eq!(client.site(), "lab");
+
+    std::env::set_var("CONFIG_TEST_RETRY_MAX_ATTEMPTS", "three");
+    let msg = config_error(UniFiClientBuilder::from_env("CONFIG_TEST_"));
+    assert!(msg.contains("CONFIG_TEST_RETRY_MAX_ATTEMPTS"), "{msg}");
+    Ok(())
+}
+
+#[test]
+fn test_config_errors_name_the_key() {
+    // What it tests: Type errors, unknown keys and invalid or conflicting values are reported as
+    // configuration errors naming the offending key.
+    //
+    // Why it's valuable: A message like "invalid type: string" without a key is hard to act on
+    // in a large config file.
+    let msg = config_error(UniFiClientConfig::from_toml_str(
+        "controller_url = \"https://unifi.example\"\n[retry]\nmax_attempts = \"three\"\n",
+    ));
+    assert!(msg.contains("`retry.max_attempts`"), "{msg}");
+
+    let msg = config_error(UniFiClientConfig::from_json_str(
+        r#"{ "tls": { "accept_invalid_certs": "yes" } }"#,
+    ));
+    assert!(msg.contains("`tls.accept_invalid_certs`"), "{msg}");
+
+    let msg = config_error(UniFiClientConfig::from_toml_str("pasword = \"typo\"\n"));
+    assert!(msg.contains("pasword"), "{msg}");
+
+    let invalid = |toml: &str| {
+        config_error(UniFiClientConfig::from_toml_str(toml).and_then(|c| c.into_builder()))
+    };
+    let msg = invalid("controller_url = \"not a url\"\nusername = \"u\"\npassword = \"p\"\n");
+    assert!(msg.contains("`controller_url`"), "{msg}");
+    let msg = invalid(
+        "controller_url = \"https://unifi.example\"\nusername = \"u\"\npassword = \"p\"\npassword_file = \"/p\"\n",
+    );
+    assert!(msg.contains("`password_file`"), "{msg}");
+    let msg = invalid(
+        "controller_url = \"https://unifi.example\"\nusername = \"u\"\npassword = \"p\"\ntimeout_secs = 0\n",
+    );
+    assert!(msg.contains("`timeout_secs`"), "{msg}");
+    let msg = invalid("controller_url = \"https://unifi.example\"\npassword = \"p\"\n");
+    assert!(msg.contains("`username`"), "{msg}");
+    let msg = invalid(
+        "controller_url = \"https://unifi.example\"\nusername = \"u\"\npassword = \"p\"\n[proxy]\nurl = \"http://bastion:3128\"\nusername = \"b\"\n",
+    );
+    assert!(msg.contains("`proxy.password`"), "{msg}");
+}
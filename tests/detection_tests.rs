@@ -0,0 +1,207 @@
+use serde_json::json;
+use wiremock::matchers::{body_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use common::{setup_login, TestControllerKind};
+use unifi_client::{ControllerKind, UniFiClient, UniFiError};
+
+async fn build_client(mock_server: &MockServer) -> Result<UniFiClient, UniFiError> {
+    UniFiClient::builder()
+        .username("test-user")
+        .password("test-password")
+        .controller_url(mock_server.uri())
+        .build()
+        .await
+}
+
+#[tokio::test]
+async fn test_detects_unifi_os_from_system_endpoint() -> Result<(), UniFiError> {
+    // What it tests: A console answering `/api/system` is detected as UniFi OS even though its root
+    // does not return 200, and the Network version is read from `/proxy/network/status`.
+    //
+    // Why it's valuable: Reverse proxies and UniFi OS Server often rewrite `/`, which made the
+    // single HEAD probe misdetect them as classic controllers.
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/system"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "hardware": { "shortname": "UCGMAX" },
+            "name": "Gateway"
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/proxy/network/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "meta": { "rc": "ok", "up": true, "server_version": "9.0.114" },
+            "data": []
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("HEAD"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(302))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+    setup_login(&mock_server, TestControllerKind::Os).await;
+
+    let client = build_client(&mock_server).await?;
+    assert_eq!(client.controller_kind(), ControllerKind::Os);
+    assert_eq!(client.controller_version(), Some("9.0.114"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_detects_network_controller_from_status_endpoint() -> Result<(), UniFiError> {
+    // What it tests: A classic controller answering `/status` is detected as Network, with its
+    // server version, even if its root returns 200.
+    //
+    // Why it's valuable: A root returning 200 (e.g., behind a proxy serving a landing page) used to
+    // be mistaken for UniFi OS.
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "meta": { "rc": "ok", "up": true, "server_version": "7.5.187" },
+            "data": []
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("HEAD"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+    setup_login(&mock_server, TestControllerKind::Network).await;
+
+    let client = build_client(&mock_server).await?;
+    assert_eq!(client.controller_kind(), ControllerKind::Network);
+    assert_eq!(client.controller_version(), Some("7.5.187"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_detects_reverse_proxied_unifi_os_from_login_response() -> Result<(), UniFiError> {
+    // What it tests: A UniFi OS console behind a reverse proxy that only forwards the login and
+    // Network API paths, and redirects `/`, is detected from the shape of its login rejection.
+    //
+    // Why it's valuable: The status probes fail behind such proxies, and a root that does not
+    // return 200 used to be taken for a classic controller.
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/auth/login"))
+        .and(body_json(json!({})))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "code": "AUTHENTICATION_FAILED_INVALID_CREDENTIALS",
+            "message": "Invalid username or password"
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("HEAD"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(302).insert_header("location", "/login"))
+        .mount(&mock_server)
+        .await;
+    setup_login(&mock_server, TestControllerKind::Os).await;
+
+    let client = build_client(&mock_server).await?;
+    assert_eq!(client.controller_kind(), ControllerKind::Os);
+    assert_eq!(client.controller_version(), None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_detects_network_controller_from_login_response() -> Result<(), UniFiError> {
+    // What it tests: A classic controller whose `/status` is not reachable is detected from the
+    // `meta` envelope of its login rejection.
+    //
+    // Why it's valuable: Classic controllers answer unknown `/api` paths with `meta` errors too, so
+    // the envelope tells them apart from UniFi OS.
+    let mock_server = MockServer::start().await;
+    for login_path in ["/api/auth/login", "/api/login"] {
+        Mock::given(method("POST"))
+            .and(path(login_path))
+            .and(body_json(json!({})))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "meta": { "rc": "error", "msg": "api.err.Invalid" },
+                "data": []
+            })))
+            .mount(&mock_server)
+            .await;
+    }
+    setup_login(&mock_server, TestControllerKind::Network).await;
+
+    let client = build_client(&mock_server).await?;
+    assert_eq!(client.controller_kind(), ControllerKind::Network);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_undetectable_controller_is_an_error() {
+    // What it tests: A server matching none of the probes fails the build with a configuration
+    // error, even if its root returns 200.
+    //
+    // Why it's valuable: Guessing the kind led to login failures far from the actual problem; the
+    // error points at `controller_kind()` instead.
+    let mock_server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    let result = build_client(&mock_server).await;
+    assert!(
+        matches!(result, Err(UniFiError::ConfigurationError(ref msg)) if msg.contains("controller_kind")),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_controller_kind_override_skips_detection() -> Result<(), UniFiError> {
+    // What it tests: An explicit controller kind is used as-is and no probe requests are sent.
+    //
+    // Why it's valuable: Some deployments cannot be detected reliably; the override makes them
+    // work without guessing.
+    let mock_server = MockServer::start().await;
+    setup_login(&mock_server, TestControllerKind::Os).await;
+
+    let client = UniFiClient::builder()
+        .username("test-user")
+        .password("test-password")
+        .controller_url(mock_server.uri())
+        .controller_kind(ControllerKind::Os)
+        .build()
+        .await?;
+    assert_eq!(client.controller_kind(), ControllerKind::Os);
+    assert_eq!(client.controller_version(), None);
+
+    let requests = mock_server.received_requests().await.unwrap_or_default();
+    assert_eq!(requests.len(), 1, "only the login request is sent");
+    assert_eq!(requests[0].url.path(), "/api/auth/login");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unreachable_controller_is_an_error() {
+    // What it tests: A connection failure during detection surfaces as an HTTP error instead of
+    // silently assuming a classic Network controller.
+    //
+    // Why it's valuable: Misdetection hid the real problem behind a confusing login failure.
+    let result = UniFiClient::builder()
+        .username("test-user")
+        .password("test-password")
+        .controller_url("http://127.0.0.1:9")
+        .build()
+        .await;
+    assert!(matches!(result, Err(UniFiError::HttpError(ref e)) if e.is_connect()));
+}
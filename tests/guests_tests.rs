@@ -338,3 +338,44 @@ async fn test_list_guests_with_negative_qos_usage_quota() {
         }
     }
 }
+
+#[tokio::test]
+async fn test_site_scope_shares_session_across_sites() {
+    // What it tests: `site_scope` handles address their own site while reusing the parent
+    // client's session, so serving several sites costs a single login.
+    //
+    // Why it's valuable: Multi-tenant deployments would otherwise need one client (and one
+    // controller session) per site.
+    for &flavor in &[TestControllerKind::Network, TestControllerKind::Os] {
+        let mock_server = MockServer::start().await;
+        setup_probe_and_login(&mock_server, flavor).await;
+
+        for site in ["default", "branch-7"] {
+            let endpoint = api_path(flavor, &format!("/api/s/{site}/stat/guest"));
+            let mock = Mock::given(method("GET")).and(path(endpoint.as_str()));
+            add_auth_headers(mock, flavor)
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "meta": { "rc": "ok" },
+                    "data": []
+                })))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let client = setup_test_client(&mock_server.uri()).await;
+        let branch = client.site_scope("branch-7");
+        assert_eq!(branch.site(), "branch-7");
+        assert_eq!(client.site(), "default");
+
+        branch.guests().list().send().await.unwrap();
+        client.guests().list().send().await.unwrap();
+
+        let requests = mock_server.received_requests().await.unwrap();
+        let logins = requests
+            .iter()
+            .filter(|r| r.url.path() == flavor.login_path())
+            .count();
+        assert_eq!(logins, 1, "site scopes must share the session ({flavor:?})");
+    }
+}
@@ -0,0 +1,220 @@
+use serde_json::json;
+use wiremock::matchers::{body_json, header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use common::{setup_api_key_test_client, setup_probe, TestControllerKind};
+use unifi_client::UniFiError;
+
+const SITE_ID: &str = "88f7af54-98f8-306a-a1c7-c9349722b1f6";
+
+fn site(n: u32) -> serde_json::Value {
+    json!({
+        "id": format!("site-{n}"),
+        "internalReference": format!("ref-{n}"),
+        "name": format!("Site {n}")
+    })
+}
+
+#[tokio::test]
+async fn test_integration_sites_paging() -> Result<(), UniFiError> {
+    // What it tests: A single page request sends `offset`/`limit` as query parameters under the
+    // `/proxy/network/integration/v1` prefix and decodes the Integration API page envelope.
+    //
+    // Why it's valuable: The Integration API does not use the legacy `{ meta, data }` envelope,
+    // so this guards both the URL layout and the envelope decoding.
+    let mock_server = MockServer::start().await;
+    setup_probe(&mock_server, TestControllerKind::Os).await;
+
+    Mock::given(method("GET"))
+        .and(path("/proxy/network/integration/v1/sites"))
+        .and(query_param("offset", "10"))
+        .and(query_param("limit", "2"))
+        .and(header("x-api-key", "test-api-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "offset": 10,
+            "limit": 2,
+            "count": 2,
+            "totalCount": 15,
+            "data": [site(10), site(11)]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = setup_api_key_test_client(&mock_server.uri()).await;
+    let page = client
+        .integration()
+        .sites()
+        .offset(10)
+        .limit(2)
+        .send()
+        .await?;
+
+    assert_eq!(page.total_count, 15);
+    assert_eq!(page.data.len(), 2);
+    assert_eq!(page.data[0].internal_reference, "ref-10");
+    assert!(page.has_more());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_integration_send_all_follows_pages() -> Result<(), UniFiError> {
+    // What it tests: `send_all()` keeps requesting pages, advancing the offset by the returned
+    // count, until `totalCount` items have been collected.
+    //
+    // Why it's valuable: Callers rely on `send_all()` to return complete inventories; stopping
+    // early or looping forever would both be silent, costly bugs.
+    let mock_server = MockServer::start().await;
+    setup_probe(&mock_server, TestControllerKind::Os).await;
+
+    let devices_path = format!("/proxy/network/integration/v1/sites/{SITE_ID}/devices");
+    let device = |n: u32| {
+        json!({
+            "id": format!("device-{n}"),
+            "name": format!("AP {n}"),
+            "model": "U6 Pro",
+            "macAddress": format!("00:11:22:33:44:{n:02}"),
+            "ipAddress": "10.0.0.2",
+            "state": "ONLINE",
+            "features": ["accessPoint"],
+            "interfaces": ["radios"]
+        })
+    };
+
+    Mock::given(method("GET"))
+        .and(path(devices_path.as_str()))
+        .and(query_param("offset", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "offset": 0,
+            "limit": 2,
+            "count": 2,
+            "totalCount": 3,
+            "data": [device(1), device(2)]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path(devices_path.as_str()))
+        .and(query_param("offset", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "offset": 2,
+            "limit": 2,
+            "count": 1,
+            "totalCount": 3,
+            "data": [device(3)]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = setup_api_key_test_client(&mock_server.uri()).await;
+    let devices = client
+        .integration()
+        .devices(SITE_ID)
+        .limit(2)
+        .send_all()
+        .await?;
+
+    let ids: Vec<&str> = devices.iter().map(|d| d.id.as_str()).collect();
+    assert_eq!(ids, ["device-1", "device-2", "device-3"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_integration_create_vouchers() -> Result<(), UniFiError> {
+    // What it tests: Voucher generation posts the camelCase request body expected by the
+    // Integration API and unwraps the `vouchers` array from the response.
+    //
+    // Why it's valuable: Field naming (e.g., `dataUsageLimitMBytes`) is easy to get wrong and the
+    // controller rejects unknown fields, so the wire format must be exact.
+    let mock_server = MockServer::start().await;
+    setup_probe(&mock_server, TestControllerKind::Os).await;
+
+    Mock::given(method("POST"))
+        .and(path(format!(
+            "/proxy/network/integration/v1/sites/{SITE_ID}/hotspot/vouchers"
+        )))
+        .and(body_json(json!({
+            "count": 1,
+            "name": "Conference",
+            "timeLimitMinutes": 60,
+            "dataUsageLimitMBytes": 500
+        })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+            "vouchers": [{
+                "id": "voucher-1",
+                "createdAt": "2025-01-01T00:00:00Z",
+                "name": "Conference",
+                "code": "4861409510",
+                "authorizedGuestLimit": null,
+                "authorizedGuestCount": 0,
+                "activatedAt": null,
+                "expiresAt": null,
+                "expired": false,
+                "timeLimitMinutes": 60,
+                "dataUsageLimitMBytes": 500,
+                "rxRateLimitKbps": null,
+                "txRateLimitKbps": null
+            }]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = setup_api_key_test_client(&mock_server.uri()).await;
+    let vouchers = client
+        .integration()
+        .create_vouchers(SITE_ID, 60)
+        .name("Conference")
+        .data_quota_megabytes(500)
+        .send()
+        .await?;
+
+    assert_eq!(vouchers.len(), 1);
+    assert_eq!(vouchers[0].code, "4861409510");
+    assert_eq!(vouchers[0].data_usage_limit_megabytes, Some(500));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_integration_error_envelope() {
+    // What it tests: Non-success responses are decoded from the Integration API error body and
+    // surfaced with the status name and message.
+    //
+    // Why it's valuable: The Integration API reports useful error details in its own schema;
+    // dropping them would leave callers with a bare status code.
+    let mock_server = MockServer::start().await;
+    setup_probe(&mock_server, TestControllerKind::Os).await;
+
+    Mock::given(method("GET"))
+        .and(path(format!(
+            "/proxy/network/integration/v1/sites/{SITE_ID}/devices/missing"
+        )))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+            "statusCode": 404,
+            "statusName": "NOT_FOUND",
+            "message": "Device not found",
+            "timestamp": "2025-01-01T00:00:00Z",
+            "requestPath": "/integration/v1/sites/x/devices/missing",
+            "requestId": "abc"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = setup_api_key_test_client(&mock_server.uri()).await;
+    match client.integration().device(SITE_ID, "missing").await {
+        Err(UniFiError::ControllerError(error)) => {
+            assert_eq!(error.status, http::StatusCode::NOT_FOUND);
+            assert_eq!(error.msg.as_deref(), Some("NOT_FOUND: Device not found"));
+            assert!(error.is_not_found());
+        }
+        other => panic!("Expected ControllerError, got {other:?}"),
+    }
+}
@@ -0,0 +1,139 @@
+#![cfg(feature = "metrics")]
+
+use http::Method;
+use metrics::{SharedString, Unit};
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+use metrics_util::CompositeKey;
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use common::{api_path, setup_probe_and_login, setup_test_client, TestControllerKind};
+use unifi_client::UniFiError;
+
+type Metrics = Vec<(CompositeKey, Option<Unit>, Option<SharedString>, DebugValue)>;
+
+/// Returns the value of the metric with the given name and labels, if it was emitted.
+fn find<'a>(metrics: &'a Metrics, name: &str, labels: &[(&str, &str)]) -> Option<&'a DebugValue> {
+    metrics.iter().find_map(|(key, _, _, value)| {
+        let matches = key.key().name() == name
+            && labels.iter().all(|(k, v)| {
+                key.key()
+                    .labels()
+                    .any(|label| label.key() == *k && label.value() == *v)
+            });
+        matches.then_some(value)
+    })
+}
+
+fn counter(metrics: &Metrics, name: &str, labels: &[(&str, &str)]) -> u64 {
+    match find(metrics, name, labels) {
+        Some(DebugValue::Counter(count)) => *count,
+        _ => 0,
+    }
+}
+
+#[tokio::test]
+async fn test_requests_logins_and_errors_are_counted() -> Result<(), UniFiError> {
+    // What it tests: A login, a request that triggers a re-login and a CSRF rotation, and a request
+    // that stays unauthorized are reported as request, login, CSRF rotation and error counters,
+    // with endpoints reduced to templates and a latency histogram per request.
+    //
+    // Why it's valuable: Operators of many controllers need to see how services use them, and
+    // endpoint templates keep label cardinality bounded.
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let _guard = metrics::set_default_local_recorder(&recorder);
+
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Os).await;
+
+    let endpoint = api_path(TestControllerKind::Os, "/api/s/default/stat/sta");
+    Mock::given(method("GET"))
+        .and(path(endpoint.as_str()))
+        .respond_with(ResponseTemplate::new(401))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(endpoint.as_str()))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("x-updated-csrf-token", "rotated-token")
+                .set_body_json(json!({ "meta": { "rc": "ok" }, "data": [] })),
+        )
+        .mount(&mock_server)
+        .await;
+
+    // Always rejected, even after re-login.
+    let denied = api_path(TestControllerKind::Os, "/api/s/default/stat/health");
+    Mock::given(method("GET"))
+        .and(path(denied.as_str()))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&mock_server)
+        .await;
+
+    let client = setup_test_client(&mock_server.uri()).await;
+    client
+        .request_json(Method::GET, "/api/s/default/stat/sta", None::<()>)
+        .await?;
+    let result = client
+        .request_json(Method::GET, "/api/s/default/stat/health", None::<()>)
+        .await;
+    assert!(matches!(result, Err(UniFiError::NotAuthenticated)));
+
+    let metrics = snapshotter.snapshot().into_vec();
+    let template = "/proxy/network/api/s/{site}/stat/sta";
+    assert_eq!(
+        counter(
+            &metrics,
+            "unifi_requests_total",
+            &[("method", "GET"), ("endpoint", template), ("status", "200")]
+        ),
+        1
+    );
+    assert!(matches!(
+        find(&metrics, "unifi_request_duration_seconds", &[("endpoint", template)]),
+        Some(DebugValue::Histogram(samples)) if samples.len() == 1
+    ));
+    assert_eq!(
+        counter(
+            &metrics,
+            "unifi_requests_total",
+            &[
+                ("endpoint", "/proxy/network/api/s/{site}/stat/health"),
+                ("status", "error")
+            ]
+        ),
+        1
+    );
+    assert_eq!(
+        counter(
+            &metrics,
+            "unifi_logins_total",
+            &[("kind", "initial"), ("result", "success")]
+        ),
+        1
+    );
+    assert_eq!(
+        counter(
+            &metrics,
+            "unifi_logins_total",
+            &[("kind", "relogin"), ("result", "success")]
+        ),
+        2
+    );
+    assert_eq!(counter(&metrics, "unifi_csrf_rotations_total", &[]), 1);
+    assert_eq!(
+        counter(
+            &metrics,
+            "unifi_errors_total",
+            &[("operation", "request"), ("error", "not_authenticated")]
+        ),
+        1
+    );
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use http::{HeaderValue, Method, StatusCode};
+use serde_json::{json, Value};
+use wiremock::matchers::{body_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use common::{api_path, setup_probe_and_login, TestControllerKind};
+use unifi_client::{
+    LoggingMiddleware, Middleware, MiddlewareRequest, RequestTiming, Response, TimingMiddleware,
+    UniFiClient, UniFiClientBuilder, UniFiError, UniFiResult,
+};
+
+/// Method, endpoint, body and status of a request seen by [`AuditMiddleware`].
+type AuditEntry = (Method, String, Option<Value>, Option<StatusCode>);
+
+/// Adds a header and records every request it sees, for audit-style assertions.
+#[derive(Default)]
+struct AuditMiddleware {
+    seen: Arc<Mutex<Vec<AuditEntry>>>,
+}
+
+#[async_trait]
+impl Middleware for AuditMiddleware {
+    async fn on_request(&self, request: &mut MiddlewareRequest) -> UniFiResult<()> {
+        request
+            .headers_mut()
+            .insert("x-audit", HeaderValue::from_static("hotspot"));
+        Ok(())
+    }
+
+    async fn on_response(
+        &self,
+        request: &MiddlewareRequest,
+        result: UniFiResult<Response>,
+    ) -> UniFiResult<Response> {
+        self.seen.lock().unwrap().push((
+            request.method().clone(),
+            request.endpoint().to_string(),
+            request.body().cloned(),
+            result.as_ref().ok().map(|r| r.status()),
+        ));
+        result
+    }
+}
+
+/// Fails every request before it is sent.
+struct FaultInjection;
+
+#[async_trait]
+impl Middleware for FaultInjection {
+    async fn on_request(&self, _request: &mut MiddlewareRequest) -> UniFiResult<()> {
+        Err(UniFiError::ApiError("injected fault".into()))
+    }
+}
+
+/// Replaces every response with a canned one.
+struct RewriteResponse;
+
+#[async_trait]
+impl Middleware for RewriteResponse {
+    async fn on_response(
+        &self,
+        _request: &MiddlewareRequest,
+        _result: UniFiResult<Response>,
+    ) -> UniFiResult<Response> {
+        let body = json!({ "meta": { "rc": "ok" }, "data": [{ "rewritten": true }] }).to_string();
+        Ok(http::Response::new(body).into())
+    }
+}
+
+fn client_builder(mock_server_uri: &str) -> UniFiClientBuilder {
+    UniFiClient::builder()
+        .username("test-user")
+        .password("test-password")
+        .controller_url(mock_server_uri)
+        .site("default")
+}
+
+#[tokio::test]
+async fn test_middleware_adds_headers_and_sees_request_and_response() -> Result<(), UniFiError> {
+    // What it tests: A middleware can add a header that reaches the controller, and its response
+    // hook sees the method, endpoint, JSON body and final status of the request.
+    //
+    // Why it's valuable: Custom headers and audit logging are the main reasons to hook into the
+    // client above the HTTP layer.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Os).await;
+
+    let endpoint = api_path(TestControllerKind::Os, "/api/s/default/cmd/stamgr");
+    Mock::given(method("POST"))
+        .and(path(endpoint.as_str()))
+        .and(header("x-audit", "hotspot"))
+        .and(body_json(json!({ "cmd": "kick-sta" })))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "meta": { "rc": "ok" }, "data": [] })),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let audit = AuditMiddleware::default();
+    let seen = Arc::clone(&audit.seen);
+    let client = client_builder(&mock_server.uri())
+        .middleware(audit)
+        .build()
+        .await?;
+
+    client
+        .request_json(
+            Method::POST,
+            "/api/s/default/cmd/stamgr",
+            Some(json!({ "cmd": "kick-sta" })),
+        )
+        .await?;
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 1);
+    assert_eq!(seen[0].0, Method::POST);
+    assert_eq!(seen[0].1, endpoint);
+    assert_eq!(seen[0].2, Some(json!({ "cmd": "kick-sta" })));
+    assert_eq!(seen[0].3, Some(StatusCode::OK));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_middleware_can_fail_requests_before_sending() {
+    // What it tests: An error returned from `on_request` aborts the request without contacting
+    // the controller, and outer middlewares still observe the failure.
+    //
+    // Why it's valuable: Fault injection in staging relies on failing requests deterministically.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Network).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/self"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let audit = AuditMiddleware::default();
+    let seen = Arc::clone(&audit.seen);
+    let client = client_builder(&mock_server.uri())
+        .middleware(audit)
+        .middleware(FaultInjection)
+        .build()
+        .await
+        .expect("Failed to build UniFiClient");
+
+    let result = client.request(Method::GET, "/api/self", None::<()>).await;
+    assert!(matches!(result, Err(UniFiError::ApiError(ref m)) if m == "injected fault"));
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 1);
+    assert_eq!(seen[0].3, None);
+}
+
+#[tokio::test]
+async fn test_middleware_can_rewrite_responses() -> Result<(), UniFiError> {
+    // What it tests: A response hook can replace the controller's response, and built-in logging
+    // and timing middlewares compose with it; timing is attached to the final response.
+    //
+    // Why it's valuable: Response rewriting lets callers paper over controller quirks, and the
+    // timing extension gives per-request latency without extra plumbing.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Network).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/self"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&mock_server)
+        .await;
+
+    let client = client_builder(&mock_server.uri())
+        .middleware(TimingMiddleware)
+        .middleware(LoggingMiddleware)
+        .middleware(RewriteResponse)
+        .build()
+        .await?;
+
+    let response = client.request(Method::GET, "/api/self", None::<()>).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.extensions().get::<RequestTiming>().is_some());
+
+    let body: Value = response.json()?;
+    assert_eq!(body["data"][0]["rewritten"], json!(true));
+
+    Ok(())
+}
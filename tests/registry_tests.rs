@@ -0,0 +1,53 @@
+#![cfg(feature = "default-client")]
+
+use std::sync::Arc;
+
+use wiremock::MockServer;
+
+mod common;
+
+use common::{setup_probe_and_login, setup_test_client, TestControllerKind};
+use unifi_client::{registry, UniFiError};
+
+#[tokio::test]
+async fn test_registry_registers_replaces_and_lists_clients() {
+    // What it tests: Clients registered by name can be looked up and listed, registering under an
+    // existing name replaces the client and returns the previous one, and unregistering removes
+    // it.
+    //
+    // Why it's valuable: Applications managing several controllers rely on each name resolving to
+    // the latest client without restarting.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Os).await;
+    let first = setup_test_client(&mock_server.uri()).await;
+    let second = setup_test_client(&mock_server.uri())
+        .await
+        .site_scope("lab");
+
+    assert!(registry::register("registry-test-lab", first).is_none());
+    let previous = registry::register("registry-test-lab", second).expect("previous client");
+    assert_eq!(previous.site(), "default");
+
+    let current = registry::get("registry-test-lab").unwrap();
+    assert_eq!(current.site(), "lab");
+    assert!(registry::clients()
+        .iter()
+        .any(|(name, client)| name == "registry-test-lab" && Arc::ptr_eq(client, &current)));
+
+    let removed = registry::unregister("registry-test-lab").expect("registered client");
+    assert!(Arc::ptr_eq(&removed, &current));
+    assert!(registry::unregister("registry-test-lab").is_none());
+}
+
+#[tokio::test]
+async fn test_registry_reports_missing_names() {
+    // What it tests: Looking up a name that was never registered is an error naming the client,
+    // rather than an inert default client.
+    //
+    // Why it's valuable: A typo in a controller name should fail loudly instead of surfacing later
+    // as confusing authentication errors.
+    match registry::get("registry-test-missing") {
+        Err(UniFiError::ClientNotRegistered(name)) => assert_eq!(name, "registry-test-missing"),
+        other => panic!("expected ClientNotRegistered, got {other:?}"),
+    }
+}
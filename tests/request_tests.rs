@@ -1,12 +1,12 @@
 use http::Method;
 use serde_json::json;
-use wiremock::matchers::{body_json, header, method, path};
+use wiremock::matchers::{body_json, header, method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 mod common;
 
 use common::{api_path, setup_probe, setup_probe_and_login, setup_test_client, TestControllerKind};
-use unifi_client::UniFiError;
+use unifi_client::{UniFiError, UniFiErrorCode};
 
 #[tokio::test]
 async fn test_invalid_endpoint_rejected() -> Result<(), UniFiError> {
@@ -141,3 +141,260 @@ async fn test_request_retries_once_on_403_os() -> Result<(), UniFiError> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_controller_error_keeps_status_code_and_body() -> Result<(), UniFiError> {
+    // What it tests: A 400 response with a `meta` envelope surfaces as a structured controller
+    // error carrying the status, raw `rc`/`msg`, endpoint, typed code and body snippet, and a
+    // missing site (`api.err.NoSiteContext`) is classified as not found.
+    //
+    // Why it's valuable: Callers can branch on the failure without parsing error strings.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Network).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/s/default/cmd/stamgr"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "meta": { "rc": "error", "msg": "api.err.InvalidPayload" },
+            "data": []
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/s/missing/stat/health"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "meta": { "rc": "error", "msg": "api.err.NoSiteContext" },
+            "data": []
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = setup_test_client(&mock_server.uri()).await;
+    let result = client
+        .request_json(
+            Method::POST,
+            "/api/s/default/cmd/stamgr",
+            Some(json!({ "cmd": "bogus" })),
+        )
+        .await;
+    let Err(UniFiError::ControllerError(error)) = result else {
+        panic!("expected ControllerError, got {result:?}");
+    };
+    assert_eq!(error.status, http::StatusCode::BAD_REQUEST);
+    assert_eq!(error.endpoint, "/api/s/default/cmd/stamgr");
+    assert_eq!(error.rc.as_deref(), Some("error"));
+    assert_eq!(error.msg.as_deref(), Some("api.err.InvalidPayload"));
+    assert_eq!(error.code, Some(UniFiErrorCode::InvalidPayload));
+    assert!(error.body.contains("api.err.InvalidPayload"));
+    assert!(!error.is_retryable() && !error.is_not_found() && !error.is_permission_denied());
+
+    let missing = client
+        .request_json(Method::GET, "/api/s/missing/stat/health", None::<()>)
+        .await
+        .unwrap_err();
+    assert!(missing.is_not_found());
+    assert_eq!(missing.code(), Some(&UniFiErrorCode::NoSiteContext));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_controller_error_from_result_code() -> Result<(), UniFiError> {
+    // What it tests: A 200 response whose `meta.rc` is not "ok" is reported with its message kept
+    // verbatim, and unknown messages map to `UniFiErrorCode::Other`.
+    //
+    // Why it's valuable: Some controllers signal failures only through `meta.rc`; those must be as
+    // inspectable as HTTP errors.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Os).await;
+
+    let endpoint = api_path(TestControllerKind::Os, "/api/s/default/rest/user/abc");
+    Mock::given(method("GET"))
+        .and(path(endpoint.as_str()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "meta": { "rc": "error", "msg": "api.err.SomethingNew" },
+            "data": []
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = setup_test_client(&mock_server.uri()).await;
+    let error = client
+        .request_json(Method::GET, "/api/s/default/rest/user/abc", None::<()>)
+        .await
+        .unwrap_err();
+    assert_eq!(error.status(), Some(http::StatusCode::OK));
+    assert_eq!(
+        error.code(),
+        Some(&UniFiErrorCode::Other("api.err.SomethingNew".into()))
+    );
+    assert_eq!(
+        error.to_string(),
+        "API error: /api/s/default/rest/user/abc returned 200 OK: api.err.SomethingNew"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_query_parameters_are_encoded() -> Result<(), UniFiError> {
+    // What it tests: `request_json_with_query` and `request_with_query` send typed parameters as
+    // an encoded query string, with reserved characters escaped and no JSON body on the GET.
+    //
+    // Why it's valuable: Event listings, reports and v2 endpoints filter through query
+    // parameters; hand-built query strings are rejected by the endpoint guard.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Network).await;
+    Mock::given(method("GET"))
+        .and(path("/api/s/default/stat/event"))
+        .and(query_param("within", "24"))
+        .and(query_param("search", "a&b=c d"))
+        .respond_with(|request: &wiremock::Request| {
+            assert!(request.body.is_empty(), "GET must not carry a body");
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "meta": { "rc": "ok" }, "data": [{ "key": "EVT" }] }))
+        })
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let client = setup_test_client(&mock_server.uri()).await;
+    let data = client
+        .request_json_with_query(
+            Method::GET,
+            "/api/s/default/stat/event",
+            &[("within", "24"), ("search", "a&b=c d")],
+            None::<()>,
+        )
+        .await?;
+    assert_eq!(data[0]["key"], json!("EVT"));
+
+    #[derive(serde::Serialize)]
+    struct EventQuery {
+        within: u32,
+        search: &'static str,
+    }
+    let response = client
+        .request_with_query(
+            Method::GET,
+            "/api/s/default/stat/event",
+            &EventQuery {
+                within: 24,
+                search: "a&b=c d",
+            },
+            None::<()>,
+        )
+        .await?;
+    assert!(response.status().is_success());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_v2_endpoints_return_bare_json() -> Result<(), UniFiError> {
+    // What it tests: `v2_endpoint` resolves under `/proxy/network` on UniFi OS and at the root on
+    // the Network application, and `request_typed` reads bare arrays, bare objects and empty
+    // bodies without the `{ meta, data }` envelope.
+    //
+    // Why it's valuable: Traffic rules, static DNS and firewall policies only exist on the v2 API,
+    // which `request_json` and `get`/`post` reject as malformed.
+    #[derive(serde::Deserialize)]
+    struct TrafficRule {
+        #[serde(rename = "_id")]
+        id: String,
+        enabled: bool,
+    }
+
+    for kind in [TestControllerKind::Network, TestControllerKind::Os] {
+        let mock_server = MockServer::start().await;
+        setup_probe_and_login(&mock_server, kind).await;
+        let rules = api_path(kind, "/v2/api/site/default/trafficrules");
+        Mock::given(method("GET"))
+            .and(path(rules.as_str()))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!([{ "_id": "rule-1", "enabled": true }])),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(rules.as_str()))
+            .and(body_json(
+                json!({ "description": "block", "enabled": false }),
+            ))
+            .respond_with(
+                ResponseTemplate::new(201)
+                    .set_body_json(json!({ "_id": "rule-2", "enabled": false })),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let rule = api_path(kind, "/v2/api/site/default/trafficrules/rule-1");
+        Mock::given(method("DELETE"))
+            .and(path(rule.as_str()))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = setup_test_client(&mock_server.uri()).await;
+        let endpoint = client.v2_endpoint("trafficrules");
+        let listed: Vec<TrafficRule> = client
+            .request_typed(Method::GET, &endpoint, None::<()>)
+            .await?;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, "rule-1");
+        assert!(listed[0].enabled);
+
+        let created: TrafficRule = client
+            .request_typed(
+                Method::POST,
+                &endpoint,
+                Some(json!({ "description": "block", "enabled": false })),
+            )
+            .await?;
+        assert_eq!(created.id, "rule-2");
+
+        client
+            .request_typed::<(), ()>(
+                Method::DELETE,
+                &client.v2_endpoint("trafficrules/rule-1"),
+                None,
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_v2_error_code_is_typed() -> Result<(), UniFiError> {
+    // What it tests: A v2 error body (`{ code, message }`) becomes a ControllerError carrying the
+    // status and the typed result code.
+    //
+    // Why it's valuable: Callers handle v2 and legacy failures with the same `code()` checks.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Network).await;
+    Mock::given(method("PUT"))
+        .and(path("/v2/api/site/default/static-dns/abc"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "code": "api.err.InvalidPayload",
+            "details": {},
+            "errorCode": 400,
+            "message": "Invalid payload"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = setup_test_client(&mock_server.uri()).await;
+    let err = client
+        .request_typed::<_, serde_json::Value>(
+            Method::PUT,
+            &client.v2_endpoint("static-dns/abc"),
+            Some(json!({ "key": "nas.lan" })),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(http::StatusCode::BAD_REQUEST));
+    assert_eq!(err.code(), Some(&UniFiErrorCode::InvalidPayload));
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+use http::{Method, StatusCode};
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use common::{api_path, setup_probe_and_login, TestControllerKind};
+use unifi_client::{RetryPolicy, UniFiClient, UniFiError};
+
+async fn setup_retrying_client(mock_server_uri: &str, policy: RetryPolicy) -> UniFiClient {
+    UniFiClient::builder()
+        .username("test-user")
+        .password("test-password")
+        .controller_url(mock_server_uri)
+        .site("default")
+        .retry_policy(policy)
+        .build()
+        .await
+        .expect("Failed to build UniFiClient")
+}
+
+fn fast_policy() -> RetryPolicy {
+    RetryPolicy::default()
+        .max_attempts(3)
+        .initial_backoff(Duration::from_millis(1))
+        .jitter(false)
+}
+
+#[tokio::test]
+async fn test_transient_failures_are_retried_with_backoff() -> Result<(), UniFiError> {
+    // What it tests: An idempotent GET that hits 503 and 502 while the controller restarts is
+    // retried until it succeeds, within the configured number of attempts.
+    //
+    // Why it's valuable: Controller restarts and upgrades routinely produce short bursts of
+    // gateway errors; retrying them transparently keeps callers from failing spuriously.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Network).await;
+
+    let endpoint = api_path(TestControllerKind::Network, "/api/self");
+    for status in [503, 502] {
+        Mock::given(method("GET"))
+            .and(path(endpoint.as_str()))
+            .respond_with(ResponseTemplate::new(status))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path(endpoint.as_str()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "meta": { "rc": "ok" },
+            "data": []
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = setup_retrying_client(&mock_server.uri(), fast_policy()).await;
+    let result = client
+        .request_json(Method::GET, "/api/self", None::<()>)
+        .await?;
+    assert!(result.is_array());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retries_stop_after_max_attempts() -> Result<(), UniFiError> {
+    // What it tests: When every attempt fails transiently, the client gives up after
+    // `max_attempts` and returns the last response to the caller.
+    //
+    // Why it's valuable: Unbounded retries would turn an outage into a traffic storm against an
+    // already struggling controller.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Network).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/self"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+
+    let client = setup_retrying_client(&mock_server.uri(), fast_policy()).await;
+    let response = client.request(Method::GET, "/api/self", None::<()>).await?;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_non_idempotent_commands_are_not_replayed() -> Result<(), UniFiError> {
+    // What it tests: A `cmd/*` POST that receives a 503 is not replayed under the default
+    // idempotency rule; without a retry policy nothing is retried at all.
+    //
+    // Why it's valuable: Replaying commands such as `authorize-guest` could apply them twice;
+    // only requests known to be safe may be sent again.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Network).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/s/default/cmd/stamgr"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = setup_retrying_client(&mock_server.uri(), fast_policy()).await;
+    let response = client
+        .request(
+            Method::POST,
+            "/api/s/default/cmd/stamgr",
+            Some(json!({ "cmd": "authorize-guest", "mac": "00:11:22:33:44:55" })),
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    Ok(())
+}
@@ -0,0 +1,372 @@
+use http::Method;
+use serde_json::json;
+use wiremock::matchers::{body_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use common::{
+    add_auth_headers, api_path, setup_probe, setup_probe_and_login, setup_test_client,
+    TestControllerKind,
+};
+use unifi_client::secrecy::{ExposeSecret, SecretString};
+use unifi_client::{LogoutGuard, UniFiClient, UniFiClientBuilder, UniFiError, UniFiResult};
+
+const SESSION_KEY: &str = "test-session-key";
+
+fn session_test_builder(mock_server_uri: &str) -> UniFiClientBuilder {
+    UniFiClient::builder()
+        .username("test-user")
+        .password("test-password")
+        .controller_url(mock_server_uri)
+        .site("default")
+        .session_key(SESSION_KEY)
+}
+
+async fn export_test_session(mock_server_uri: &str) -> UniFiResult<SecretString> {
+    session_test_builder(mock_server_uri)
+        .build()
+        .await?
+        .export_session()
+        .await
+}
+
+async fn restore_test_client(
+    mock_server_uri: &str,
+    session: SecretString,
+) -> UniFiResult<UniFiClient> {
+    session_test_builder(mock_server_uri)
+        .restore_session(session)
+        .build()
+        .await
+}
+
+#[tokio::test]
+async fn test_restored_session_reuses_cookies_and_csrf() -> Result<(), UniFiError> {
+    // What it tests: A session exported from one client can be restored into a second client that
+    // then sends the same cookie (and, on UniFi OS, the same CSRF token) without logging in again
+    // or re-probing the controller.
+    //
+    // Why it's valuable: Short-lived jobs restore sessions to avoid creating a new controller
+    // session on every run; a second login here would defeat the purpose.
+    for &kind in &[TestControllerKind::Network, TestControllerKind::Os] {
+        let mock_server = MockServer::start().await;
+        setup_probe_and_login(&mock_server, kind).await;
+
+        let endpoint = api_path(kind, "/api/self");
+        let mock = Mock::given(method("GET")).and(path(endpoint.as_str()));
+        add_auth_headers(mock, kind)
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "meta": { "rc": "ok" },
+                "data": []
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let session = export_test_session(&mock_server.uri()).await?;
+
+        let restored = restore_test_client(&mock_server.uri(), session).await?;
+        restored
+            .request_json(Method::GET, "/api/self", None::<()>)
+            .await?;
+
+        let requests = mock_server.received_requests().await.unwrap();
+        let logins = requests
+            .iter()
+            .filter(|r| r.url.path() == kind.login_path())
+            .count();
+        let probes = requests
+            .iter()
+            .filter(|r| r.url.path() == "/api/system")
+            .count();
+        assert_eq!(logins, 1, "restoring must not log in again ({kind:?})");
+        assert_eq!(
+            probes, 1,
+            "restoring must not re-probe the controller ({kind:?})"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_restored_session_falls_back_to_login_on_401() -> Result<(), UniFiError> {
+    // What it tests: When the controller no longer accepts a restored session (401), the client
+    // performs a regular login and retries the request.
+    //
+    // Why it's valuable: Sessions expire between runs; callers should not have to handle stale
+    // snapshots themselves.
+    let mock_server = MockServer::start().await;
+    setup_probe(&mock_server, TestControllerKind::Network).await;
+
+    let login_path = TestControllerKind::Network.login_path();
+    let session = {
+        let _initial_login = Mock::given(method("POST"))
+            .and(path(login_path))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "meta": { "rc": "ok" }, "data": [] }))
+                    .insert_header("set-cookie", "unifises=stale-cookie"),
+            )
+            .expect(1)
+            .mount_as_scoped(&mock_server)
+            .await;
+        export_test_session(&mock_server.uri()).await?
+    };
+
+    Mock::given(method("GET"))
+        .and(path("/api/self"))
+        .and(header("cookie", "unifises=stale-cookie"))
+        .respond_with(ResponseTemplate::new(401))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path(login_path))
+        .and(body_json(json!({
+            "username": "test-user",
+            "password": "test-password"
+        })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "meta": { "rc": "ok" }, "data": [] }))
+                .insert_header("set-cookie", "unifises=fresh-cookie"),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/self"))
+        .and(header("cookie", "unifises=fresh-cookie"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "meta": { "rc": "ok" },
+            "data": []
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let restored = restore_test_client(&mock_server.uri(), session).await?;
+    let result = restored
+        .request_json(Method::GET, "/api/self", None::<()>)
+        .await?;
+    assert!(result.is_array());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_restore_session_rejects_other_controller_and_garbage() {
+    // What it tests: A snapshot is only accepted for the controller URL it was exported from, and
+    // malformed data is rejected with a configuration error.
+    //
+    // Why it's valuable: Sending one controller's session cookies to another host would leak
+    // credentials; failing at build time keeps snapshots bound to their origin.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Network).await;
+    let session = export_test_session(&mock_server.uri()).await.unwrap();
+
+    match restore_test_client("https://other.example:8443", session).await {
+        Err(UniFiError::ConfigurationError(msg)) => {
+            assert_eq!(
+                msg,
+                "Restored session belongs to a different controller URL"
+            )
+        }
+        other => panic!("Expected ConfigurationError, got {other:?}"),
+    }
+
+    match restore_test_client(&mock_server.uri(), "not a session".into()).await {
+        Err(UniFiError::ConfigurationError(msg)) => assert_eq!(msg, "Invalid session data"),
+        other => panic!("Expected ConfigurationError, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_exported_session_is_encrypted_and_tamper_evident() -> Result<(), UniFiError> {
+    // What it tests: An exported session does not reveal its cookie, restores only with the key
+    // it was exported with, and altered blobs are rejected; exporting or restoring without a key
+    // is a configuration error.
+    //
+    // Why it's valuable: Sessions are stored in files and caches between runs; whoever can read
+    // that storage must not be able to take over the admin session or rewrite its cookies.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Os).await;
+    let session = export_test_session(&mock_server.uri()).await?;
+    assert!(session.expose_secret().starts_with("v1."));
+    let decoded = data_encoding::BASE64URL_NOPAD
+        .decode(&session.expose_secret().as_bytes()[3..])
+        .unwrap();
+    for secret in ["test-token", "test-csrf"] {
+        assert!(!String::from_utf8_lossy(&decoded).contains(secret));
+    }
+    restore_test_client(&mock_server.uri(), session.clone()).await?;
+
+    // Flip one character of the ciphertext, near the end of the blob.
+    let mut tampered = session.expose_secret().to_string();
+    let at = tampered.len() - 5;
+    let flipped = if tampered.as_bytes()[at] == b'A' {
+        "B"
+    } else {
+        "A"
+    };
+    tampered.replace_range(at..at + 1, flipped);
+    let wrong_key = session_test_builder(&mock_server.uri())
+        .session_key("other-key")
+        .restore_session(session.clone())
+        .build()
+        .await;
+    for result in [
+        restore_test_client(&mock_server.uri(), tampered.into()).await,
+        wrong_key,
+    ] {
+        match result {
+            Err(UniFiError::ConfigurationError(msg)) => assert!(msg.contains("modified"), "{msg}"),
+            other => panic!("Expected ConfigurationError, got {other:?}"),
+        }
+    }
+
+    let without_key = setup_test_client(&mock_server.uri()).await;
+    assert!(matches!(
+        without_key.export_session().await,
+        Err(UniFiError::ConfigurationError(_))
+    ));
+    let restored_without_key = UniFiClient::builder()
+        .username("test-user")
+        .password("test-password")
+        .controller_url(mock_server.uri())
+        .restore_session(session)
+        .build()
+        .await;
+    assert!(matches!(
+        restored_without_key,
+        Err(UniFiError::ConfigurationError(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_logout_ends_session_until_next_login() -> Result<(), UniFiError> {
+    // What it tests: `logout()` posts to the kind-specific logout endpoint (with the CSRF token on
+    // UniFi OS), after which requests fail with `NotAuthenticated` without reaching the
+    // controller, and `login()` starts a new session.
+    //
+    // Why it's valuable: Services that rotate credentials or shut down must be able to close
+    // their admin session instead of leaving it to expire.
+    for &(kind, logout_path) in &[
+        (TestControllerKind::Network, "/api/logout"),
+        (TestControllerKind::Os, "/api/auth/logout"),
+    ] {
+        let mock_server = MockServer::start().await;
+        setup_probe_and_login(&mock_server, kind).await;
+
+        let logout = add_auth_headers(Mock::given(method("POST")).and(path(logout_path)), kind);
+        logout
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(api_path(kind, "/api/self").as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "meta": { "rc": "ok" },
+                "data": []
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = setup_test_client(&mock_server.uri()).await;
+        client.logout().await?;
+        // A second logout is a no-op.
+        client.logout().await?;
+
+        let result = client
+            .request_json(Method::GET, "/api/self", None::<()>)
+            .await;
+        assert!(
+            matches!(result, Err(UniFiError::NotAuthenticated)),
+            "expected NotAuthenticated after logout ({kind:?}), got {result:?}"
+        );
+        assert!(matches!(
+            client.export_session().await,
+            Err(UniFiError::NotAuthenticated)
+        ));
+
+        client.login().await?;
+        client
+            .request_json(Method::GET, "/api/self", None::<()>)
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_logout_guard_logs_out_when_last_clone_drops() -> Result<(), UniFiError> {
+    // What it tests: A `LogoutGuard` logs out only once the last clone of the client, guard or
+    // not, is dropped.
+    //
+    // Why it's valuable: Guards and plain client clones are shared across tasks; logging out while
+    // any of them is still in use would break it.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Os).await;
+    Mock::given(method("POST"))
+        .and(path("/api/auth/logout"))
+        .and(header("x-csrf-token", "test-csrf"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = session_test_builder(&mock_server.uri()).build().await?;
+    let guard = LogoutGuard::new(client.clone());
+    let guard_clone = guard.clone();
+    let scoped = guard.site_scope("lab");
+    drop(guard);
+    drop(guard_clone);
+    drop(client);
+    assert!(scoped.export_session().await.is_ok(), "still logged in");
+
+    drop(scoped);
+    for _ in 0..50 {
+        let requests = mock_server.received_requests().await.unwrap();
+        if requests.iter().any(|r| r.url.path() == "/api/auth/logout") {
+            return Ok(());
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("logout was not sent after the last clone was dropped");
+}
+
+#[tokio::test]
+async fn test_logout_guard_shutdown_logs_out_once() -> Result<(), UniFiError> {
+    // What it tests: `LogoutGuard::shutdown` logs out before returning, and the later drop of the
+    // remaining clones sends no second logout.
+    //
+    // Why it's valuable: A logout spawned on drop is lost when the runtime shuts down; services
+    // need an awaitable path to close the session before exiting.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Os).await;
+    Mock::given(method("POST"))
+        .and(path("/api/auth/logout"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = session_test_builder(&mock_server.uri()).build().await?;
+    let guard = LogoutGuard::new(client.clone());
+    guard.shutdown().await?;
+    assert!(matches!(
+        client.export_session().await,
+        Err(UniFiError::NotAuthenticated)
+    ));
+
+    drop(client);
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    Ok(())
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "testing")]
+
+use std::time::Duration;
+
+use http::{Method, StatusCode};
+use unifi_client::testing::{FakeController, Fault};
+use unifi_client::{ControllerKind, RetryPolicy, UniFiError};
+
+const KINDS: [ControllerKind; 2] = [ControllerKind::Network, ControllerKind::Os];
+
+#[tokio::test]
+async fn test_fake_controller_guest_lifecycle() -> Result<(), UniFiError> {
+    // What it tests: Guests authorized through the client show up in `stat/guest`, expire when the
+    // fake clock passes their end, and are marked unauthorized by `unauthorize-guest`.
+    //
+    // Why it's valuable: Downstream integrations test their guest flows against the fake instead
+    // of hand-written mocks, so the fake must keep state like a controller does.
+    for kind in KINDS {
+        let controller = FakeController::start(kind).await;
+        let client = controller.client().await?;
+        assert_eq!(client.controller_kind(), kind);
+        assert_eq!(client.controller_version(), Some(FakeController::VERSION));
+
+        let guests = client.guests();
+        guests
+            .authorize("00:11:22:33:44:55")
+            .duration_minutes(30)
+            .send()
+            .await?;
+        guests.authorize("66:77:88:99:aa:bb").send().await?;
+
+        controller.advance(Duration::from_secs(31 * 60));
+        let listed = guests.list().send().await?;
+        assert_eq!(listed.len(), 2);
+        assert!(listed[0].is_expired());
+        assert!(!listed[1].is_expired());
+
+        guests.unauthorize("66:77:88:99:aa:bb").send().await?;
+        let stored = controller.guests("default");
+        assert!(stored[1].is_expired() && stored[1].was_unauthorized());
+        assert!(controller.guests("other").is_empty());
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fake_controller_sessions_expire_and_csrf_rotates() -> Result<(), UniFiError> {
+    // What it tests: Expired sessions are rejected with 401 so the client logs in again, and a
+    // rotated CSRF token replaces the old one.
+    //
+    // Why it's valuable: Session handling is where integrations break in production; the fake
+    // must reproduce it to catch those bugs in tests.
+    let controller = FakeController::start(ControllerKind::Os).await;
+    controller.set_session_ttl(Duration::from_secs(3600));
+    let client = controller.client().await?;
+
+    controller.rotate_csrf();
+    client.guests().list().send().await?;
+    client.guests().list().send().await?;
+    assert_eq!(controller.login_count(), 1);
+
+    controller.advance(Duration::from_secs(3600));
+    client.guests().list().send().await?;
+    assert_eq!(controller.login_count(), 2);
+
+    controller.expire_sessions();
+    client.guests().list().send().await?;
+    assert_eq!(controller.login_count(), 3);
+
+    controller.set_credentials("admin", "changed");
+    controller.expire_sessions();
+    let result = client.guests().list().send().await;
+    assert!(matches!(result, Err(UniFiError::AuthenticationError(_))));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fake_controller_fault_injection() -> Result<(), UniFiError> {
+    // What it tests: Injected faults fail the chosen number of matching requests and are then
+    // cleared, and delays trigger client timeouts.
+    //
+    // Why it's valuable: Retry and error handling can only be tested if failures can be produced
+    // on demand.
+    let controller = FakeController::start(ControllerKind::Network).await;
+    let client = controller
+        .client_builder()
+        .retry_policy(
+            RetryPolicy::default()
+                .initial_backoff(Duration::from_millis(1))
+                .jitter(false),
+        )
+        .timeout(Duration::from_millis(200))
+        .build()
+        .await?;
+
+    controller.inject_fault(Fault::status(503).path("/stat/guest").times(2));
+    assert!(client.guests().list().send().await?.is_empty());
+
+    controller.inject_fault(Fault::status(500));
+    let result = client
+        .request_json(Method::GET, "/api/self", None::<()>)
+        .await;
+    assert_eq!(
+        result.unwrap_err().status(),
+        Some(StatusCode::INTERNAL_SERVER_ERROR)
+    );
+
+    controller.inject_fault(Fault::delay(Duration::from_secs(1)).times(3));
+    let result = client.guests().list().send().await;
+    assert!(matches!(result, Err(UniFiError::HttpError(ref e)) if e.is_timeout()));
+
+    controller.inject_fault(Fault::status(401).path("/api/login"));
+    controller.expire_sessions();
+    assert!(client.guests().list().send().await.is_err());
+    assert!(client.guests().list().send().await.is_ok());
+    Ok(())
+}
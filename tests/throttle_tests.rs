@@ -0,0 +1,121 @@
+use std::time::{Duration, Instant};
+
+use http::Method;
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use common::{api_path, setup_probe_and_login, TestControllerKind};
+use unifi_client::{RateLimit, ThrottleDelay, UniFiClient, UniFiError};
+
+async fn mount_self_endpoint(mock_server: &MockServer, delay: Duration) {
+    let endpoint = api_path(TestControllerKind::Network, "/api/self");
+    Mock::given(method("GET"))
+        .and(path(endpoint.as_str()))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "meta": { "rc": "ok" }, "data": [] }))
+                .set_delay(delay),
+        )
+        .mount(mock_server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_rate_limit_is_shared_across_clones() -> Result<(), UniFiError> {
+    // What it tests: With a 20 requests/second limit and a burst of 1, five requests issued from
+    // clones of the same client are spaced out, and later requests report how long they waited.
+    //
+    // Why it's valuable: Fan-out from many tasks shares one client; the limit must apply to the
+    // controller as a whole, not per clone, or small controllers get overwhelmed.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Network).await;
+    mount_self_endpoint(&mock_server, Duration::ZERO).await;
+
+    let client = UniFiClient::builder()
+        .username("test-user")
+        .password("test-password")
+        .controller_url(mock_server.uri())
+        .rate_limit(RateLimit::per_second(20).burst(1))
+        .build()
+        .await?;
+
+    let start = Instant::now();
+    let mut delays = Vec::new();
+    for _ in 0..5 {
+        let response = client
+            .clone()
+            .request(Method::GET, "/api/self", None::<()>)
+            .await?;
+        let delay = response
+            .extensions()
+            .get::<ThrottleDelay>()
+            .copied()
+            .expect("Throttle delay is attached to responses");
+        delays.push(delay.0);
+    }
+
+    // Four of the five requests must wait ~50ms each for a token.
+    assert!(start.elapsed() >= Duration::from_millis(180));
+    assert_eq!(delays[0], Duration::ZERO);
+    assert!(delays.iter().sum::<Duration>() >= Duration::from_millis(100));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_max_concurrent_requests_caps_in_flight() -> Result<(), UniFiError> {
+    // What it tests: With at most two requests in flight and a controller that takes 100ms per
+    // response, four concurrent requests complete in two waves.
+    //
+    // Why it's valuable: Bounding in-flight requests protects the controller from bursts that a
+    // rate limit alone would still allow.
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Network).await;
+    mount_self_endpoint(&mock_server, Duration::from_millis(100)).await;
+
+    let client = UniFiClient::builder()
+        .username("test-user")
+        .password("test-password")
+        .controller_url(mock_server.uri())
+        .max_concurrent_requests(2)
+        .build()
+        .await?;
+
+    let start = Instant::now();
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move {
+                client
+                    .request_json(Method::GET, "/api/self", None::<()>)
+                    .await
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.await.expect("task panicked")?;
+    }
+
+    assert!(start.elapsed() >= Duration::from_millis(200));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invalid_limits_are_rejected() {
+    // What it tests: Zero rate limits and concurrency caps fail at build time.
+    //
+    // Why it's valuable: A zero limit would block every request forever instead of surfacing the
+    // misconfiguration.
+    let result = UniFiClient::builder()
+        .username("test-user")
+        .password("test-password")
+        .controller_url("https://controller.example")
+        .max_concurrent_requests(0)
+        .build()
+        .await;
+    assert!(matches!(result, Err(UniFiError::ConfigurationError(_))));
+}
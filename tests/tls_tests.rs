@@ -0,0 +1,193 @@
+use std::sync::Arc;
+
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use unifi_client::{UniFiClient, UniFiError};
+
+const CERT_PEM: &[u8] = include_bytes!("fixtures/controller-cert.pem");
+const CERT_DER: &[u8] = include_bytes!("fixtures/controller-cert.der");
+const KEY_PEM: &[u8] = include_bytes!("fixtures/controller-key.pem");
+#[cfg(feature = "tls-pinning")]
+const CERT_SHA256: &str =
+    "D8:F5:E6:D1:CE:F0:2D:30:C2:43:6F:D8:E4:73:5A:D0:27:4B:C0:27:71:79:D5:0D:7F:05:EE:0E:36:74:2A:D2";
+
+/// Starts an HTTPS server with the self-signed fixture certificate that answers every request like
+/// a UniFi OS console (200 probe, successful login), and returns its port.
+async fn start_tls_controller() -> u16 {
+    let cert = CertificateDer::from_pem_slice(CERT_PEM).unwrap();
+    let key = PrivateKeyDer::from_pem_slice(KEY_PEM).unwrap();
+    let config = rustls::ServerConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .unwrap()
+    .with_no_client_auth()
+    .with_single_cert(vec![cert], key)
+    .unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        loop {
+            let Ok((stream, _)) = listener.accept().await else {
+                return;
+            };
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                // Handshake failures are expected when the client rejects the certificate.
+                let Ok(mut stream) = acceptor.accept(stream).await else {
+                    return;
+                };
+                let mut buf = vec![0u8; 8192];
+                let Ok(n) = stream.read(&mut buf).await else {
+                    return;
+                };
+                let body = if buf[..n].starts_with(b"HEAD") {
+                    ""
+                } else {
+                    "{}"
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\n\
+                     Set-Cookie: TOKEN=test-cookie; Path=/\r\n\
+                     X-CSRF-Token: test-csrf\r\n\
+                     Content-Type: application/json\r\n\
+                     Content-Length: 2\r\n\
+                     Connection: close\r\n\r\n{body}"
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+
+    port
+}
+
+fn client_builder(port: u16) -> unifi_client::UniFiClientBuilder {
+    UniFiClient::builder()
+        .controller_url(format!("https://localhost:{port}"))
+        .username("test-user")
+        .password("test-password")
+}
+
+#[tokio::test]
+async fn test_self_signed_certificate_is_rejected_by_default() {
+    // What it tests: Without extra trust configuration, a self-signed controller certificate fails
+    // verification.
+    //
+    // Why it's valuable: Confirms the tests below exercise the trust options rather than a lax
+    // default.
+    let port = start_tls_controller().await;
+    let result = client_builder(port).build().await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_custom_root_certificate_is_trusted() -> Result<(), UniFiError> {
+    // What it tests: Adding the controller's certificate as a root (PEM or DER) lets the client
+    // connect with verification on.
+    //
+    // Why it's valuable: Self-signed controllers no longer require `accept_invalid_certs`.
+    let port = start_tls_controller().await;
+    client_builder(port)
+        .add_root_certificate_pem(CERT_PEM)
+        .build()
+        .await?;
+    client_builder(port)
+        .add_root_certificate_der(CERT_DER)
+        .build()
+        .await?;
+
+    let invalid = client_builder(port)
+        .add_root_certificate_pem(&b"not a certificate"[..])
+        .build()
+        .await;
+    assert!(matches!(invalid, Err(UniFiError::ConfigurationError(_))));
+
+    Ok(())
+}
+
+#[cfg(feature = "tls-pinning")]
+#[tokio::test]
+async fn test_certificate_pinning() -> Result<(), UniFiError> {
+    // What it tests: A matching SHA-256 pin is accepted, a different pin aborts the handshake
+    // before login, and malformed pins are rejected at build time.
+    //
+    // Why it's valuable: Pinning keeps verification on for self-signed controllers and stops
+    // credentials from being sent to an impostor.
+    let port = start_tls_controller().await;
+
+    let client = client_builder(port)
+        .pin_certificate_sha256(CERT_SHA256)
+        .build()
+        .await?;
+    assert_eq!(
+        client.pinned_certificate_sha256().as_deref(),
+        Some(CERT_SHA256)
+    );
+
+    let wrong_pin = "00".repeat(32);
+    let result = client_builder(port)
+        .pin_certificate_sha256(wrong_pin)
+        .build()
+        .await;
+    assert!(matches!(result, Err(UniFiError::HttpError(_))));
+
+    let malformed = client_builder(port)
+        .pin_certificate_sha256("D8:F5")
+        .build()
+        .await;
+    assert!(matches!(malformed, Err(UniFiError::ConfigurationError(_))));
+
+    Ok(())
+}
+
+#[cfg(feature = "tls-pinning")]
+#[tokio::test]
+async fn test_trust_on_first_use_learns_fingerprint() -> Result<(), UniFiError> {
+    // What it tests: With trust-on-first-use and no pin, the first certificate seen is accepted
+    // and its fingerprint is exposed for persisting.
+    //
+    // Why it's valuable: Operators can bootstrap pinning without copying fingerprints by hand.
+    let port = start_tls_controller().await;
+
+    let client = client_builder(port)
+        .trust_on_first_use(true)
+        .build()
+        .await?;
+    assert_eq!(
+        client.pinned_certificate_sha256().as_deref(),
+        Some(CERT_SHA256)
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "tls-pinning")]
+#[tokio::test]
+async fn test_certificate_pinning_rejects_root_certificates() {
+    // What it tests: Configuring a pin or trust-on-first-use together with root certificates
+    // fails the build with a configuration error.
+    //
+    // Why it's valuable: Pinned connections ignore root certificates; dropping them silently
+    // would hide a misconfiguration.
+    let pinned = client_builder(443)
+        .add_root_certificate_pem(CERT_PEM)
+        .pin_certificate_sha256(CERT_SHA256)
+        .build()
+        .await;
+    assert!(matches!(pinned, Err(UniFiError::ConfigurationError(_))));
+
+    let tofu = client_builder(443)
+        .add_root_certificate_der(CERT_DER)
+        .trust_on_first_use(true)
+        .build()
+        .await;
+    assert!(matches!(tofu, Err(UniFiError::ConfigurationError(_))));
+}
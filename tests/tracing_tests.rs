@@ -0,0 +1,140 @@
+#![cfg(feature = "tracing")]
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use http::Method;
+use serde_json::json;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{Layer, Registry};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use common::{api_path, setup_probe_and_login, setup_test_client, TestControllerKind};
+use unifi_client::UniFiError;
+
+/// A span name and its recorded fields.
+type SpanData = (String, HashMap<String, String>);
+
+/// Collects spans and their fields so tests can assert on them.
+#[derive(Clone, Default)]
+struct SpanRecorder {
+    spans: Arc<Mutex<Vec<(Id, SpanData)>>>,
+}
+
+impl SpanRecorder {
+    fn spans_named(&self, name: &str) -> Vec<HashMap<String, String>> {
+        self.spans
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, (n, _))| n == name)
+            .map(|(_, (_, fields))| fields.clone())
+            .collect()
+    }
+}
+
+struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+}
+
+impl<S> Layer<S> for SpanRecorder
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _ctx: Context<'_, S>) {
+        let mut fields = HashMap::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        self.spans
+            .lock()
+            .unwrap()
+            .push((id.clone(), (attrs.metadata().name().to_string(), fields)));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+        let mut spans = self.spans.lock().unwrap();
+        // Span ids are reused after close, so the latest span with this id is the live one.
+        if let Some((_, (_, fields))) = spans.iter_mut().rev().find(|(span_id, _)| span_id == id) {
+            values.record(&mut FieldVisitor(fields));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_request_login_and_reauth_spans() -> Result<(), UniFiError> {
+    // What it tests: Logins, requests and the re-authentication triggered by a 401 produce spans
+    // carrying the endpoint, site, controller kind, status, attempt, re-auth count and auth
+    // epoch, and the leader role of the re-login; the password never appears in any field.
+    //
+    // Why it's valuable: Distributed traces are only useful if controller calls show up with
+    // enough context to attribute latency, and they must never leak credentials.
+    let recorder = SpanRecorder::default();
+    let _guard = tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+
+    let mock_server = MockServer::start().await;
+    setup_probe_and_login(&mock_server, TestControllerKind::Os).await;
+
+    let endpoint = api_path(TestControllerKind::Os, "/api/self");
+    Mock::given(method("GET"))
+        .and(path(endpoint.as_str()))
+        .respond_with(ResponseTemplate::new(401))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(endpoint.as_str()))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "meta": { "rc": "ok" }, "data": [] })),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = setup_test_client(&mock_server.uri()).await;
+    client
+        .request_json(Method::GET, "/api/self", None::<()>)
+        .await?;
+
+    let logins = recorder.spans_named("unifi.login");
+    assert_eq!(logins.len(), 2, "initial login and re-login");
+    assert_eq!(logins[0]["controller_kind"], "Os");
+    assert_eq!(logins[0]["status"], "200");
+
+    let requests = recorder.spans_named("unifi.request");
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request["method"], "GET");
+    assert_eq!(request["endpoint"], endpoint);
+    assert_eq!(request["site"], "default");
+    assert_eq!(request["status"], "200");
+    assert_eq!(request["reauth"], "1");
+    assert_eq!(request["auth_epoch"], "2");
+
+    let reauths = recorder.spans_named("unifi.reauth");
+    assert_eq!(reauths.len(), 1);
+    assert_eq!(reauths[0]["role"], "leader");
+    assert_eq!(reauths[0]["auth_epoch"], "1");
+
+    let spans = recorder.spans.lock().unwrap();
+    assert!(spans
+        .iter()
+        .flat_map(|(_, (_, fields))| fields.values())
+        .all(|value| !value.contains("test-password")));
+
+    Ok(())
+}
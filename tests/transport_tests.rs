@@ -0,0 +1,134 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use http::{HeaderMap, HeaderValue, Method, StatusCode};
+use serde_json::json;
+use unifi_client::{
+    ControllerKind, Response, RetryPolicy, Transport, TransportError, TransportErrorKind,
+    TransportRequest, UniFiClient, UniFiError, UniFiResult,
+};
+
+/// An in-process UniFi OS controller that refuses the first connection to `/api/self`.
+#[derive(Debug, Default)]
+struct FakeController {
+    requests: Mutex<Vec<TransportRequest>>,
+    self_calls: AtomicUsize,
+}
+
+fn json_response(status: StatusCode, headers: HeaderMap, body: serde_json::Value) -> Response {
+    Response::new(status, headers, body.to_string())
+}
+
+#[async_trait]
+impl Transport for FakeController {
+    async fn send(&self, request: TransportRequest) -> UniFiResult<Response> {
+        self.requests.lock().unwrap().push(request.clone());
+        match (request.method().as_str(), request.url().path()) {
+            ("POST", "/api/auth/login") => {
+                let mut headers = HeaderMap::new();
+                headers.insert("set-cookie", HeaderValue::from_static("TOKEN=fake"));
+                headers.insert("x-csrf-token", HeaderValue::from_static("fake-csrf"));
+                Ok(json_response(StatusCode::OK, headers, json!({})))
+            }
+            ("GET", "/proxy/network/api/self") => {
+                if self.self_calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    return Err(TransportError::new(TransportErrorKind::Connect, "refused").into());
+                }
+                Ok(json_response(
+                    StatusCode::OK,
+                    HeaderMap::new(),
+                    json!({ "meta": { "rc": "ok" }, "data": [{ "name": "fake" }] }),
+                ))
+            }
+            _ => Ok(Response::new(StatusCode::NOT_FOUND, HeaderMap::new(), "")),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_custom_transport_reuses_auth_csrf_and_retries() -> Result<(), UniFiError> {
+    // What it tests: A client built on a custom transport logs in, sends the CSRF token, and
+    // retries a connection failure reported as a `TransportError`, without any HTTP server.
+    //
+    // Why it's valuable: Fakes and recording transports only help if they exercise the same
+    // authentication and retry logic as the reqwest transport.
+    let transport = Arc::new(FakeController::default());
+    let client = UniFiClient::builder()
+        .controller_url("https://fake.invalid")
+        .username("test-user")
+        .password("test-password")
+        .controller_kind(ControllerKind::Os)
+        .retry_policy(
+            RetryPolicy::default()
+                .initial_backoff(Duration::from_millis(1))
+                .jitter(false),
+        )
+        .transport(Arc::clone(&transport))
+        .session_key("test-session-key")
+        .build()
+        .await?;
+
+    let data = client
+        .request_json(Method::GET, "/api/self", None::<()>)
+        .await?;
+    assert_eq!(data[0]["name"], json!("fake"));
+
+    {
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests.len(), 3, "login plus two attempts");
+        let login = &requests[0];
+        assert_eq!(
+            login.headers().get("content-type").unwrap(),
+            "application/json"
+        );
+        let login_body: serde_json::Value = serde_json::from_slice(login.body().unwrap()).unwrap();
+        assert_eq!(login_body["username"], json!("test-user"));
+        for attempt in &requests[1..] {
+            assert_eq!(attempt.headers().get("x-csrf-token").unwrap(), "fake-csrf");
+        }
+    }
+
+    // The fake does not expose cookies, so there is no session to export.
+    assert!(matches!(
+        client.export_session().await,
+        Err(UniFiError::ConfigurationError(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_custom_transport_rejects_tls_settings() {
+    // What it tests: TLS options that only the reqwest transport understands are rejected when a
+    // custom transport is configured.
+    //
+    // Why it's valuable: Silently ignoring a trusted root or certificate pin would change
+    // certificate validation unnoticed.
+    let result = UniFiClient::builder()
+        .controller_url("https://fake.invalid")
+        .username("test-user")
+        .password("test-password")
+        .add_root_certificate_pem(&b"-----BEGIN CERTIFICATE-----"[..])
+        .transport(FakeController::default())
+        .build()
+        .await;
+    assert!(matches!(result, Err(UniFiError::ConfigurationError(_))));
+}
+
+#[test]
+fn test_transport_connect_and_timeout_errors_are_retryable() {
+    // What it tests: `is_retryable` treats connection failures and timeouts reported by a custom
+    // transport like those reported by reqwest.
+    //
+    // Why it's valuable: Callers decide whether to retry from `is_retryable`, whichever
+    // transport the client uses.
+    for (kind, retryable) in [
+        (TransportErrorKind::Connect, true),
+        (TransportErrorKind::Timeout, true),
+        (TransportErrorKind::Other, false),
+    ] {
+        let error = UniFiError::from(TransportError::new(kind, "failed"));
+        assert_eq!(error.is_retryable(), retryable, "{kind:?}");
+    }
+}